@@ -0,0 +1,56 @@
+//! Collect of a refresh with the proofs verified serially and in parallel.
+//!
+//! The verification of the proofs grows with the square of the committee size while the
+//! decryption of the new share is a single operation, so the larger the committee the more of a
+//! collect runs on the thread pool with `collect_parallel`, and the decryption stays on the
+//! calling thread in both cases. Run with `cargo bench --features parallel,sim`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use curv::elliptic::curves::Secp256k1;
+use fs_dkr::refresh_message::{DecryptionKey, RefreshMessage};
+use fs_dkr::sim::Network;
+use fs_dkr::M_SECURITY;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use sha2::Sha256;
+
+type Message = RefreshMessage<Secp256k1, Sha256, M_SECURITY>;
+
+fn round(t: u16, n: u16) -> (LocalKey<Secp256k1>, DecryptionKey, Vec<Message>) {
+    let mut keys = Network::<M_SECURITY>::keygen(t, n).unwrap().into_keys();
+    let (refresh_messages, mut new_dks): (Vec<Message>, Vec<DecryptionKey>) = keys
+        .iter_mut()
+        .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+        .unzip();
+    (keys.remove(0), new_dks.remove(0), refresh_messages)
+}
+
+fn collect(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collect");
+    group.sample_size(10);
+    for (t, n) in [(1, 3), (2, 5), (4, 9)] {
+        let (key, new_dk, refresh_messages) = round(t, n);
+        group.bench_with_input(BenchmarkId::new("serial", n), &n, |b, _| {
+            b.iter_batched(
+                || (key.clone(), new_dk.clone()),
+                |(mut key, new_dk)| {
+                    RefreshMessage::collect(&refresh_messages, &mut key, new_dk, &[]).unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", n), &n, |b, _| {
+            b.iter_batched(
+                || (key.clone(), new_dk.clone()),
+                |(mut key, new_dk)| {
+                    RefreshMessage::collect_parallel(&refresh_messages, &mut key, new_dk, &[])
+                        .unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, collect);
+criterion_main!(benches);
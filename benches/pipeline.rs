@@ -0,0 +1,146 @@
+//! The stages of a refresh, from distribute to the end-to-end collect of an existing party.
+//!
+//! Every stage is measured for committees of 3, 5, 10 and 25 parties, each at the largest
+//! threshold within the honest majority distribute enforces. Distribute is dominated by the
+//! generation of the new Paillier key and by the proofs, it is also measured with a 3072 bit
+//! key through a [PaillierBackend] generating larger keys. Collect only accepts moduli of
+//! [PAILLIER_KEY_SIZE](fs_dkr::PAILLIER_KEY_SIZE) bits, the later stages run at that size only.
+//! The ciphertext sum is the homomorphic sum a party decrypts into its new share, and the public
+//! shares are accumulated into `pk_vec` and interpolated into the public key by
+//! `collect_public_only`. Run with `cargo bench --bench pipeline`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use curv::elliptic::curves::Secp256k1;
+use curv::BigInt;
+use fs_dkr::error::FsDkrResult;
+use fs_dkr::paillier_backend::{KzenPaillier, PaillierBackend};
+use fs_dkr::params::{ShareCount, Threshold};
+use fs_dkr::refresh_message::{DecryptionKey, RefreshMessage};
+use fs_dkr::M_SECURITY;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::{
+    Keygen, LocalKey,
+};
+use paillier::EncryptionKey;
+use round_based::dev::Simulation;
+use sha2::Sha256;
+use zk_paillier::zkproofs::NiCorrectKeyProof;
+
+type Message = RefreshMessage<Secp256k1, Sha256, M_SECURITY>;
+
+const COMMITTEES: [(u16, u16); 4] = [(1, 3), (2, 5), (4, 10), (12, 25)];
+
+/// [KzenPaillier] generating keys of 3072 bits whatever size is asked for.
+struct Modulus3072;
+
+impl PaillierBackend for Modulus3072 {
+    fn keypair(_modulus_bits: usize) -> (EncryptionKey, DecryptionKey) {
+        KzenPaillier::keypair(3072)
+    }
+
+    fn encrypt(ek: &EncryptionKey, plaintext: &BigInt, randomness: &BigInt) -> BigInt {
+        KzenPaillier::encrypt(ek, plaintext, randomness)
+    }
+
+    fn add(ek: &EncryptionKey, c1: &BigInt, c2: &BigInt) -> BigInt {
+        KzenPaillier::add(ek, c1, c2)
+    }
+
+    fn mul(ek: &EncryptionKey, c: &BigInt, k: &BigInt) -> BigInt {
+        KzenPaillier::mul(ek, c, k)
+    }
+
+    fn decrypt(
+        dk: &DecryptionKey,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        party_index: u16,
+    ) -> FsDkrResult<BigInt> {
+        KzenPaillier::decrypt(dk, ek, c, party_index)
+    }
+
+    fn prove_correct_key(dk: &DecryptionKey) -> NiCorrectKeyProof {
+        KzenPaillier::prove_correct_key(dk)
+    }
+
+    fn verify_correct_key(ek: &EncryptionKey, proof: &NiCorrectKeyProof) -> bool {
+        KzenPaillier::verify_correct_key(ek, proof)
+    }
+}
+
+fn committee(t: u16, n: u16) -> String {
+    format!("{}-of-{}", t, n)
+}
+
+fn keygen(t: u16, n: u16) -> Vec<LocalKey<Secp256k1>> {
+    let mut simulation = Simulation::new();
+    simulation.enable_benchmarks(false);
+    for i in 1..=n {
+        simulation.add_party(Keygen::new(i, t, n).unwrap());
+    }
+    simulation.run().unwrap()
+}
+
+fn round(t: u16, n: u16) -> (LocalKey<Secp256k1>, DecryptionKey, Vec<Message>) {
+    let mut keys = keygen(t, n);
+    let (refresh_messages, mut new_dks): (Vec<Message>, Vec<DecryptionKey>) = keys
+        .iter_mut()
+        .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+        .unzip();
+    (keys.remove(0), new_dks.remove(0), refresh_messages)
+}
+
+fn distribute(c: &mut Criterion) {
+    let mut group = c.benchmark_group("distribute");
+    group.sample_size(10);
+    for (t, n) in COMMITTEES {
+        let key = keygen(t, n).remove(0);
+        group.bench_with_input(BenchmarkId::new("2048", committee(t, n)), &n, |b, n| {
+            b.iter_batched(
+                || key.clone(),
+                |mut key| Message::distribute_with_backend::<KzenPaillier>(key.i, &mut key, *n),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("3072", committee(t, n)), &n, |b, n| {
+            b.iter_batched(
+                || key.clone(),
+                |mut key| Message::distribute_with_backend::<Modulus3072>(key.i, &mut key, *n),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn collect_stages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("collect_stages");
+    group.sample_size(10);
+    for (t, n) in COMMITTEES {
+        let (key, new_dk, refresh_messages) = round(t, n);
+        let ek = key.paillier_key_vec[(key.i - 1) as usize].clone();
+        group.bench_with_input(
+            BenchmarkId::new("ciphertext_sum", committee(t, n)),
+            &t,
+            |b, t| b.iter(|| Message::ciphertext_sum(&refresh_messages, key.i, *t, &ek).unwrap()),
+        );
+        group.bench_with_input(BenchmarkId::new("pk_vec", committee(t, n)), &n, |b, n| {
+            b.iter(|| {
+                Message::collect_public_only(&refresh_messages, Threshold(t), ShareCount(*n))
+                    .unwrap()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("collect", committee(t, n)), &n, |b, _| {
+            b.iter_batched(
+                || (key.clone(), new_dk.clone()),
+                |(mut key, new_dk)| {
+                    RefreshMessage::collect(&refresh_messages, &mut key, new_dk, &[]).unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, distribute, collect_stages);
+criterion_main!(benches);
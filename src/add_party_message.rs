@@ -10,19 +10,30 @@
 //! existing keys taking into the account the join messages that they received.
 //! ** All parties (including new ones) collect the refresh messages and the join messages.
 
+use crate::decryption::DecryptionContext;
+use crate::dlog_pool::{DLogStatementPool, DLogStatementProofs};
+use crate::entropy::Entropy;
 use crate::error::{FsDkrError, FsDkrResult};
-use crate::refresh_message::RefreshMessage;
-use curv::arithmetic::{BasicOps, Modulo, One, Samplable, Zero};
-use curv::cryptographic_primitives::hashing::Digest;
+use crate::freshness::Freshness;
+use crate::paillier_backend::{KzenPaillier, PaillierBackend};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::{RefreshMessage, ShareDecryption};
+use crate::reshuffle::Reshuffle;
+use crate::signed_message::SignedJoinMessage;
+use crate::strictness::Strictness;
+use curv::arithmetic::{BasicOps, BitManipulation, Modulo, One, Zero};
+use curv::cryptographic_primitives::hashing::{Digest, DigestExt};
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
     ShamirSecretSharing, VerifiableSS,
 };
 use curv::elliptic::curves::{Curve, Point, Scalar};
-use curv::BigInt;
+use curv::{BigInt, HashChoice};
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::SharedKeys;
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
-use paillier::{Decrypt, EncryptionKey, KeyGeneration, Paillier};
+use paillier::{DecryptionKey, EncryptionKey};
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -30,10 +41,21 @@ use zk_paillier::zkproofs::{CompositeDLogProof, DLogStatement, NiCorrectKeyProof
 
 use crate::ring_pedersen_proof::{RingPedersenProof, RingPedersenStatement};
 
+/// Version of the [JoinMessage] wire format produced by this crate, bumped on every
+/// incompatible change to the encoding.
+pub const JOIN_MESSAGE_VERSION: u16 = 1;
+
+/// Number of samples [JoinMessage::distribute] draws for the exponent relating `h1` and `h2`
+/// before giving up. A sample is rejected when it is not invertible modulo `phi(N tilde)`, which
+/// happens for about half of them, so the default only fails on a broken random number
+/// generator.
+pub const KEYGEN_RETRIES: usize = 256;
+
 /// Message used by new parties to join the protocol.
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[serde(bound = "E: Curve, H: Digest + Clone")]
 pub struct JoinMessage<E: Curve, H: Digest + Clone, const M: usize> {
+    pub(crate) version: u16,
     pub(crate) ek: EncryptionKey,
     pub(crate) dk_correctness_proof: NiCorrectKeyProof,
     pub(crate) party_index: Option<u16>,
@@ -42,33 +64,153 @@ pub struct JoinMessage<E: Curve, H: Digest + Clone, const M: usize> {
     pub(crate) composite_dlog_proof_base_h2: CompositeDLogProof,
     pub(crate) ring_pedersen_statement: RingPedersenStatement<E, H>,
     pub(crate) ring_pedersen_proof: RingPedersenProof<E, H, M>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) freshness: Option<Freshness>,
+}
+
+/// The version of an encoded [JoinMessage], read before the rest of the message so that a
+/// message of another version is reported as such instead of as a parse failure. Messages
+/// encoded before the version was introduced carry none and are read as version 0.
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    version: u16,
+}
+
+/// Compact, log safe description of a [JoinMessage], see [JoinMessage::redacted]. The proofs
+/// are identified by the first 8 bytes of the SHA-256 digest of their encoding, hex encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinMessageSummary {
+    pub party_index: Option<u16>,
+    /// Bit length of the Paillier modulus `ek.n`.
+    pub ek_bit_length: usize,
+    pub dk_correctness_proof_hash: String,
+    pub composite_dlog_proofs_hash: String,
+    pub ring_pedersen_proof_hash: String,
+}
+
+/// Bounds on the bit length of the Paillier modulus of a party admitted to a committee, see
+/// [JoinMessage::validate_for_committee]. The default bounds are the ones collect enforces, a
+/// policy can only narrow them: a modulus outside of the default bounds is rejected by collect
+/// whatever the policy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyPolicy {
+    pub min_modulus_bits: usize,
+    pub max_modulus_bits: usize,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        KeyPolicy {
+            min_modulus_bits: crate::PAILLIER_KEY_SIZE - 1,
+            max_modulus_bits: crate::PAILLIER_KEY_SIZE,
+        }
+    }
+}
+
+/// Hex encoding of the first 8 bytes of the SHA-256 digest of the encoding of `value`.
+fn short_hash<T: Serialize>(value: &T) -> String {
+    let encoded = serde_json::to_vec(value).unwrap_or_default();
+    sha2::Sha256::digest(&encoded)[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Verifies the Paillier key material of a candidate party before it is invited to join: the
+/// proof that `ek` was generated correctly and the size of its modulus. These are the checks
+/// collect runs on the `ek` and `dk_correctness_proof` of a [JoinMessage], so that a candidate
+/// can be vetted out-of-band from just its key. The candidate has no index yet, the errors
+/// report party index 0.
+pub fn verify_paillier_key(ek: &EncryptionKey, proof: &NiCorrectKeyProof) -> FsDkrResult<()> {
+    verify_paillier_key_of(ek, proof, 0)
+}
+
+/// Same as [verify_paillier_key], reporting `party_index` in the errors.
+pub(crate) fn verify_paillier_key_of(
+    ek: &EncryptionKey,
+    proof: &NiCorrectKeyProof,
+    party_index: u16,
+) -> FsDkrResult<()> {
+    verify_paillier_key_with::<KzenPaillier>(ek, proof, party_index)
+}
+
+/// Same as [verify_paillier_key_of], verifying the proof with `B`.
+pub(crate) fn verify_paillier_key_with<B: PaillierBackend>(
+    ek: &EncryptionKey,
+    proof: &NiCorrectKeyProof,
+    party_index: u16,
+) -> FsDkrResult<()> {
+    if !B::verify_correct_key(ek, proof) {
+        return Err(FsDkrError::PaillierVerificationError { party_index });
+    }
+    verify_paillier_key_size_of(ek, party_index)
+}
+
+/// Checks that the modulus of `ek` has the size collect expects, reporting `party_index` in the
+/// error.
+pub(crate) fn verify_paillier_key_size_of(ek: &EncryptionKey, party_index: u16) -> FsDkrResult<()> {
+    let n_length = ek.n.bit_length();
+    if n_length > crate::PAILLIER_KEY_SIZE || n_length < crate::PAILLIER_KEY_SIZE - 1 {
+        return Err(FsDkrError::ModuliTooSmall {
+            party_index,
+            moduli_size: n_length,
+        });
+    }
+    Ok(())
 }
 
 /// Generates the parameters needed for the h1_h2_N_tilde_vec. These parameters can be seen as
 /// environment variables for each party that they agree on. In this case, each new party generates
-/// it's own DlogStatements and submits it's proofs
-fn generate_h1_h2_n_tilde() -> (BigInt, BigInt, BigInt, BigInt, BigInt) {
-    let (ek_tilde, dk_tilde) = Paillier::keypair_with_modulus_size(crate::PAILLIER_KEY_SIZE).keys();
+/// it's own DlogStatements and submits it's proofs. The modulus and the exponents are drawn from
+/// `entropy`. Fails with [FsDkrError::KeyGenerationFailed] if none of `max_attempts` samples of
+/// the exponent is invertible.
+fn generate_h1_h2_n_tilde(
+    max_attempts: usize,
+    entropy: &mut Entropy,
+) -> FsDkrResult<(BigInt, BigInt, BigInt, BigInt, BigInt)> {
+    let (ek_tilde, dk_tilde) =
+        entropy.paillier_keypair::<KzenPaillier>(crate::PAILLIER_KEY_SIZE)?;
+    crate::validate_generated_modulus(&ek_tilde.n, crate::PAILLIER_KEY_SIZE)?;
     let one = BigInt::one();
     let phi = (&dk_tilde.p - &one) * (&dk_tilde.q - &one);
-    let h1 = BigInt::sample_below(&ek_tilde.n);
-    let (mut xhi, mut xhi_inv) = loop {
-        let xhi_ = BigInt::sample_below(&phi);
-        match BigInt::mod_inv(&xhi_, &phi) {
-            Some(inv) => break (xhi_, inv),
-            None => continue,
+    let h1 = entropy.sample_below(&ek_tilde.n)?;
+    let mut exponents = None;
+    for _ in 0..max_attempts {
+        let xhi_ = entropy.sample_below(&phi)?;
+        if let Some(inv) = BigInt::mod_inv(&xhi_, &phi) {
+            exponents = Some((xhi_, inv));
+            break;
         }
-    };
+    }
+    let (mut xhi, mut xhi_inv) = exponents.ok_or(FsDkrError::KeyGenerationFailed {
+        attempts: max_attempts,
+    })?;
     let h2 = BigInt::mod_pow(&h1, &xhi, &ek_tilde.n);
     xhi = BigInt::sub(&phi, &xhi);
     xhi_inv = BigInt::sub(&phi, &xhi_inv);
-    (ek_tilde.n, h1, h2, xhi, xhi_inv)
+    Ok((ek_tilde.n, h1, h2, xhi, xhi_inv))
 }
 
 /// Generates the DlogStatement and CompositeProofs using the parameters generated by [generate_h1_h2_n_tilde]
-fn generate_dlog_statement_proofs() -> (DLogStatement, CompositeDLogProof, CompositeDLogProof) {
-    let (n_tilde, h1, h2, xhi, xhi_inv) = generate_h1_h2_n_tilde();
+pub(crate) fn generate_dlog_statement_proofs(
+    max_attempts: usize,
+) -> FsDkrResult<DLogStatementProofs> {
+    let mut entropy = Entropy::Os;
+    let (n_tilde, h1, h2, xhi, xhi_inv) = generate_h1_h2_n_tilde(max_attempts, &mut entropy)?;
+    dlog_statement_proofs(n_tilde, h1, h2, &xhi, &xhi_inv, &mut entropy)
+}
 
+/// Builds the DlogStatement over `n_tilde` and proves both of its directions, drawing the nonces
+/// of the composite dlog proofs from `entropy`.
+fn dlog_statement_proofs(
+    n_tilde: BigInt,
+    h1: BigInt,
+    h2: BigInt,
+    xhi: &BigInt,
+    xhi_inv: &BigInt,
+    entropy: &mut Entropy,
+) -> FsDkrResult<DLogStatementProofs> {
     let dlog_statement_base_h1 = DLogStatement {
         N: n_tilde.clone(),
         g: h1.clone(),
@@ -81,14 +223,130 @@ fn generate_dlog_statement_proofs() -> (DLogStatement, CompositeDLogProof, Compo
         ni: h1,
     };
 
-    let composite_dlog_proof_base_h1 = CompositeDLogProof::prove(&dlog_statement_base_h1, &xhi);
-    let composite_dlog_proof_base_h2 = CompositeDLogProof::prove(&dlog_statement_base_h2, &xhi_inv);
+    let composite_dlog_proof_base_h1 = prove_composite_dlog(&dlog_statement_base_h1, xhi, entropy)?;
+    let composite_dlog_proof_base_h2 =
+        prove_composite_dlog(&dlog_statement_base_h2, xhi_inv, entropy)?;
 
-    (
+    Ok((
         dlog_statement_base_h1,
         composite_dlog_proof_base_h1,
         composite_dlog_proof_base_h2,
-    )
+    ))
+}
+
+/// Checks that the committee keys of a new [LocalKey] hold exactly one Paillier key and one dlog
+/// statement per party, and that no statement is degenerate: the modulus must be larger than one
+/// and both bases must be nonzero and reduced modulo it. The statements are used by the range
+/// proofs of signing, a malformed one would only surface there.
+pub(crate) fn validate_committee_statements(
+    paillier_key_vec: &[EncryptionKey],
+    h1_h2_n_tilde_vec: &[DLogStatement],
+    n: u16,
+) -> FsDkrResult<()> {
+    if paillier_key_vec.len() != n as usize || h1_h2_n_tilde_vec.len() != n as usize {
+        return Err(FsDkrError::CommitteeKeysLengthMismatch {
+            expected: n as usize,
+            paillier_keys: paillier_key_vec.len(),
+            dlog_statements: h1_h2_n_tilde_vec.len(),
+        });
+    }
+    for (statement, party_index) in h1_h2_n_tilde_vec.iter().zip(1..) {
+        let in_range = |base: &BigInt| *base > BigInt::zero() && *base < statement.N;
+        if statement.N <= BigInt::one() || !in_range(&statement.g) || !in_range(&statement.ni) {
+            return Err(FsDkrError::DegenerateDLogStatement { party_index });
+        }
+    }
+    Ok(())
+}
+
+/// Shares `secret` like [VerifiableSS::share], drawing the polynomial coefficients and the nonce
+/// of the proof of knowledge of the secret from `seed` instead of fresh randomness. Only the
+/// commitments are returned, the shares themselves are not needed by the caller.
+fn share_with_seed<E: Curve>(
+    t: u16,
+    n: u16,
+    secret: &Scalar<E>,
+    seed: &[u8; 32],
+) -> FsDkrResult<VerifiableSS<E, sha2::Sha256>> {
+    let derive_scalar = |label: u16| {
+        let digest = sha2::Sha256::new()
+            .chain(seed)
+            .chain(label.to_be_bytes())
+            .finalize();
+        Scalar::<E>::from(&BigInt::from_bytes(&digest))
+    };
+
+    let generator = Point::<E>::generator();
+    let commitments: Vec<Point<E>> = std::iter::once(secret.clone())
+        .chain((1..=t).map(derive_scalar))
+        .map(|coefficient| generator * &coefficient)
+        .collect();
+
+    let proof = dlog_proof_with_nonce(secret, &derive_scalar(0))?;
+
+    Ok(VerifiableSS {
+        parameters: ShamirSecretSharing {
+            threshold: t,
+            share_count: n,
+        },
+        commitments,
+        proof,
+    })
+}
+
+/// A Schnorr proof of knowledge of `secret`, as [DLogProof::prove] but with the given `nonce`
+/// instead of one drawn from the operating system generator.
+pub(crate) fn dlog_proof_with_nonce<E: Curve>(
+    secret: &Scalar<E>,
+    nonce: &Scalar<E>,
+) -> FsDkrResult<DLogProof<E, sha2::Sha256>> {
+    let generator = Point::<E>::generator();
+    let pk = generator * secret;
+    let pk_t_rand_commitment = generator * nonce;
+    let challenge: Scalar<E> = sha2::Sha256::new()
+        .chain_point(&pk_t_rand_commitment)
+        .chain_point(&generator.to_point())
+        .chain_point(&pk)
+        .result_scalar();
+    let proof = DLogProof {
+        pk,
+        pk_t_rand_commitment,
+        challenge_response: nonce - &(challenge * secret),
+        hash_choice: HashChoice::new(),
+    };
+    DLogProof::verify(&proof).map_err(|_| FsDkrError::PublicShareValidationError)?;
+    Ok(proof)
+}
+
+/// Proves knowledge of `secret` for `statement`, as [CompositeDLogProof::prove] but with the
+/// nonce drawn from `entropy`. The nonce exceeds the bit length of the modulus by the 256 bits of
+/// the challenge and a statistical security margin of 128 bits, so that it hides the challenge
+/// times the secret, which is below the modulus. The proof is verified before it is returned.
+fn prove_composite_dlog(
+    statement: &DLogStatement,
+    secret: &BigInt,
+    entropy: &mut Entropy,
+) -> FsDkrResult<CompositeDLogProof> {
+    if let Entropy::Os = entropy {
+        return Ok(CompositeDLogProof::prove(statement, secret));
+    }
+    let bound = BigInt::one() << (statement.N.bit_length() + 256 + 128);
+    let r = entropy.sample_below(&bound)?;
+    let x = BigInt::mod_pow(&statement.g, &r, &statement.N);
+    let e = [&x, &statement.g, &statement.N, &statement.ni]
+        .iter()
+        .fold(sha2::Sha256::new(), |digest, value| {
+            digest.chain(value.to_bytes())
+        })
+        .finalize();
+    let proof = CompositeDLogProof {
+        y: r + BigInt::from_bytes(&e) * secret,
+        x,
+    };
+    proof
+        .verify(statement)
+        .map_err(|_| FsDkrError::DLogProofValidation { party_index: 0 })?;
+    Ok(proof)
 }
 
 impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
@@ -98,17 +356,82 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
     /// The distribute phase for a new party. This distribute phase has to happen before the existing
     /// parties distribute. Calling this function will generate a JoinMessage and a pair of Paillier
     /// [Keys] that are going to be used when generating the [LocalKey].
-    pub fn distribute() -> (Self, Keys) {
-        let paillier_key_pair = Keys::create(0);
+    pub fn distribute() -> FsDkrResult<(Self, Keys)> {
+        JoinMessage::distribute_with_retries(KEYGEN_RETRIES)
+    }
+
+    /// Same as [JoinMessage::distribute], drawing up to `retries` samples of the dlog statement
+    /// exponent instead of [KEYGEN_RETRIES].
+    pub fn distribute_with_retries(retries: usize) -> FsDkrResult<(Self, Keys)> {
+        JoinMessage::distribute_with_dlog_statement(generate_dlog_statement_proofs(retries)?)
+    }
+
+    /// Same as [JoinMessage::distribute] with a dlog statement taken from `pool` instead of
+    /// generated on the spot, see [DLogStatementPool].
+    pub fn distribute_with_pool(pool: &DLogStatementPool) -> FsDkrResult<(Self, Keys)> {
+        JoinMessage::distribute_with_dlog_statement(pool.pop()?)
+    }
+
+    /// Same as [JoinMessage::distribute], drawing the Paillier key, the dlog statement and the
+    /// ring-Pedersen parameters with the nonces of their proofs from `rng`, e.g. the `HardwareRng`
+    /// of the `hardware-rng` feature, see the [entropy](crate::entropy) module. Fails with
+    /// [FsDkrError::EntropySourceUnavailable] if `rng` fails to deliver.
+    pub fn distribute_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> FsDkrResult<(Self, Keys)> {
+        let mut entropy = Entropy::from_rng(rng);
+        let (n_tilde, h1, h2, xhi, xhi_inv) = generate_h1_h2_n_tilde(KEYGEN_RETRIES, &mut entropy)?;
+        let (ek, dk) = entropy.paillier_keypair::<KzenPaillier>(crate::PAILLIER_KEY_SIZE)?;
+        let u_i = entropy.scalar()?;
+        let paillier_key_pair = Keys {
+            y_i: Point::generator() * &u_i,
+            u_i,
+            dk,
+            ek,
+            party_index: 0,
+            N_tilde: n_tilde.clone(),
+            h1: h1.clone(),
+            h2: h2.clone(),
+            xhi: xhi.clone(),
+            xhi_inv: xhi_inv.clone(),
+        };
+        let dlog_statement_proofs =
+            dlog_statement_proofs(n_tilde, h1, h2, &xhi, &xhi_inv, &mut entropy)?;
+        JoinMessage::distribute_with_key_pair(
+            dlog_statement_proofs,
+            paillier_key_pair,
+            &mut entropy,
+        )
+    }
+
+    fn distribute_with_dlog_statement(
+        dlog_statement_proofs: DLogStatementProofs,
+    ) -> FsDkrResult<(Self, Keys)> {
+        JoinMessage::distribute_with_key_pair(
+            dlog_statement_proofs,
+            Keys::create(0),
+            &mut Entropy::Os,
+        )
+    }
+
+    fn distribute_with_key_pair(
+        dlog_statement_proofs: DLogStatementProofs,
+        paillier_key_pair: Keys,
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<(Self, Keys)> {
+        crate::validate_generated_modulus(&paillier_key_pair.ek.n, crate::PAILLIER_KEY_SIZE)?;
         let (dlog_statement, composite_dlog_proof_base_h1, composite_dlog_proof_base_h2) =
-            generate_dlog_statement_proofs();
+            dlog_statement_proofs;
 
-        let (ring_pedersen_statement, ring_pedersen_witness) = RingPedersenStatement::generate();
+        let (ring_pedersen_statement, ring_pedersen_witness) =
+            RingPedersenStatement::generate_with(entropy)?;
 
-        let ring_pedersen_proof =
-            RingPedersenProof::prove(&ring_pedersen_witness, &ring_pedersen_statement);
+        let ring_pedersen_proof = RingPedersenProof::prove_with(
+            &ring_pedersen_witness,
+            &ring_pedersen_statement,
+            entropy,
+        )?;
 
         let join_message = JoinMessage {
+            version: JOIN_MESSAGE_VERSION,
             // in a join message, we only care about the ek and the correctness proof
             ek: paillier_key_pair.ek.clone(),
             dk_correctness_proof: NiCorrectKeyProof::proof(&paillier_key_pair.dk, None),
@@ -118,10 +441,163 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
             ring_pedersen_statement,
             ring_pedersen_proof,
             party_index: None,
+            freshness: None,
         };
 
-        (join_message, paillier_key_pair)
+        Ok((join_message, paillier_key_pair))
     }
+
+    /// Same as [JoinMessage::distribute] with externally generated key material, e.g. a Paillier
+    /// key pair held by an HSM: `ek` and the proof `dk_correctness_proof` that it was generated
+    /// correctly, the dlog statement with its composite dlog proofs, see [DLogStatementPool], and
+    /// the ring-Pedersen parameters with their proof. The proofs and the size of the modulus are
+    /// checked like collect checks them before the message is built, the message has no index
+    /// yet so the errors report party index 0, as [verify_paillier_key] does.
+    ///
+    /// The new share can be decrypted where the key is held and passed to
+    /// [JoinMessage::collect_with_plaintext_share]. The [LocalKey] that collect returns must
+    /// still hold the decryption key itself: GG20 stores it in `LocalKey::paillier_dk` and
+    /// decrypts the MtA ciphertexts of every signing with it, so a key that never leaves an HSM
+    /// can not be used with this crate.
+    pub fn distribute_with_key(
+        ek: EncryptionKey,
+        dk_correctness_proof: NiCorrectKeyProof,
+        dlog_statement_proofs: DLogStatementProofs,
+        ring_pedersen_statement: RingPedersenStatement<E, H>,
+        ring_pedersen_proof: RingPedersenProof<E, H, M>,
+    ) -> FsDkrResult<Self> {
+        verify_paillier_key(&ek, &dk_correctness_proof)?;
+        let (dlog_statement, composite_dlog_proof_base_h1, composite_dlog_proof_base_h2) =
+            dlog_statement_proofs;
+        let mut join_message = JoinMessage {
+            version: JOIN_MESSAGE_VERSION,
+            ek,
+            dk_correctness_proof,
+            dlog_statement,
+            composite_dlog_proof_base_h1,
+            composite_dlog_proof_base_h2,
+            ring_pedersen_statement,
+            ring_pedersen_proof,
+            party_index: Some(0),
+            freshness: None,
+        };
+        join_message.verify_ring_pedersen()?;
+        join_message.verify_dlog_proofs()?;
+        join_message.party_index = None;
+        Ok(join_message)
+    }
+
+    /// Returns a compact summary of the message that is cheap to log, without the Paillier
+    /// modulus and the proof material.
+    pub fn redacted(&self) -> JoinMessageSummary {
+        JoinMessageSummary {
+            party_index: self.party_index,
+            ek_bit_length: self.modulus_bits(),
+            dk_correctness_proof_hash: short_hash(&self.dk_correctness_proof),
+            composite_dlog_proofs_hash: short_hash(&(
+                &self.composite_dlog_proof_base_h1,
+                &self.composite_dlog_proof_base_h2,
+            )),
+            ring_pedersen_proof_hash: short_hash(&self.ring_pedersen_proof),
+        }
+    }
+
+    /// Serializes the message into its canonical encoding, see [JoinMessage::from_bytes].
+    pub fn to_bytes(&self) -> FsDkrResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Parses a join message received from the broadcast channel. A message of another version
+    /// than [JOIN_MESSAGE_VERSION] is rejected with [FsDkrError::UnsupportedMessageVersion]. The
+    /// encoding must be canonical, i.e. exactly the one [JoinMessage::to_bytes] produces for the
+    /// parsed message, which among others rejects big integers with leading zeros, and no
+    /// modulus, group element or proof value may be negative. The proofs themselves are verified
+    /// by collect.
+    pub fn from_bytes(bytes: &[u8]) -> FsDkrResult<Self> {
+        let probe: VersionProbe =
+            serde_json::from_slice(bytes).map_err(|e| FsDkrError::SerializationError {
+                reason: e.to_string(),
+            })?;
+        if probe.version != JOIN_MESSAGE_VERSION {
+            return Err(FsDkrError::UnsupportedMessageVersion {
+                found: probe.version,
+                supported: JOIN_MESSAGE_VERSION,
+            });
+        }
+        let join_message: Self =
+            serde_json::from_slice(bytes).map_err(|e| FsDkrError::SerializationError {
+                reason: e.to_string(),
+            })?;
+        if join_message.to_bytes()? != bytes {
+            return Err(FsDkrError::NonCanonicalEncoding {
+                field: "join_message".to_string(),
+            });
+        }
+        join_message.validate_canonical_values()?;
+        Ok(join_message)
+    }
+
+    /// Checks that the moduli are positive, that `nn` is the square of the Paillier modulus and
+    /// that no group element or proof value is negative.
+    pub(crate) fn validate_canonical_values(&self) -> FsDkrResult<()> {
+        let non_canonical = |field: &str| FsDkrError::NonCanonicalEncoding {
+            field: field.to_string(),
+        };
+        let positive = |field: &str, value: &BigInt| {
+            if *value > BigInt::zero() {
+                Ok(())
+            } else {
+                Err(non_canonical(field))
+            }
+        };
+        let non_negative = |field: &str, value: &BigInt| {
+            if *value >= BigInt::zero() {
+                Ok(())
+            } else {
+                Err(non_canonical(field))
+            }
+        };
+
+        positive("ek.n", &self.ek.n)?;
+        if self.ek.nn != &self.ek.n * &self.ek.n {
+            return Err(non_canonical("ek.nn"));
+        }
+        for sigma in self.dk_correctness_proof.sigma_vec.iter() {
+            non_negative("dk_correctness_proof", sigma)?;
+        }
+
+        positive("dlog_statement.N", &self.dlog_statement.N)?;
+        non_negative("dlog_statement.g", &self.dlog_statement.g)?;
+        non_negative("dlog_statement.ni", &self.dlog_statement.ni)?;
+        for (field, proof) in [
+            (
+                "composite_dlog_proof_base_h1",
+                &self.composite_dlog_proof_base_h1,
+            ),
+            (
+                "composite_dlog_proof_base_h2",
+                &self.composite_dlog_proof_base_h2,
+            ),
+        ] {
+            non_negative(field, &proof.x)?;
+            non_negative(field, &proof.y)?;
+        }
+
+        positive("ring_pedersen_statement.N", &self.ring_pedersen_statement.N)?;
+        non_negative("ring_pedersen_statement.S", &self.ring_pedersen_statement.S)?;
+        non_negative("ring_pedersen_statement.T", &self.ring_pedersen_statement.T)?;
+        positive(
+            "ring_pedersen_statement.ek.n",
+            &self.ring_pedersen_statement.ek.n,
+        )?;
+        if !self.ring_pedersen_proof.is_non_negative() {
+            return Err(non_canonical("ring_pedersen_proof"));
+        }
+        Ok(())
+    }
+
     /// Returns the party index if it has been assigned one, throws
     /// [FsDkrError::NewPartyUnassignedIndexError] otherwise
     pub fn get_party_index(&self) -> FsDkrResult<u16> {
@@ -129,6 +605,158 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
             .ok_or(FsDkrError::NewPartyUnassignedIndexError)
     }
 
+    /// Same as [JoinMessage::get_party_index], additionally checking that the index is a valid
+    /// 1 based index in a committee of `n` parties.
+    pub(crate) fn get_party_index_in_committee(&self, n: u16) -> FsDkrResult<u16> {
+        let party_index = self.get_party_index()?;
+        if party_index == 0 || party_index > n {
+            return Err(FsDkrError::PartyIndexOutOfRange {
+                index: party_index,
+                n,
+            });
+        }
+        Ok(party_index)
+    }
+
+    /// Verifies the ring-pedersen parameters broadcasted by the new party.
+    pub(crate) fn verify_ring_pedersen(&self) -> FsDkrResult<()> {
+        RingPedersenProof::verify(&self.ring_pedersen_proof, &self.ring_pedersen_statement)
+    }
+
+    /// Verifies the proof that the Paillier key of the new party was generated correctly.
+    pub(crate) fn verify_paillier_key_correctness(&self) -> FsDkrResult<()> {
+        self.verify_paillier_key_correctness_with::<KzenPaillier>()
+    }
+
+    /// Same as [JoinMessage::verify_paillier_key_correctness], verifying the proof with `B`.
+    pub(crate) fn verify_paillier_key_correctness_with<B: PaillierBackend>(
+        &self,
+    ) -> FsDkrResult<()> {
+        let party_index = self.get_party_index()?;
+        if !B::verify_correct_key(&self.ek, &self.dk_correctness_proof) {
+            return Err(FsDkrError::PaillierVerificationError { party_index });
+        }
+        Ok(())
+    }
+
+    /// The dlog statement with base h2. It is not transmitted but derived from the base h1
+    /// statement, so that both statements share the modulus and have swapped bases by
+    /// construction.
+    pub(crate) fn dlog_statement_base_h2(&self) -> DLogStatement {
+        DLogStatement {
+            N: self.dlog_statement.N.clone(),
+            g: self.dlog_statement.ni.clone(),
+            ni: self.dlog_statement.g.clone(),
+        }
+    }
+
+    /// Checks that both bases of the dlog statement are non zero residues modulo `N`, as the
+    /// honest generator samples them. Otherwise the derived base h2 statement would not be the
+    /// inverse of the base h1 statement.
+    pub(crate) fn verify_dlog_statements_consistency(&self) -> FsDkrResult<()> {
+        let party_index = self.get_party_index()?;
+        let statement = &self.dlog_statement;
+        let is_canonical = |base: &BigInt| *base > BigInt::zero() && *base < statement.N;
+        if !is_canonical(&statement.g) || !is_canonical(&statement.ni) {
+            return Err(FsDkrError::InconsistentDLogStatements { party_index });
+        }
+        Ok(())
+    }
+
+    /// Verifies the composite dlog proofs for both bases of the broadcasted [DLogStatement].
+    ///
+    /// The two proofs share the modulus `N`, but are deliberately not verified as one random
+    /// linear combination: `N` is chosen by the prover, who knows its factorization and can
+    /// multiply the commitments by elements of small order modulo `N`, e.g. `-1`. A combination
+    /// with random exponents misses such a component with constant probability (one half for an
+    /// element of order two), so the batched check would accept proofs rejected one by one.
+    /// Squaring both sides of the verification equations, as batch verifiers over RSA groups
+    /// do, only removes the components of order two: a modulus with a prime factor `p` where
+    /// `p - 1` has a small odd factor `d` still hides components of order `d`, missed with
+    /// probability `1 / d`. Batching would therefore first need a proof that `N` is a product
+    /// of safe primes, which the join message does not carry, and the challenges are derived
+    /// inside `CompositeDLogProof::verify`. The proofs stay verified one by one.
+    pub(crate) fn verify_dlog_proofs(&self) -> FsDkrResult<()> {
+        let party_index = self.get_party_index()?;
+        self.verify_dlog_statements_consistency()?;
+        let dlog_statement_base_h2 = self.dlog_statement_base_h2();
+        if self
+            .composite_dlog_proof_base_h1
+            .verify(&self.dlog_statement)
+            .is_err()
+            || self
+                .composite_dlog_proof_base_h2
+                .verify(&dlog_statement_base_h2)
+                .is_err()
+        {
+            return Err(FsDkrError::DLogProofValidation { party_index });
+        }
+        Ok(())
+    }
+
+    /// Checks that the Paillier modulus of the new party has the expected size.
+    pub(crate) fn verify_paillier_key_size(&self) -> FsDkrResult<()> {
+        let n_length = self.ek.n.bit_length();
+        if n_length > crate::PAILLIER_KEY_SIZE || n_length < crate::PAILLIER_KEY_SIZE - 1 {
+            return Err(FsDkrError::ModuliTooSmall {
+                party_index: self.get_party_index()?,
+                moduli_size: n_length,
+            });
+        }
+        Ok(())
+    }
+
+    /// Bit length of the Paillier modulus `ek.n` of the new party.
+    pub fn modulus_bits(&self) -> usize {
+        self.ek.n.bit_length()
+    }
+
+    /// Policy check rejecting a Paillier modulus of less than `min_bits` bits with
+    /// [FsDkrError::ModulusBelowMinimum]. Collect independently requires the modulus to have
+    /// 2047 or 2048 bits, a minimum above that rejects every joiner. A message without an
+    /// assigned index reports party index 0.
+    pub fn enforce_min_modulus(&self, min_bits: usize) -> FsDkrResult<()> {
+        let moduli_size = self.modulus_bits();
+        if moduli_size < min_bits {
+            return Err(FsDkrError::ModulusBelowMinimum {
+                party_index: self.party_index.unwrap_or(0),
+                moduli_size,
+                min_bits,
+            });
+        }
+        Ok(())
+    }
+
+    /// Admission check of the message into a committee of `n` parties, of which the indices in
+    /// `occupied` are taken: the assigned index must be in range and free, the proofs of the
+    /// Paillier key, the ring-Pedersen parameters and the dlog statement must verify, and the
+    /// Paillier modulus must satisfy both the bounds of collect and `policy`. An occupied index
+    /// is reported as [FsDkrError::DuplicatePartyIndex].
+    pub fn validate_for_committee(
+        &self,
+        occupied: &[u16],
+        n: u16,
+        policy: &KeyPolicy,
+    ) -> FsDkrResult<()> {
+        crate::validate_committee_size(n as usize)?;
+        let party_index = self.get_party_index_in_committee(n)?;
+        if occupied.contains(&party_index) {
+            return Err(FsDkrError::DuplicatePartyIndex { party_index });
+        }
+
+        self.verify_paillier_key_size()?;
+        let n_length = self.ek.n.bit_length();
+        if n_length < policy.min_modulus_bits || n_length > policy.max_modulus_bits {
+            return Err(FsDkrError::ModuliTooSmall {
+                party_index,
+                moduli_size: n_length,
+            });
+        }
+        self.verify_paillier_key_correctness()?;
+        self.verify_ring_pedersen()?;
+        self.verify_dlog_proofs()
+    }
+
     /// Collect phase of the protocol. Compared to the [RefreshMessage::collect], this has to be
     /// tailored for a sent JoinMessage on which we assigned party_index. In this collect, a [LocalKey]
     /// is filled with the information provided by the [RefreshMessage]s from the other parties and
@@ -138,77 +766,369 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
         refresh_messages: &[RefreshMessage<E, H, M>],
         paillier_key: Keys,
         join_messages: &[JoinMessage<E, H, M>],
-        t: u16,
-        n: u16,
+        t: Threshold,
+        n: ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        self.collect_with_strictness(
+            refresh_messages,
+            paillier_key,
+            join_messages,
+            t,
+            n,
+            Strictness::default(),
+        )
+    }
+
+    /// Same as [JoinMessage::collect] for join messages received over an unauthenticated
+    /// channel, see [RefreshMessage::collect_signed]. The party's own message is trusted and
+    /// does not need to be among the signed ones.
+    pub fn collect_signed(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        signed_join_messages: &[SignedJoinMessage<E, H, M>],
+        registry: &HashMap<u16, Point<E>>,
+        t: Threshold,
+        n: ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        let join_messages = SignedJoinMessage::verify_all(signed_join_messages, registry)?;
+        self.collect(refresh_messages, paillier_key, &join_messages, t, n)
+    }
+
+    /// Same as [JoinMessage::collect], additionally requiring that all refresh messages were
+    /// produced at `expected_epoch`. Returns the new [LocalKey] together with its epoch.
+    pub fn collect_with_epoch(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: Threshold,
+        n: ShareCount,
+        expected_epoch: u64,
+    ) -> FsDkrResult<(LocalKey<E>, u64)> {
+        RefreshMessage::validate_epoch(refresh_messages, expected_epoch)?;
+        let local_key = self.collect(refresh_messages, paillier_key, join_messages, t, n)?;
+        Ok((local_key, expected_epoch + 1))
+    }
+
+    /// Recommended entry point of the collect phase for a new party, see
+    /// [RefreshMessage::collect_verified]. All the received messages are verified before the new
+    /// share is decrypted.
+    pub fn collect_verified(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        RefreshMessage::verify_transcript(refresh_messages, join_messages, None, t, n)?;
+        let local_key = self.collect_with_strictness(
+            refresh_messages,
+            paillier_key,
+            join_messages,
+            Threshold(t),
+            ShareCount(n),
+            Strictness::Structural,
+        )?;
+        RefreshMessage::<E, H, M>::validate_own_public_share(&local_key)?;
+        Ok(local_key)
+    }
+
+    /// Same as [JoinMessage::collect], with the amount of verification selected by `strictness`.
+    pub fn collect_with_strictness(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+        strictness: Strictness,
     ) -> FsDkrResult<LocalKey<E>> {
-        RefreshMessage::validate_collect(refresh_messages, t, n)?;
+        self.collect_with_options(
+            refresh_messages,
+            paillier_key.dk.clone(),
+            ShareDecryption::Key,
+            join_messages,
+            t,
+            t,
+            n,
+            strictness,
+            None,
+        )
+        .map(|(local_key, _)| local_key)
+    }
 
+    /// Same as [JoinMessage::collect_with_strictness], first requiring the Paillier modulus of
+    /// every join message to have at least `min_bits` bits, see
+    /// [JoinMessage::enforce_min_modulus].
+    #[allow(clippy::too_many_arguments)]
+    pub fn collect_with_min_modulus(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: Threshold,
+        n: ShareCount,
+        strictness: Strictness,
+        min_bits: usize,
+    ) -> FsDkrResult<LocalKey<E>> {
+        for join_message in join_messages.iter() {
+            join_message.enforce_min_modulus(min_bits)?;
+        }
+        self.collect_with_strictness(
+            refresh_messages,
+            paillier_key,
+            join_messages,
+            t,
+            n,
+            strictness,
+        )
+    }
+
+    /// Same as [JoinMessage::collect], additionally returning the homomorphic sum of ciphertexts
+    /// that was decrypted into the new share, see [RefreshMessage::collect_with_ciphertext_sum].
+    pub fn collect_with_ciphertext_sum(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<(LocalKey<E>, BigInt)> {
+        self.collect_with_options(
+            refresh_messages,
+            paillier_key.dk.clone(),
+            ShareDecryption::Key,
+            join_messages,
+            t,
+            t,
+            n,
+            Strictness::default(),
+            None,
+        )
+    }
+
+    /// Same as [JoinMessage::collect], except that the whole [LocalKey] is a deterministic
+    /// function of the inputs and `seed`: the `vss_scheme` re-sharing the new share is derived
+    /// from the seed instead of fresh randomness. Every slot of the committee must be filled by a
+    /// refresh or a join message, since the placeholder statements of missing slots can not be
+    /// derived, otherwise [FsDkrError::MissingContribution] is returned.
+    pub fn collect_with_seed(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+        seed: &[u8; 32],
+    ) -> FsDkrResult<LocalKey<E>> {
+        self.collect_with_options(
+            refresh_messages,
+            paillier_key.dk.clone(),
+            ShareDecryption::Key,
+            join_messages,
+            t,
+            t,
+            n,
+            Strictness::default(),
+            Some(seed),
+        )
+        .map(|(local_key, _)| local_key)
+    }
+
+    /// Same as [JoinMessage::collect], refusing to produce a [LocalKey] whose `y_sum_s` is not
+    /// `expected_pk`, the public key of the group the party was invited to. Fails with
+    /// [FsDkrError::UnexpectedPublicKey] otherwise.
+    pub fn collect_expecting(
+        &self,
+        expected_pk: &Point<E>,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: Threshold,
+        n: ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        RefreshMessage::validate_expected_public_key(refresh_messages, expected_pk)?;
+        self.collect(refresh_messages, paillier_key, join_messages, t, n)
+    }
+
+    /// Collect phase of a [Reshuffle] for a joining party, whose message was assigned one of the
+    /// indices of `reshuffle.add`. `t` and `n` describe the current committee, the returned
+    /// [LocalKey] belongs to the new committee and is at the new threshold.
+    pub fn collect_reshuffle(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        reshuffle: &Reshuffle,
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        reshuffle.validate(Threshold(t), ShareCount(n))?;
+        if !reshuffle.add.contains(&self.get_party_index()?) {
+            return Err(FsDkrError::InvalidReshuffle {
+                reason: "the joining party was not assigned an added index".to_string(),
+            });
+        }
         for refresh_message in refresh_messages.iter() {
-            RingPedersenProof::verify(
-                &refresh_message.ring_pedersen_proof,
-                &refresh_message.ring_pedersen_statement,
-            )
-            .map_err(|_| FsDkrError::RingPedersenProofValidation {
-                party_index: refresh_message.party_index,
-            })?;
+            refresh_message.validate_polynomial_degree(reshuffle.new_threshold)?;
         }
+        self.collect_with_options(
+            refresh_messages,
+            paillier_key.dk.clone(),
+            ShareDecryption::Key,
+            join_messages,
+            t,
+            reshuffle.new_threshold,
+            reshuffle.new_n(n)?,
+            Strictness::default(),
+            None,
+        )
+        .map(|(local_key, _)| local_key)
+    }
 
-        for join_message in join_messages.iter() {
-            RingPedersenProof::verify(
-                &join_message.ring_pedersen_proof,
-                &join_message.ring_pedersen_statement,
-            )
-            .map_err(|e| {
-                if let Some(party_index) = join_message.party_index {
-                    FsDkrError::RingPedersenProofValidation { party_index }
-                } else {
-                    e
-                }
-            })?;
+    /// Same as [JoinMessage::collect] with the new share decrypted outside of the collect, e.g.
+    /// by the HSM holding the Paillier key of [JoinMessage::distribute_with_key]. `plaintext` is
+    /// the decryption of the [ciphertext_sum](RefreshMessage::ciphertext_sum) of the round for
+    /// the party's index under the key of this message, and is checked against the points the
+    /// quorum committed to for the slot, which fails with
+    /// [FsDkrError::PublicShareValidationError] if it is wrong. `paillier_dk` is not used to
+    /// decrypt, it is only stored in the returned [LocalKey], which GG20 signing can not do
+    /// without, see [JoinMessage::distribute_with_key]. It must be the decryption key of this
+    /// message, otherwise [FsDkrError::KeyPartyMismatch] is returned.
+    pub fn collect_with_plaintext_share(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_dk: DecryptionKey,
+        plaintext: &BigInt,
+        join_messages: &[JoinMessage<E, H, M>],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        self.collect_with_options(
+            refresh_messages,
+            paillier_dk,
+            ShareDecryption::Plaintext(plaintext),
+            join_messages,
+            t,
+            t,
+            n,
+            Strictness::default(),
+            None,
+        )
+        .map(|(local_key, _)| local_key)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn collect_with_options(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_dk: DecryptionKey,
+        decryption: ShareDecryption,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: u16,
+        new_t: u16,
+        n: u16,
+        strictness: Strictness,
+        seed: Option<&[u8; 32]>,
+    ) -> FsDkrResult<(LocalKey<E>, BigInt)> {
+        RefreshMessage::validate_collect_with_strictness(refresh_messages, t, n, strictness)?;
+
+        if strictness.verifies_proofs() {
+            for refresh_message in refresh_messages.iter() {
+                RingPedersenProof::verify(
+                    &refresh_message.ring_pedersen_proof,
+                    &refresh_message.ring_pedersen_statement,
+                )
+                .map_err(|_| FsDkrError::RingPedersenProofValidation {
+                    party_index: refresh_message.party_index,
+                })?;
+            }
+
+            for join_message in join_messages.iter() {
+                RingPedersenProof::verify(
+                    &join_message.ring_pedersen_proof,
+                    &join_message.ring_pedersen_statement,
+                )
+                .map_err(|e| {
+                    if let Some(party_index) = join_message.party_index {
+                        FsDkrError::RingPedersenProofValidation { party_index }
+                    } else {
+                        e
+                    }
+                })?;
+            }
         }
 
-        // check if a party_index has been assigned to the current party
-        let party_index = self.get_party_index()?;
+        // check if a valid party_index has been assigned to the current party
+        let party_index = self.get_party_index_in_committee(n)?;
 
-        // check if a party_index has been assigned to all other new parties
-        // TODO: Check if no party_index collision exists
-        for join_message in join_messages.iter() {
-            join_message.get_party_index()?;
+        // the refresh messages encrypt the shares of this slot to the key of the join message
+        if &paillier_dk.p * &paillier_dk.q != self.ek.n {
+            return Err(FsDkrError::KeyPartyMismatch { party_index });
         }
 
+        // check if a valid party_index has been assigned to all other new parties, and that no
+        // two messages were sent for the same index
+        RefreshMessage::validate_unique_senders(refresh_messages, join_messages, n)?;
+
         let parameters = ShamirSecretSharing {
             threshold: t,
             share_count: n,
         };
 
         // generate a new share, the details can be found here https://hackmd.io/@omershlo/Hy1jBo6JY.
-        let (cipher_text_sum, li_vec) = RefreshMessage::get_ciphertext_sum(
-            refresh_messages,
+        let quorum = RefreshMessage::quorum(refresh_messages, t)?;
+        let (cipher_text_sum, li_vec, sum_info) = RefreshMessage::get_ciphertext_sum::<KzenPaillier>(
+            &quorum,
             party_index,
             &parameters,
-            &paillier_key.ek,
+            &self.ek,
         );
-        let new_share = Paillier::decrypt(&paillier_key.dk, cipher_text_sum)
-            .0
-            .into_owned();
+        if strictness.verifies_proofs() {
+            RefreshMessage::validate_aggregate_commitments(
+                &quorum,
+                &li_vec,
+                &refresh_messages[0].public_key,
+            )?;
+        }
+        let cipher_text_sum = cipher_text_sum.0.into_owned();
+        let new_share = match decryption {
+            ShareDecryption::Key => DecryptionContext::new(&paillier_dk)?.decrypt_verified(
+                &self.ek,
+                &cipher_text_sum,
+                party_index,
+            )?,
+            ShareDecryption::Context(context) => {
+                context.decrypt_verified(&self.ek, &cipher_text_sum, party_index)?
+            }
+            ShareDecryption::Plaintext(plaintext) => plaintext.clone(),
+        };
+        RefreshMessage::<E, H, M>::validate_share_range(&new_share, &sum_info, party_index)?;
 
-        let new_share_fe: Scalar<E> = Scalar::<E>::from(&new_share);
-        let paillier_dk = paillier_key.dk.clone();
+        let new_share_fe: Scalar<E> =
+            Scalar::<E>::from(&BigInt::modulus(&new_share, &crate::curve_order::<E>()));
         let key_linear_x_i = new_share_fe.clone();
         let key_linear_y = Point::<E>::generator() * new_share_fe.clone();
         let keys_linear = SharedKeys {
             x_i: key_linear_x_i,
             y: key_linear_y,
         };
-        let mut pk_vec: Vec<_> = (0..n as usize)
-            .map(|i| refresh_messages[0].points_committed_vec[i].clone() * li_vec[0].clone())
-            .collect();
+        let pk_vec = RefreshMessage::public_shares(&quorum, &li_vec, n as usize);
+        if strictness.verifies_proofs() {
+            RefreshMessage::validate_public_key_reconstruction(
+                &quorum,
+                &pk_vec,
+                &refresh_messages[0].public_key,
+            )?;
+        }
 
-        for i in 0..n as usize {
-            for j in 1..(t + 1) as usize {
-                pk_vec[i] = pk_vec[i].clone()
-                    + refresh_messages[j].points_committed_vec[i].clone() * li_vec[j].clone();
-            }
+        // a plaintext decrypted elsewhere is bound to the committed points only by this check
+        if (strictness.is_paranoid() || matches!(decryption, ShareDecryption::Plaintext(_)))
+            && Point::<E>::generator() * &new_share_fe != pk_vec[(party_index - 1) as usize]
+        {
+            return Err(FsDkrError::PublicShareValidationError);
         }
 
         // check what parties are assigned in the current rotation and associate their paillier
@@ -217,7 +1137,7 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
         let available_parties: HashMap<u16, &EncryptionKey> = refresh_messages
             .iter()
             .map(|msg| (msg.party_index, &msg.ek))
-            .chain(std::iter::once((party_index, &paillier_key.ek)))
+            .chain(std::iter::once((party_index, &self.ek)))
             .chain(
                 join_messages
                     .iter()
@@ -253,17 +1173,25 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
                 }
             })
             .collect();
+        RefreshMessage::debug_assert_key_positions(
+            &paillier_key_vec,
+            refresh_messages,
+            join_messages,
+        );
         // generate the DLogStatement vec needed for the LocalKey generation.
         let h1_h2_ntilde_vec: Vec<DLogStatement> = (1..n + 1)
             .map(|party| {
                 let statement = available_h1_h2_ntilde_vec.get(&party);
 
-                match statement {
-                    None => generate_dlog_statement_proofs().0,
-                    Some(dlog_statement) => (*dlog_statement).clone(),
+                match (statement, seed) {
+                    (None, None) => generate_dlog_statement_proofs(KEYGEN_RETRIES)
+                        .map(|(dlog_statement, _, _)| dlog_statement),
+                    (None, Some(_)) => Err(FsDkrError::MissingContribution { party_index: party }),
+                    (Some(dlog_statement), _) => Ok((*dlog_statement).clone()),
                 }
             })
-            .collect();
+            .collect::<FsDkrResult<Vec<_>>>()?;
+        validate_committee_statements(&paillier_key_vec, &h1_h2_ntilde_vec, n)?;
 
         // check if all the existing parties submitted the same public key. If they differ, abort.
         // TODO: this should be verifiable?
@@ -274,7 +1202,10 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
         }
 
         // generate the vss_scheme for the LocalKey
-        let (vss_scheme, _) = VerifiableSS::<E, sha2::Sha256>::share(t, n, &new_share_fe);
+        let vss_scheme = match seed {
+            Some(seed) => share_with_seed(new_t, n, &new_share_fe, seed)?,
+            None => VerifiableSS::<E, sha2::Sha256>::share(new_t, n, &new_share_fe).0,
+        };
         // TODO: secret cleanup might be needed.
 
         let local_key = LocalKey {
@@ -286,10 +1217,10 @@ impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
             h1_h2_n_tilde_vec: h1_h2_ntilde_vec,
             vss_scheme,
             i: party_index,
-            t: t,
+            t: new_t,
             n: n,
         };
 
-        Ok(local_key)
+        Ok((local_key, cipher_text_sum))
     }
 }
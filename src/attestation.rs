@@ -0,0 +1,60 @@
+//! Attestation by a party that it holds the share committed to in its public share.
+//!
+//! A joining party's collect reconstructs its share from the refresh messages, the committee only
+//! knows the public share `pk_vec[i - 1]` the round committed to for it. Before relying on the
+//! new party for signing, the committee can ask for a [ShareAttestation]: a Schnorr proof of
+//! knowledge of `keys_linear.x_i` for the point `x_i * G`, which every other party checks against
+//! the public share in its own [LocalKey]. A party whose collect went wrong can not produce it,
+//! [ShareAttestation::new] refuses to attest a share that does not match its public share.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::elliptic::curves::{Curve, Point};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use serde::{Deserialize, Serialize};
+
+/// Proof of knowledge of the share of a party, see the [module level](self) documentation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct ShareAttestation<E: Curve> {
+    pub party_index: u16,
+    proof: DLogProof<E, sha2::Sha256>,
+}
+
+impl<E: Curve> ShareAttestation<E> {
+    /// Attests the share of `local_key`. Fails with [FsDkrError::PublicShareValidationError] if
+    /// the share does not match the party's public share.
+    pub fn new(local_key: &LocalKey<E>) -> FsDkrResult<Self> {
+        let public_share = public_share(local_key, local_key.i)?;
+        if Point::<E>::generator() * &local_key.keys_linear.x_i != *public_share {
+            return Err(FsDkrError::PublicShareValidationError);
+        }
+        Ok(ShareAttestation {
+            party_index: local_key.i,
+            proof: DLogProof::prove(&local_key.keys_linear.x_i),
+        })
+    }
+
+    /// Verifies the attestation against the public share of its party in `local_key`, the key of
+    /// the verifying party. Fails with [FsDkrError::ShareAttestationInvalid] if the proof does
+    /// not hold or proves knowledge of another point.
+    pub fn verify(&self, local_key: &LocalKey<E>) -> FsDkrResult<()> {
+        let public_share = public_share(local_key, self.party_index)?;
+        if self.proof.pk != *public_share || DLogProof::verify(&self.proof).is_err() {
+            return Err(FsDkrError::ShareAttestationInvalid {
+                party_index: self.party_index,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn public_share<E: Curve>(local_key: &LocalKey<E>, party_index: u16) -> FsDkrResult<&Point<E>> {
+    party_index
+        .checked_sub(1)
+        .and_then(|slot| local_key.pk_vec.get(slot as usize))
+        .ok_or(FsDkrError::PartyIndexOutOfRange {
+            index: party_index,
+            n: local_key.n,
+        })
+}
@@ -0,0 +1,213 @@
+//! Read-only validation of a refresh transcript.
+//!
+//! An auditor does not hold any Paillier decryption key, so it can not recover a share. It can
+//! still check everything that is public in a transcript: the proofs carried by every
+//! [RefreshMessage] and [JoinMessage], that every existing party broadcasted the expected public
+//! key and committed to a polynomial of the expected degree, that no party index is used twice,
+//! and that the commitments of a quorum of the valid messages re-share the expected public key.
+//! [audit_transcript] never decrypts anything.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::ShamirSecretSharing;
+use curv::elliptic::curves::{Curve, Point};
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use zk_paillier::zkproofs::DLogStatement;
+
+/// Outcome of the audit of a single message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartyAudit {
+    /// Index of the sender, `None` for a join message that was not assigned an index.
+    pub party_index: Option<u16>,
+    /// The first check that failed for this message, `None` if all checks passed.
+    pub failure: Option<FsDkrError>,
+}
+
+impl PartyAudit {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Per party results of [audit_transcript], in the order the messages were given.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub refresh_parties: Vec<PartyAudit>,
+    pub join_parties: Vec<PartyAudit>,
+    /// The failure of the checks over a quorum of the refresh messages that passed, `None` if
+    /// they passed. It can not be attributed to a single message.
+    pub quorum_failure: Option<FsDkrError>,
+}
+
+impl AuditReport {
+    /// Returns true if every message of the transcript and the quorum passed all checks.
+    pub fn is_valid(&self) -> bool {
+        self.quorum_failure.is_none()
+            && self
+                .refresh_parties
+                .iter()
+                .chain(self.join_parties.iter())
+                .all(PartyAudit::passed)
+    }
+
+    /// Returns the audits of all the messages that failed a check.
+    pub fn failures(&self) -> Vec<&PartyAudit> {
+        self.refresh_parties
+            .iter()
+            .chain(self.join_parties.iter())
+            .filter(|audit| !audit.passed())
+            .collect()
+    }
+}
+
+/// Audits a full refresh transcript without any secret key.
+///
+/// `paillier_key_vec` and `h1_h2_n_tilde_vec` are the per-slot keys the existing parties
+/// encrypted the new shares to, i.e. the public part of their [LocalKey] when they ran
+/// distribute. `public_key` is the group public key the refresh must re-share, taken from the
+/// auditor's own record such as the `y_sum_s` of a [LocalKey], never from the messages. Every
+/// refresh message must broadcast it and commit to a polynomial of degree `t`. The first `t + 1`
+/// refresh messages that pass, by party index, then form the quorum whose coefficient
+/// commitments must combine into `public_key` and whose committed points must interpolate to it.
+/// An error is returned only if the transcript is malformed as a whole, every per-party failure
+/// is reported in the [AuditReport].
+///
+/// [LocalKey]: multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey
+pub fn audit_transcript<E: Curve, H: Digest + Clone, const M: usize>(
+    refresh_messages: &[RefreshMessage<E, H, M>],
+    join_messages: &[JoinMessage<E, H, M>],
+    paillier_key_vec: &[EncryptionKey],
+    h1_h2_n_tilde_vec: &[DLogStatement],
+    public_key: &Point<E>,
+    Threshold(t): Threshold,
+    ShareCount(n): ShareCount,
+) -> FsDkrResult<AuditReport> {
+    crate::validate_committee_size(n as usize)?;
+    if paillier_key_vec.len() < n as usize || h1_h2_n_tilde_vec.len() < n as usize {
+        return Err(FsDkrError::CommitteeKeysLengthMismatch {
+            expected: n as usize,
+            paillier_keys: paillier_key_vec.len(),
+            dlog_statements: h1_h2_n_tilde_vec.len(),
+        });
+    }
+    RefreshMessage::validate_structure(refresh_messages, t)?;
+
+    let mut seen_indices = HashSet::new();
+
+    let mut refresh_parties = Vec::with_capacity(refresh_messages.len());
+    for refresh_message in refresh_messages.iter() {
+        let party_index = refresh_message.party_index;
+        let failure = audit_refresh_message(
+            refresh_message,
+            public_key,
+            paillier_key_vec,
+            h1_h2_n_tilde_vec,
+            n,
+        )
+        .and_then(|_| refresh_message.validate_polynomial_degree(t))
+        .and_then(|_| check_unique_index(&mut seen_indices, party_index))
+        .err();
+        refresh_parties.push(PartyAudit {
+            party_index: Some(party_index),
+            failure,
+        });
+    }
+
+    let mut join_parties = Vec::with_capacity(join_messages.len());
+    for join_message in join_messages.iter() {
+        let failure = audit_join_message(join_message)
+            .and_then(|party_index| check_unique_index(&mut seen_indices, party_index))
+            .err();
+        join_parties.push(PartyAudit {
+            party_index: join_message.party_index,
+            failure,
+        });
+    }
+
+    let passed: Vec<_> = refresh_messages
+        .iter()
+        .zip(refresh_parties.iter())
+        .filter(|(_, audit)| audit.passed())
+        .map(|(refresh_message, _)| refresh_message)
+        .collect();
+    let quorum_failure = audit_quorum(passed, public_key, t, n).err();
+
+    Ok(AuditReport {
+        refresh_parties,
+        join_parties,
+        quorum_failure,
+    })
+}
+
+// checks that a quorum of the refresh messages that passed re-shares `public_key`
+fn audit_quorum<E: Curve, H: Digest + Clone, const M: usize>(
+    passed: Vec<&RefreshMessage<E, H, M>>,
+    public_key: &Point<E>,
+    t: u16,
+    n: u16,
+) -> FsDkrResult<()> {
+    if passed.len() <= t as usize {
+        return Err(FsDkrError::PartiesThresholdViolation {
+            threshold: t,
+            refreshed_keys: passed.len(),
+        });
+    }
+    let quorum = RefreshMessage::quorum_of(passed, t)?;
+    let parameters = ShamirSecretSharing {
+        threshold: t,
+        share_count: n,
+    };
+    let li_vec = RefreshMessage::quorum_lagrange_coefficients(&quorum, &parameters);
+    RefreshMessage::validate_aggregate_commitments(&quorum, &li_vec, public_key)?;
+    let pk_vec = RefreshMessage::public_shares(&quorum, &li_vec, n as usize);
+    RefreshMessage::validate_public_key_reconstruction(&quorum, &pk_vec, public_key)
+}
+
+pub(crate) fn audit_refresh_message<E: Curve, H: Digest + Clone, const M: usize>(
+    refresh_message: &RefreshMessage<E, H, M>,
+    public_key: &Point<E>,
+    paillier_key_vec: &[EncryptionKey],
+    h1_h2_n_tilde_vec: &[DLogStatement],
+    n: u16,
+) -> FsDkrResult<()> {
+    crate::validate_committee_size(n as usize)?;
+    refresh_message.validate_slot_count(n)?;
+    refresh_message.validate_public_shares(n)?;
+    refresh_message.validate_curve_points()?;
+    refresh_message.verify_share_proofs(paillier_key_vec, h1_h2_n_tilde_vec, n as usize)?;
+    refresh_message.verify_ring_pedersen().map_err(|_| {
+        FsDkrError::RingPedersenProofValidation {
+            party_index: refresh_message.party_index,
+        }
+    })?;
+    refresh_message.verify_paillier_key()?;
+    if refresh_message.public_key != *public_key {
+        return Err(FsDkrError::BroadcastedPublicKeyError);
+    }
+    Ok(())
+}
+
+pub(crate) fn audit_join_message<E: Curve, H: Digest + Clone, const M: usize>(
+    join_message: &JoinMessage<E, H, M>,
+) -> FsDkrResult<u16> {
+    let party_index = join_message.get_party_index()?;
+    join_message
+        .verify_ring_pedersen()
+        .map_err(|_| FsDkrError::RingPedersenProofValidation { party_index })?;
+    join_message.verify_paillier_key_correctness()?;
+    join_message.verify_dlog_proofs()?;
+    join_message.verify_paillier_key_size()?;
+    Ok(party_index)
+}
+
+fn check_unique_index(seen_indices: &mut HashSet<u16>, party_index: u16) -> FsDkrResult<()> {
+    if !seen_indices.insert(party_index) {
+        return Err(FsDkrError::DuplicatePartyIndex { party_index });
+    }
+    Ok(())
+}
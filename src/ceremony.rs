@@ -0,0 +1,215 @@
+//! Checkpointing of a party's side of a refresh ceremony.
+//!
+//! Between distribute and collect a party has to retain secret material (the new
+//! [DecryptionKey] of an existing party, the Paillier [Keys] of a joining party) and buffer the
+//! messages of the other parties as they arrive. [CeremonyState] captures all of it, so that it can
+//! be saved after every step and loaded again after a restart, and the ceremony continues from
+//! where it left off.
+//!
+//! The saved bytes contain secret keys and must be stored with the same care as a [LocalKey].
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use serde::{Deserialize, Serialize};
+
+/// The step of the ceremony a [CeremonyState] is at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CeremonyPhase {
+    /// The party distributed its message and buffers the messages of the other parties.
+    Collecting,
+    /// The party collected the round, [CeremonyState::local_key] is the refreshed key.
+    Completed,
+}
+
+/// The secret material a party retains from its distribute phase until it collects.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "E: Curve, H: Digest + Clone")]
+enum PendingSecret<E: Curve, H: Digest + Clone, const M: usize> {
+    Refresh {
+        new_dk: DecryptionKey,
+    },
+    Join {
+        join_message: JoinMessage<E, H, M>,
+        paillier_keys: Keys,
+    },
+}
+
+/// The state of one party in a refresh ceremony, see the [module level](self) documentation.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "E: Curve, H: Digest + Clone")]
+pub struct CeremonyState<E: Curve, H: Digest + Clone, const M: usize> {
+    phase: CeremonyPhase,
+    t: u16,
+    n: u16,
+    local_key: Option<LocalKey<E>>,
+    pending: Option<PendingSecret<E, H, M>>,
+    refresh_messages: Vec<RefreshMessage<E, H, M>>,
+    join_messages: Vec<JoinMessage<E, H, M>>,
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> CeremonyState<E, H, M> {
+    /// State of an existing party right after [RefreshMessage::distribute] or
+    /// [RefreshMessage::replace], given the key it distributed from, its broadcasted message
+    /// and its new decryption key.
+    pub fn existing(
+        local_key: LocalKey<E>,
+        refresh_message: RefreshMessage<E, H, M>,
+        new_dk: DecryptionKey,
+    ) -> Self {
+        CeremonyState {
+            phase: CeremonyPhase::Collecting,
+            t: local_key.t,
+            n: refresh_message.points_committed_vec.len() as u16,
+            local_key: Some(local_key),
+            pending: Some(PendingSecret::Refresh { new_dk }),
+            refresh_messages: vec![refresh_message],
+            join_messages: Vec::new(),
+        }
+    }
+
+    /// State of a joining party right after [JoinMessage::distribute], once it was assigned its
+    /// index in a `t` out of `n` committee.
+    pub fn joining(
+        join_message: JoinMessage<E, H, M>,
+        paillier_keys: Keys,
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<Self> {
+        crate::validate_committee_size(n as usize)?;
+        join_message.get_party_index()?;
+        Ok(CeremonyState {
+            phase: CeremonyPhase::Collecting,
+            t,
+            n,
+            local_key: None,
+            pending: Some(PendingSecret::Join {
+                join_message: join_message.clone(),
+                paillier_keys,
+            }),
+            refresh_messages: Vec::new(),
+            join_messages: vec![join_message],
+        })
+    }
+
+    pub fn phase(&self) -> CeremonyPhase {
+        self.phase
+    }
+
+    /// The key of the party: for an existing party the key it distributed from until the
+    /// ceremony is completed, the refreshed key afterwards.
+    pub fn local_key(&self) -> Option<&LocalKey<E>> {
+        self.local_key.as_ref()
+    }
+
+    /// Buffers a refresh message. A message that was already received byte for byte, e.g.
+    /// delivered again after a restart, is ignored. Another message from a party that was already
+    /// heard from is an equivocation and fails with [FsDkrError::DuplicatePartyIndex], leaving
+    /// the buffered messages unchanged.
+    pub fn receive_refresh_message(
+        &mut self,
+        refresh_message: RefreshMessage<E, H, M>,
+    ) -> FsDkrResult<()> {
+        if let Some(received) = self
+            .refresh_messages
+            .iter()
+            .find(|received| received.party_index == refresh_message.party_index)
+        {
+            if encode(received)? == encode(&refresh_message)? {
+                return Ok(());
+            }
+            return Err(FsDkrError::DuplicatePartyIndex {
+                party_index: refresh_message.party_index,
+            });
+        }
+        self.refresh_messages.push(refresh_message);
+        Ok(())
+    }
+
+    /// Buffers a join message, which must have been assigned its index. A message that was
+    /// already received byte for byte is ignored, another message for an index that was already
+    /// received fails with [FsDkrError::DuplicatePartyIndex].
+    pub fn receive_join_message(&mut self, join_message: JoinMessage<E, H, M>) -> FsDkrResult<()> {
+        let party_index = join_message.get_party_index()?;
+        if let Some(received) = self
+            .join_messages
+            .iter()
+            .find(|received| received.party_index == Some(party_index))
+        {
+            if encode(received)? == encode(&join_message)? {
+                return Ok(());
+            }
+            return Err(FsDkrError::DuplicatePartyIndex { party_index });
+        }
+        self.join_messages.push(join_message);
+        Ok(())
+    }
+
+    /// Collects the buffered messages with [RefreshMessage::collect_verified] or
+    /// [JoinMessage::collect_verified] and returns the refreshed key. On failure the state is
+    /// left unchanged so that more messages can be received and collect retried. Once completed,
+    /// the refreshed key is returned again without collecting.
+    pub fn collect(&mut self) -> FsDkrResult<&LocalKey<E>> {
+        if self.phase == CeremonyPhase::Collecting {
+            let new_key = match (&self.pending, &self.local_key) {
+                (Some(PendingSecret::Refresh { new_dk }), Some(local_key)) => {
+                    RefreshMessage::collect_verified(
+                        &self.refresh_messages,
+                        local_key,
+                        new_dk.clone(),
+                        &self.join_messages,
+                    )?
+                }
+                (
+                    Some(PendingSecret::Join {
+                        join_message,
+                        paillier_keys,
+                    }),
+                    None,
+                ) => join_message.collect_verified(
+                    &self.refresh_messages,
+                    paillier_keys.clone(),
+                    &self.join_messages,
+                    Threshold(self.t),
+                    ShareCount(self.n),
+                )?,
+                _ => return Err(FsDkrError::InconsistentCeremonyState),
+            };
+            self.local_key = Some(new_key);
+            self.pending = None;
+            self.refresh_messages.clear();
+            self.join_messages.clear();
+            self.phase = CeremonyPhase::Completed;
+        }
+
+        self.local_key
+            .as_ref()
+            .ok_or(FsDkrError::InconsistentCeremonyState)
+    }
+
+    /// Encodes the state to bytes, see [CeremonyState::load].
+    pub fn save(&self) -> FsDkrResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Restores a state saved with [CeremonyState::save].
+    pub fn load(bytes: &[u8]) -> FsDkrResult<Self> {
+        serde_json::from_slice(bytes).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+}
+
+fn encode<T: Serialize>(message: &T) -> FsDkrResult<Vec<u8>> {
+    serde_json::to_vec(message).map_err(|e| FsDkrError::SerializationError {
+        reason: e.to_string(),
+    })
+}
@@ -0,0 +1,184 @@
+//! Audit record of a completed refresh ceremony.
+//!
+//! A [CeremonyLog] records what a party collected: the fingerprints of the refresh and join
+//! messages, the quorum whose shares were re-shared, the joining and removed parties, the epoch
+//! and the resulting committee and public key. It holds no secret material, only the public
+//! parts of the refreshed [LocalKey] and digests of the broadcasted messages, so it can be
+//! archived and handed to auditors as is. [CeremonyLog::to_bytes] is canonical: the lists are
+//! sorted and the fields encoded in a fixed order, so two parties of the same ceremony produce
+//! the same bytes apart from [CeremonyLog::recorded_at] and [CeremonyLog::party_index], and
+//! [CeremonyLog::digest] can be signed for archival.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::{Curve, Point};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the [CeremonyLog] encoding.
+pub const CEREMONY_LOG_VERSION: u16 = 1;
+
+/// The SHA-256 digest of the encoding of a broadcasted message, hex encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageFingerprint {
+    /// Party index of the sender in the new committee.
+    pub party_index: u16,
+    pub digest: String,
+}
+
+/// The record of a ceremony, see the [module level](self) documentation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct CeremonyLog<E: Curve> {
+    pub version: u16,
+    /// Seconds since the Unix epoch at which the log was recorded.
+    pub recorded_at: u64,
+    /// Index of the recording party in the new committee.
+    pub party_index: u16,
+    pub epoch: u64,
+    /// Old party indices of the parties whose shares were re-shared.
+    pub quorum: Vec<u16>,
+    /// Party indices of the joining parties in the new committee.
+    pub joiners: Vec<u16>,
+    /// Party indices of the old committee that were removed.
+    pub removed: Vec<u16>,
+    pub t: u16,
+    pub n: u16,
+    pub y_sum_s: Point<E>,
+    pub refresh_messages: Vec<MessageFingerprint>,
+    pub join_messages: Vec<MessageFingerprint>,
+}
+
+impl<E: Curve> CeremonyLog<E> {
+    /// Records the ceremony that produced `local_key` from `refresh_messages` and
+    /// `join_messages`, re-sharing shares of a committee with threshold `old_t`.
+    pub fn record<H: Digest + Clone, const M: usize>(
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        join_messages: &[JoinMessage<E, H, M>],
+        old_t: u16,
+        local_key: &LocalKey<E>,
+    ) -> FsDkrResult<Self> {
+        let mut quorum: Vec<u16> = RefreshMessage::quorum(refresh_messages, old_t)?
+            .iter()
+            .map(|refresh_message| refresh_message.old_party_index)
+            .collect();
+        quorum.sort_unstable();
+
+        let mut joiners = join_messages
+            .iter()
+            .map(|join_message| join_message.get_party_index())
+            .collect::<FsDkrResult<Vec<u16>>>()?;
+        joiners.sort_unstable();
+
+        let mut removed: Vec<u16> = refresh_messages
+            .iter()
+            .flat_map(|refresh_message| refresh_message.remove_party_indices.iter().copied())
+            .collect();
+        removed.sort_unstable();
+        removed.dedup();
+
+        let mut refresh_fingerprints = refresh_messages
+            .iter()
+            .map(|refresh_message| fingerprint(refresh_message.party_index, refresh_message))
+            .collect::<FsDkrResult<Vec<_>>>()?;
+        refresh_fingerprints.sort_by_key(|fingerprint| fingerprint.party_index);
+
+        let mut join_fingerprints = join_messages
+            .iter()
+            .map(|join_message| fingerprint(join_message.get_party_index()?, join_message))
+            .collect::<FsDkrResult<Vec<_>>>()?;
+        join_fingerprints.sort_by_key(|fingerprint| fingerprint.party_index);
+
+        Ok(CeremonyLog {
+            version: CEREMONY_LOG_VERSION,
+            recorded_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            party_index: local_key.i,
+            epoch: refresh_messages
+                .first()
+                .map(|refresh_message| refresh_message.epoch)
+                .unwrap_or_default(),
+            quorum,
+            joiners,
+            removed,
+            t: local_key.t,
+            n: local_key.n,
+            y_sum_s: local_key.y_sum_s.clone(),
+            refresh_messages: refresh_fingerprints,
+            join_messages: join_fingerprints,
+        })
+    }
+
+    /// The canonical encoding of the log.
+    pub fn to_bytes(&self) -> FsDkrResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> FsDkrResult<Self> {
+        serde_json::from_slice(bytes).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// The SHA-256 digest of [CeremonyLog::to_bytes], the value to sign for archival.
+    pub fn digest(&self) -> FsDkrResult<[u8; 32]> {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&sha2::Sha256::digest(&self.to_bytes()?));
+        Ok(digest)
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Same as [RefreshMessage::collect], also returning the [CeremonyLog] of the collected
+    /// round.
+    pub fn collect_with_log(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<CeremonyLog<E>> {
+        let old_t = local_key.t;
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)?;
+        CeremonyLog::record(refresh_messages, join_messages, old_t, local_key)
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
+    /// Same as [JoinMessage::collect], also returning the [CeremonyLog] of the collected round.
+    pub fn collect_with_log(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: Threshold,
+        n: ShareCount,
+    ) -> FsDkrResult<(LocalKey<E>, CeremonyLog<E>)> {
+        let local_key = self.collect(refresh_messages, paillier_key, join_messages, t, n)?;
+        let log = CeremonyLog::record(refresh_messages, join_messages, *t, &local_key)?;
+        Ok((local_key, log))
+    }
+}
+
+fn fingerprint<T: Serialize>(party_index: u16, message: &T) -> FsDkrResult<MessageFingerprint> {
+    let encoded = serde_json::to_vec(message).map_err(|e| FsDkrError::SerializationError {
+        reason: e.to_string(),
+    })?;
+    let digest = sha2::Sha256::digest(&encoded)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(MessageFingerprint {
+        party_index,
+        digest,
+    })
+}
@@ -0,0 +1,208 @@
+//! Validation of a chain of refreshes back to the keygen.
+//!
+//! [verify_refresh_chain] walks the transcripts of consecutive refreshes of a committee, starting
+//! from the public key of its keygen. Every round is audited like [audit_transcript] does, and
+//! must re-share the keygen public key: the messages broadcast it, the constant terms of the
+//! quorum's coefficient commitments combine into it, and it can be interpolated from the public
+//! shares of the new committee. Consecutive rounds must fit together: a round encrypts to the
+//! Paillier keys and dlog statements installed by the previous one, apart from the slots of the
+//! parties joining in it, runs at the threshold the previous one re-shared to, and from the
+//! second round on every existing party re-shares the public share the previous round gave it.
+//! The parties keep their index from one round to the next, a round that renumbers them, such as
+//! a reshuffle removing parties, does not continue the chain. Like the audit, the walk needs no
+//! secret.
+//!
+//! [audit_transcript]: crate::audit::audit_transcript
+
+use crate::add_party_message::JoinMessage;
+use crate::audit::audit_transcript;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::ShamirSecretSharing;
+use curv::elliptic::curves::{Curve, Point};
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use zk_paillier::zkproofs::DLogStatement;
+
+/// The public record of one refresh round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve, H: Digest + Clone")]
+pub struct RefreshTranscript<E: Curve, H: Digest + Clone, const M: usize> {
+    /// Threshold of the committee that was refreshed.
+    pub t: u16,
+    /// The keys the new shares were encrypted to, indexed by the receiving party. For the first
+    /// round these are the keys of the keygen.
+    pub paillier_key_vec: Vec<EncryptionKey>,
+    pub h1_h2_n_tilde_vec: Vec<DLogStatement>,
+    pub refresh_messages: Vec<RefreshMessage<E, H, M>>,
+    pub join_messages: Vec<JoinMessage<E, H, M>>,
+}
+
+/// The committee at the end of a chain verified by [verify_refresh_chain].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct ChainReport<E: Curve> {
+    /// Number of rounds verified.
+    pub rounds: usize,
+    pub t: u16,
+    pub n: u16,
+    pub y_sum_s: Point<E>,
+    /// Public shares of the final committee, indexed by party.
+    pub pk_vec: Vec<Point<E>>,
+    pub paillier_key_vec: Vec<EncryptionKey>,
+    pub h1_h2_n_tilde_vec: Vec<DLogStatement>,
+}
+
+/// Verifies the chain of refreshes `transcripts`, in the order they ran, of the committee whose
+/// keygen produced `genesis_pk`, see the [module level](self) documentation. Fails with
+/// [FsDkrError::RefreshChainBroken] for the first round that does not verify.
+pub fn verify_refresh_chain<E: Curve, H: Digest + Clone, const M: usize>(
+    genesis_pk: &Point<E>,
+    transcripts: &[RefreshTranscript<E, H, M>],
+) -> FsDkrResult<ChainReport<E>> {
+    let mut committee: Option<ChainReport<E>> = None;
+    for (round, transcript) in transcripts.iter().enumerate() {
+        let report = verify_round(genesis_pk, transcript, committee.as_ref()).map_err(|e| {
+            FsDkrError::RefreshChainBroken {
+                round,
+                reason: e.to_string(),
+            }
+        })?;
+        committee = Some(report);
+    }
+    committee.ok_or_else(|| FsDkrError::RefreshChainBroken {
+        round: 0,
+        reason: "the chain has no transcript".to_string(),
+    })
+}
+
+// verifies one round against the committee left by the previous one, `None` for the first round
+fn verify_round<E: Curve, H: Digest + Clone, const M: usize>(
+    genesis_pk: &Point<E>,
+    transcript: &RefreshTranscript<E, H, M>,
+    previous: Option<&ChainReport<E>>,
+) -> FsDkrResult<ChainReport<E>> {
+    let refresh_messages = &transcript.refresh_messages;
+    let join_messages = &transcript.join_messages;
+    let t = transcript.t;
+    let n = (refresh_messages.len() + join_messages.len()) as u16;
+
+    if let Some(previous) = previous {
+        if t != previous.t || !continues(transcript, previous) {
+            return Err(FsDkrError::InconsistentCeremonyState);
+        }
+    }
+
+    let report = audit_transcript(
+        refresh_messages,
+        join_messages,
+        &transcript.paillier_key_vec,
+        &transcript.h1_h2_n_tilde_vec,
+        genesis_pk,
+        Threshold(t),
+        ShareCount(n),
+    )?;
+    if let Some(audit) = report.failures().first() {
+        return Err(audit
+            .failure
+            .clone()
+            .unwrap_or(FsDkrError::InconsistentCeremonyState));
+    }
+    if let Some(failure) = report.quorum_failure {
+        return Err(failure);
+    }
+    if let Some(previous) = previous {
+        for refresh_message in refresh_messages.iter() {
+            refresh_message.validate_reshared_share(&previous.pk_vec)?;
+        }
+    }
+
+    let quorum = RefreshMessage::quorum(refresh_messages, t)?;
+    // the next round runs at the threshold checked here, never at one read from the messages
+    for refresh_message in quorum.iter() {
+        refresh_message.validate_polynomial_degree(t)?;
+    }
+    let parameters = ShamirSecretSharing {
+        threshold: t,
+        share_count: n,
+    };
+    let li_vec = RefreshMessage::quorum_lagrange_coefficients(&quorum, &parameters);
+    RefreshMessage::validate_aggregate_commitments(&quorum, &li_vec, genesis_pk)?;
+    let pk_vec = RefreshMessage::public_shares(&quorum, &li_vec, n as usize);
+    RefreshMessage::validate_public_key_reconstruction(&quorum, &pk_vec, genesis_pk)?;
+
+    // the keys installed by the round: the new key of every sender, the other slots keep theirs
+    let mut paillier_key_vec = Vec::with_capacity(n as usize);
+    let mut h1_h2_n_tilde_vec = Vec::with_capacity(n as usize);
+    for party_index in 1..=n {
+        let slot = (party_index - 1) as usize;
+        let installed = refresh_messages
+            .iter()
+            .find(|refresh_message| refresh_message.party_index == party_index)
+            .map(|refresh_message| (&refresh_message.ek, &refresh_message.dlog_statement))
+            .or_else(|| {
+                join_messages
+                    .iter()
+                    .find(|join_message| join_message.party_index == Some(party_index))
+                    .map(|join_message| (&join_message.ek, &join_message.dlog_statement))
+            })
+            .or_else(|| {
+                transcript
+                    .paillier_key_vec
+                    .get(slot)
+                    .zip(transcript.h1_h2_n_tilde_vec.get(slot))
+            })
+            .ok_or(FsDkrError::MissingContribution { party_index })?;
+        paillier_key_vec.push(installed.0.clone());
+        h1_h2_n_tilde_vec.push(installed.1.clone());
+    }
+
+    Ok(ChainReport {
+        rounds: previous.map(|previous| previous.rounds).unwrap_or_default() + 1,
+        t,
+        n,
+        y_sum_s: genesis_pk.clone(),
+        pk_vec,
+        paillier_key_vec,
+        h1_h2_n_tilde_vec,
+    })
+}
+
+// checks that the round encrypts to the keys the previous round installed, with the slots of
+// the parties joining in the round taken by their keys
+fn continues<E: Curve, H: Digest + Clone, const M: usize>(
+    transcript: &RefreshTranscript<E, H, M>,
+    previous: &ChainReport<E>,
+) -> bool {
+    let same_statement = |statement: &DLogStatement, expected: &DLogStatement| {
+        statement.N == expected.N && statement.g == expected.g && statement.ni == expected.ni
+    };
+    transcript.paillier_key_vec.len() == transcript.h1_h2_n_tilde_vec.len()
+        && transcript
+            .paillier_key_vec
+            .iter()
+            .zip(transcript.h1_h2_n_tilde_vec.iter())
+            .zip(1..)
+            .all(|((ek, statement), party_index)| {
+                let slot = (party_index - 1) as usize;
+                let expected = transcript
+                    .join_messages
+                    .iter()
+                    .find(|join_message| join_message.party_index == Some(party_index))
+                    .map(|join_message| (&join_message.ek, &join_message.dlog_statement))
+                    .or_else(|| {
+                        previous
+                            .paillier_key_vec
+                            .get(slot)
+                            .zip(previous.h1_h2_n_tilde_vec.get(slot))
+                    });
+                match expected {
+                    Some((expected_ek, expected_statement)) => {
+                        ek == expected_ek && same_statement(statement, expected_statement)
+                    }
+                    None => false,
+                }
+            })
+}
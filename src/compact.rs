@@ -0,0 +1,173 @@
+//! Storage encoding of a [LocalKey] without repeated moduli.
+//!
+//! The Paillier moduli in `paillier_key_vec` and the `N tilde` moduli in `h1_h2_n_tilde_vec` make
+//! up most of the encoding of a [LocalKey]. Parties whose dlog statements come from the same
+//! trusted setup share `N tilde`, and the placeholder keys of a joining party's collect all have
+//! a zero modulus. [CompressLocalKey::to_compact_bytes] stores every distinct modulus once and
+//! refers to it by position, and the squared Paillier moduli `nn` are recomputed instead of
+//! stored. [from_compact_bytes] restores a [LocalKey] identical to the one encoded. Like the serde
+//! encoding of a [LocalKey], the compact encoding carries the secret share and the Paillier
+//! decryption key in the clear.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::arithmetic::Converter;
+use curv::elliptic::curves::Curve;
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use zk_paillier::zkproofs::DLogStatement;
+
+/// Version of the [CompactLocalKey] encoding.
+pub const COMPACT_KEY_VERSION: u16 = 1;
+
+/// A dlog statement with its modulus given by position in [CompactLocalKey::moduli].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CompactDLogStatement {
+    modulus: usize,
+    g: BigInt,
+    ni: BigInt,
+}
+
+/// A [LocalKey] with its moduli deduplicated, see the [module level](self) documentation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct CompactLocalKey<E: Curve> {
+    pub version: u16,
+    /// The distinct moduli of the committee keys.
+    moduli: Vec<BigInt>,
+    /// Position in `moduli` of the Paillier modulus of every party.
+    paillier_moduli: Vec<usize>,
+    dlog_statements: Vec<CompactDLogStatement>,
+    /// The key without `paillier_key_vec` and `h1_h2_n_tilde_vec`.
+    local_key: LocalKey<E>,
+}
+
+/// Compact encoding of a [LocalKey] for storage.
+pub trait CompressLocalKey {
+    /// Encodes the key with every distinct modulus stored once.
+    fn to_compact_bytes(&self) -> FsDkrResult<Vec<u8>>;
+}
+
+impl<E: Curve> CompressLocalKey for LocalKey<E> {
+    fn to_compact_bytes(&self) -> FsDkrResult<Vec<u8>> {
+        CompactLocalKey::compact(self)?.to_bytes()
+    }
+}
+
+/// Decodes a key encoded by [CompressLocalKey::to_compact_bytes].
+pub fn from_compact_bytes<E: Curve>(bytes: &[u8]) -> FsDkrResult<LocalKey<E>> {
+    CompactLocalKey::from_bytes(bytes)?.expand()
+}
+
+impl<E: Curve> CompactLocalKey<E> {
+    /// Deduplicates the moduli of `local_key`. Fails if a Paillier key does not carry the square
+    /// of its modulus as `nn`, it could not be restored identically.
+    pub fn compact(local_key: &LocalKey<E>) -> FsDkrResult<Self> {
+        let mut moduli = Vec::new();
+        let mut positions: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut position_of = |modulus: &BigInt| {
+            *positions.entry(modulus.to_bytes()).or_insert_with(|| {
+                moduli.push(modulus.clone());
+                moduli.len() - 1
+            })
+        };
+
+        let paillier_moduli = local_key
+            .paillier_key_vec
+            .iter()
+            .map(|ek| {
+                if ek.nn != &ek.n * &ek.n {
+                    return Err(serialization_error("a Paillier key has an inconsistent nn"));
+                }
+                Ok(position_of(&ek.n))
+            })
+            .collect::<FsDkrResult<Vec<_>>>()?;
+        let dlog_statements = local_key
+            .h1_h2_n_tilde_vec
+            .iter()
+            .map(|statement| CompactDLogStatement {
+                modulus: position_of(&statement.N),
+                g: statement.g.clone(),
+                ni: statement.ni.clone(),
+            })
+            .collect();
+
+        let mut stripped = local_key.clone();
+        stripped.paillier_key_vec = Vec::new();
+        stripped.h1_h2_n_tilde_vec = Vec::new();
+        Ok(CompactLocalKey {
+            version: COMPACT_KEY_VERSION,
+            moduli,
+            paillier_moduli,
+            dlog_statements,
+            local_key: stripped,
+        })
+    }
+
+    /// Restores the [LocalKey]. Fails with [FsDkrError::SerializationError] if a modulus is
+    /// referred to by a position out of range.
+    pub fn expand(self) -> FsDkrResult<LocalKey<E>> {
+        let modulus = |position: usize| {
+            self.moduli
+                .get(position)
+                .ok_or_else(|| serialization_error("a modulus position is out of range"))
+        };
+        let paillier_key_vec = self
+            .paillier_moduli
+            .iter()
+            .map(|position| {
+                let n = modulus(*position)?;
+                Ok(EncryptionKey {
+                    n: n.clone(),
+                    nn: n * n,
+                })
+            })
+            .collect::<FsDkrResult<Vec<_>>>()?;
+        let h1_h2_n_tilde_vec = self
+            .dlog_statements
+            .iter()
+            .map(|statement| {
+                Ok(DLogStatement {
+                    N: modulus(statement.modulus)?.clone(),
+                    g: statement.g.clone(),
+                    ni: statement.ni.clone(),
+                })
+            })
+            .collect::<FsDkrResult<Vec<_>>>()?;
+
+        let mut local_key = self.local_key;
+        local_key.paillier_key_vec = paillier_key_vec;
+        local_key.h1_h2_n_tilde_vec = h1_h2_n_tilde_vec;
+        Ok(local_key)
+    }
+
+    pub fn to_bytes(&self) -> FsDkrResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Decodes a compact key, rejecting other versions with
+    /// [FsDkrError::UnsupportedMessageVersion].
+    pub fn from_bytes(bytes: &[u8]) -> FsDkrResult<Self> {
+        let compact: Self =
+            serde_json::from_slice(bytes).map_err(|e| FsDkrError::SerializationError {
+                reason: e.to_string(),
+            })?;
+        if compact.version != COMPACT_KEY_VERSION {
+            return Err(FsDkrError::UnsupportedMessageVersion {
+                found: compact.version,
+                supported: COMPACT_KEY_VERSION,
+            });
+        }
+        Ok(compact)
+    }
+}
+
+fn serialization_error(reason: &str) -> FsDkrError {
+    FsDkrError::SerializationError {
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,144 @@
+//! Paillier decryption with precomputed CRT parameters.
+//!
+//! Decrypting modulo `p^2` and `q^2` separately and recombining with the CRT needs a handful of
+//! values that depend only on the decryption key: `p^2`, `q^2`, the constants `hp` and `hq`, the
+//! inverse of `p` modulo `q`, and for the re-encryption check of collect the inverse of `N`
+//! modulo `phi(N)`. A [DecryptionContext] computes them once, so that a party decrypting several
+//! times with the same key, e.g. retrying a collect as more messages arrive, does not recompute
+//! them on every call. Note that collect rotates the key: the context is built for the
+//! [DecryptionKey] the round is decrypted with, i.e. the `paillier_dk` of the [LocalKey] before
+//! collect.
+//!
+//! [LocalKey]: multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey
+
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::arithmetic::{Modulo, One, Zero};
+use curv::BigInt;
+use paillier::{
+    DecryptionKey, EncryptWithChosenRandomness, EncryptionKey, Paillier, Randomness, RawPlaintext,
+};
+use zeroize::Zeroize;
+
+/// Precomputed CRT parameters of a [DecryptionKey], see the [module level](self) documentation.
+pub struct DecryptionContext {
+    p: BigInt,
+    q: BigInt,
+    n: BigInt,
+    pp: BigInt,
+    qq: BigInt,
+    p_minus_one: BigInt,
+    q_minus_one: BigInt,
+    hp: BigInt,
+    hq: BigInt,
+    p_inv_q: BigInt,
+    n_inv_phi: BigInt,
+}
+
+impl DecryptionContext {
+    /// Precomputes the parameters of `dk`. Fails with [FsDkrError::InvalidDecryptionKey] if `dk`
+    /// is not a valid Paillier key, i.e. one of the required inverses does not exist.
+    pub fn new(dk: &DecryptionKey) -> FsDkrResult<Self> {
+        let p = dk.p.clone();
+        let q = dk.q.clone();
+        let n = &p * &q;
+        let pp = &p * &p;
+        let qq = &q * &q;
+        let p_minus_one = &p - BigInt::one();
+        let q_minus_one = &q - BigInt::one();
+
+        // with g = N + 1, h_p = L_p(g^(p - 1) mod p^2)^-1 mod p and likewise for q
+        let g = &n + BigInt::one();
+        let mut lp = (BigInt::mod_pow(&g, &p_minus_one, &pp) - BigInt::one()) / &p;
+        let mut lq = (BigInt::mod_pow(&g, &q_minus_one, &qq) - BigInt::one()) / &q;
+        let mut phi = &p_minus_one * &q_minus_one;
+        let (hp, hq, p_inv_q, n_inv_phi) = (
+            BigInt::mod_inv(&lp, &p),
+            BigInt::mod_inv(&lq, &q),
+            BigInt::mod_inv(&p, &q),
+            BigInt::mod_inv(&n, &phi),
+        );
+        lp.zeroize();
+        lq.zeroize();
+        phi.zeroize();
+
+        let valid = hp.is_some() && hq.is_some() && p_inv_q.is_some() && n_inv_phi.is_some();
+        // built even for an invalid key so that dropping it zeroizes the partial parameters
+        let context = DecryptionContext {
+            p,
+            q,
+            n,
+            pp,
+            qq,
+            p_minus_one,
+            q_minus_one,
+            hp: hp.unwrap_or_else(BigInt::zero),
+            hq: hq.unwrap_or_else(BigInt::zero),
+            p_inv_q: p_inv_q.unwrap_or_else(BigInt::zero),
+            n_inv_phi: n_inv_phi.unwrap_or_else(BigInt::zero),
+        };
+        if !valid {
+            return Err(FsDkrError::InvalidDecryptionKey);
+        }
+        Ok(context)
+    }
+
+    /// Returns true if the context was built for `dk`.
+    pub fn matches(&self, dk: &DecryptionKey) -> bool {
+        self.p == dk.p && self.q == dk.q
+    }
+
+    /// Decrypts `ciphertext`, with the same result as `Paillier::decrypt` with the key the
+    /// context was built for.
+    pub fn decrypt(&self, ciphertext: &BigInt) -> BigInt {
+        let cp = BigInt::mod_pow(ciphertext, &self.p_minus_one, &self.pp);
+        let mp = BigInt::mod_mul(&((cp - BigInt::one()) / &self.p), &self.hp, &self.p);
+        let cq = BigInt::mod_pow(ciphertext, &self.q_minus_one, &self.qq);
+        let mq = BigInt::mod_mul(&((cq - BigInt::one()) / &self.q), &self.hq, &self.q);
+
+        // m = mp + p * ((mq - mp) * p^-1 mod q)
+        let u = BigInt::mod_mul(&BigInt::mod_sub(&mq, &mp, &self.q), &self.p_inv_q, &self.q);
+        mp + &self.p * u
+    }
+
+    /// Decrypts `ciphertext` and checks that re-encrypting the plaintext under `ek`, with the
+    /// randomness recovered from the ciphertext, gives `ciphertext` back. This fails with
+    /// [FsDkrError::DecryptionVerificationFailed] if the context is not for the decryption key of
+    /// `ek`, which would otherwise silently produce a wrong share.
+    pub fn decrypt_verified(
+        &self,
+        ek: &EncryptionKey,
+        ciphertext: &BigInt,
+        party_index: u16,
+    ) -> FsDkrResult<BigInt> {
+        let plaintext = self.decrypt(ciphertext);
+
+        // c = (1 + N)^m * r^N mod N^2, so r = c^(N^-1 mod phi(N)) mod N
+        let randomness = BigInt::mod_pow(ciphertext, &self.n_inv_phi, &self.n);
+        let reencrypted = Paillier::encrypt_with_chosen_randomness(
+            ek,
+            RawPlaintext::from(plaintext.clone()),
+            &Randomness::from(randomness),
+        )
+        .0
+        .into_owned();
+        if reencrypted != *ciphertext {
+            return Err(FsDkrError::DecryptionVerificationFailed { party_index });
+        }
+        Ok(plaintext)
+    }
+}
+
+impl Drop for DecryptionContext {
+    fn drop(&mut self) {
+        self.p.zeroize();
+        self.q.zeroize();
+        self.pp.zeroize();
+        self.qq.zeroize();
+        self.p_minus_one.zeroize();
+        self.q_minus_one.zeroize();
+        self.hp.zeroize();
+        self.hq.zeroize();
+        self.p_inv_q.zeroize();
+        self.n_inv_phi.zeroize();
+    }
+}
@@ -0,0 +1,236 @@
+//! Storage of a [LocalKey] as the difference to its previous version.
+//!
+//! A backup that keeps a snapshot of the key after every refresh stores mostly identical data: a
+//! refresh leaves the group public key, the index and the parameters unchanged, and entries of
+//! `pk_vec`, `paillier_key_vec` and `h1_h2_n_tilde_vec` that a round did not touch stay as they
+//! were. [DiffLocalKey::delta_from] encodes only the fields and vector entries that differ from
+//! the previous key into a [LocalKeyDelta], and [apply_delta] restores the new key from the
+//! previous one. Both keys are identified by the SHA-256 digest of their serde encoding: a delta
+//! is rejected with [FsDkrError::DeltaBaseMismatch] when applied to another key than the one it
+//! was computed from, and the restored key is checked to encode to exactly the bytes of the key
+//! the delta was computed for. Like the serde encoding of a [LocalKey], a delta carries a changed
+//! secret share or Paillier decryption key in the clear.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::{Curve, Point};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::SharedKeys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{DecryptionKey, EncryptionKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zk_paillier::zkproofs::DLogStatement;
+
+/// Version of the [LocalKeyDelta] encoding.
+pub const KEY_DELTA_VERSION: u16 = 1;
+
+/// The entries of a vector that differ from the previous version, by position, and its new
+/// length. An entry beyond the previous length is always included.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct VecDelta<T> {
+    len: usize,
+    changed: Vec<(usize, T)>,
+}
+
+/// The fields of a [LocalKey] that differ from its previous version, see the
+/// [module level](self) documentation. A field that is unchanged is `None`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct LocalKeyDelta<E: Curve> {
+    pub version: u16,
+    /// SHA-256 of the serde encoding of the key the delta applies to.
+    pub previous_digest: [u8; 32],
+    /// SHA-256 of the serde encoding of the key the delta restores.
+    pub digest: [u8; 32],
+    paillier_dk: Option<DecryptionKey>,
+    pk_vec: Option<VecDelta<Point<E>>>,
+    keys_linear: Option<SharedKeys<E>>,
+    paillier_key_vec: Option<VecDelta<EncryptionKey>>,
+    y_sum_s: Option<Point<E>>,
+    h1_h2_n_tilde_vec: Option<VecDelta<DLogStatement>>,
+    vss_scheme: Option<VerifiableSS<E, Sha256>>,
+    i: Option<u16>,
+    t: Option<u16>,
+    n: Option<u16>,
+}
+
+/// Delta encoding of a [LocalKey] for storage.
+pub trait DiffLocalKey<E: Curve> {
+    /// The fields of the key that differ from `previous`, the key it was refreshed from.
+    fn delta_from(&self, previous: &LocalKey<E>) -> FsDkrResult<LocalKeyDelta<E>>;
+}
+
+impl<E: Curve> DiffLocalKey<E> for LocalKey<E> {
+    fn delta_from(&self, previous: &LocalKey<E>) -> FsDkrResult<LocalKeyDelta<E>> {
+        Ok(LocalKeyDelta {
+            version: KEY_DELTA_VERSION,
+            previous_digest: key_digest(previous)?,
+            digest: key_digest(self)?,
+            paillier_dk: Some(self.paillier_dk.clone()).filter(|_| {
+                self.paillier_dk.p != previous.paillier_dk.p
+                    || self.paillier_dk.q != previous.paillier_dk.q
+            }),
+            pk_vec: vec_delta(&previous.pk_vec, &self.pk_vec, |old, new| old == new),
+            keys_linear: Some(self.keys_linear.clone()).filter(|_| {
+                self.keys_linear.x_i != previous.keys_linear.x_i
+                    || self.keys_linear.y != previous.keys_linear.y
+            }),
+            paillier_key_vec: vec_delta(
+                &previous.paillier_key_vec,
+                &self.paillier_key_vec,
+                |old, new| old.n == new.n && old.nn == new.nn,
+            ),
+            y_sum_s: Some(self.y_sum_s.clone()).filter(|_| self.y_sum_s != previous.y_sum_s),
+            h1_h2_n_tilde_vec: vec_delta(
+                &previous.h1_h2_n_tilde_vec,
+                &self.h1_h2_n_tilde_vec,
+                |old, new| old.N == new.N && old.g == new.g && old.ni == new.ni,
+            ),
+            vss_scheme: Some(self.vss_scheme.clone()).filter(|_| {
+                self.vss_scheme.parameters.threshold != previous.vss_scheme.parameters.threshold
+                    || self.vss_scheme.parameters.share_count
+                        != previous.vss_scheme.parameters.share_count
+                    || self.vss_scheme.commitments != previous.vss_scheme.commitments
+            }),
+            i: Some(self.i).filter(|i| *i != previous.i),
+            t: Some(self.t).filter(|t| *t != previous.t),
+            n: Some(self.n).filter(|n| *n != previous.n),
+        })
+    }
+}
+
+/// Restores the key `delta` was computed for from `previous`, see the [module level](self)
+/// documentation. Fails with [FsDkrError::DeltaBaseMismatch] if `previous` is not the key the
+/// delta was computed from, and with [FsDkrError::SerializationError] if the delta is malformed
+/// or the restored key does not encode to the bytes of the key it was computed for.
+pub fn apply_delta<E: Curve>(
+    previous: &LocalKey<E>,
+    delta: &LocalKeyDelta<E>,
+) -> FsDkrResult<LocalKey<E>> {
+    if delta.version != KEY_DELTA_VERSION {
+        return Err(FsDkrError::UnsupportedMessageVersion {
+            found: delta.version,
+            supported: KEY_DELTA_VERSION,
+        });
+    }
+    if key_digest(previous)? != delta.previous_digest {
+        return Err(FsDkrError::DeltaBaseMismatch);
+    }
+
+    let local_key = LocalKey {
+        paillier_dk: delta
+            .paillier_dk
+            .clone()
+            .unwrap_or_else(|| previous.paillier_dk.clone()),
+        pk_vec: apply_vec_delta(&previous.pk_vec, &delta.pk_vec)?,
+        keys_linear: delta
+            .keys_linear
+            .clone()
+            .unwrap_or_else(|| previous.keys_linear.clone()),
+        paillier_key_vec: apply_vec_delta(&previous.paillier_key_vec, &delta.paillier_key_vec)?,
+        y_sum_s: delta
+            .y_sum_s
+            .clone()
+            .unwrap_or_else(|| previous.y_sum_s.clone()),
+        h1_h2_n_tilde_vec: apply_vec_delta(&previous.h1_h2_n_tilde_vec, &delta.h1_h2_n_tilde_vec)?,
+        vss_scheme: delta
+            .vss_scheme
+            .clone()
+            .unwrap_or_else(|| previous.vss_scheme.clone()),
+        i: delta.i.unwrap_or(previous.i),
+        t: delta.t.unwrap_or(previous.t),
+        n: delta.n.unwrap_or(previous.n),
+    };
+    if key_digest(&local_key)? != delta.digest {
+        return Err(serialization_error(
+            "the restored key does not match the key the delta was computed for",
+        ));
+    }
+    Ok(local_key)
+}
+
+impl<E: Curve> LocalKeyDelta<E> {
+    /// Returns true if the key did not change, the delta restores the key it was computed from.
+    pub fn is_empty(&self) -> bool {
+        self.previous_digest == self.digest
+    }
+
+    pub fn to_bytes(&self) -> FsDkrResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| FsDkrError::SerializationError {
+            reason: e.to_string(),
+        })
+    }
+
+    /// Decodes a delta, rejecting other versions with [FsDkrError::UnsupportedMessageVersion].
+    pub fn from_bytes(bytes: &[u8]) -> FsDkrResult<Self> {
+        let delta: Self =
+            serde_json::from_slice(bytes).map_err(|e| FsDkrError::SerializationError {
+                reason: e.to_string(),
+            })?;
+        if delta.version != KEY_DELTA_VERSION {
+            return Err(FsDkrError::UnsupportedMessageVersion {
+                found: delta.version,
+                supported: KEY_DELTA_VERSION,
+            });
+        }
+        Ok(delta)
+    }
+}
+
+fn key_digest<E: Curve>(local_key: &LocalKey<E>) -> FsDkrResult<[u8; 32]> {
+    let bytes = serde_json::to_vec(local_key).map_err(|e| FsDkrError::SerializationError {
+        reason: e.to_string(),
+    })?;
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&Sha256::digest(&bytes));
+    Ok(digest)
+}
+
+fn vec_delta<T: Clone>(
+    previous: &[T],
+    current: &[T],
+    unchanged: impl Fn(&T, &T) -> bool,
+) -> Option<VecDelta<T>> {
+    let changed: Vec<(usize, T)> = current
+        .iter()
+        .enumerate()
+        .filter(|(position, entry)| {
+            previous
+                .get(*position)
+                .map_or(true, |old| !unchanged(old, entry))
+        })
+        .map(|(position, entry)| (position, entry.clone()))
+        .collect();
+    if changed.is_empty() && previous.len() == current.len() {
+        return None;
+    }
+    Some(VecDelta {
+        len: current.len(),
+        changed,
+    })
+}
+
+fn apply_vec_delta<T: Clone>(previous: &[T], delta: &Option<VecDelta<T>>) -> FsDkrResult<Vec<T>> {
+    let delta = match delta {
+        Some(delta) => delta,
+        None => return Ok(previous.to_vec()),
+    };
+    let mut entries: Vec<Option<T>> = previous.iter().take(delta.len).cloned().map(Some).collect();
+    entries.resize(delta.len, None);
+    for (position, entry) in delta.changed.iter() {
+        let slot = entries
+            .get_mut(*position)
+            .ok_or_else(|| serialization_error("a changed entry is beyond the length"))?;
+        *slot = Some(entry.clone());
+    }
+    entries
+        .into_iter()
+        .map(|entry| entry.ok_or_else(|| serialization_error("a new entry is missing")))
+        .collect()
+}
+
+fn serialization_error(reason: &str) -> FsDkrError {
+    FsDkrError::SerializationError {
+        reason: reason.to_string(),
+    }
+}
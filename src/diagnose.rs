@@ -0,0 +1,200 @@
+//! Self-consistency checks explaining why a collected key can not sign.
+//!
+//! A collect that succeeded can still leave a [LocalKey] GG20 signing fails on, e.g. a joining
+//! party's key with placeholder slots in the quorum, or a key whose public shares disagree with
+//! its share or with the group public key. The signing machine only reports that a round failed.
+//! [diagnose_local_key] runs every check that only needs the key itself and returns a
+//! [Diagnostic] per problem found, each of which displays as a human-readable finding. An empty
+//! list means the key is consistent, it does not prove that the other parties hold keys of the
+//! same committee, see
+//! [committee_commitment](crate::local_key_ext::FsDkrLocalKeyExt::committee_commitment) for that.
+
+use crate::local_key_ext::{active_indices, public_share_at};
+use curv::elliptic::curves::{Curve, Point};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A problem found by [diagnose_local_key].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Diagnostic {
+    /// The threshold leaves no quorum in the committee.
+    ThresholdNotBelowCommitteeSize { t: u16, n: u16 },
+    /// The index of the party is outside of `1..=n`.
+    PartyIndexOutOfRange { index: u16, n: u16 },
+    /// A per-party vector of the key does not have one entry per party.
+    VectorLengthMismatch { field: String, len: usize, n: u16 },
+    /// `keys_linear.y` is not the public point of the share `keys_linear.x_i`.
+    ShareScalarMismatch,
+    /// The public share of the party in `pk_vec` is not the public point of its share.
+    OwnPublicShareMismatch,
+    /// The Paillier decryption key does not belong to the encryption key of the party's slot.
+    OwnDecryptionKeyMismatch,
+    /// The slot holds a placeholder instead of a Paillier key and a dlog statement, the party at
+    /// the index can not take part in signing.
+    PlaceholderSlot { party_index: u16 },
+    /// Fewer than `t + 1` slots hold a key, no quorum can sign.
+    QuorumUnavailable { active_slots: u16, threshold: u16 },
+    /// The public shares do not interpolate to the group public key `y_sum_s`.
+    PublicKeyMismatch,
+    /// The public share of the party is not on the polynomial of the other public shares.
+    PublicShareOffPolynomial { party_index: u16 },
+    /// The sharing parameters of `vss_scheme` are not the `t` and `n` of the key.
+    VssParametersMismatch { threshold: u16, share_count: u16 },
+    /// `vss_scheme` does not commit to a polynomial of degree `t`.
+    VssDegreeMismatch { commitments: usize, expected: usize },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::ThresholdNotBelowCommitteeSize { t, n } => {
+                write!(f, "threshold {} needs more than the {} parties", t, n)
+            }
+            Diagnostic::PartyIndexOutOfRange { index, n } => {
+                write!(f, "party index {} is outside of the {} parties", index, n)
+            }
+            Diagnostic::VectorLengthMismatch { field, len, n } => {
+                write!(f, "{} has {} entries for {} parties", field, len, n)
+            }
+            Diagnostic::ShareScalarMismatch => {
+                write!(f, "keys_linear.y is not the public point of the share")
+            }
+            Diagnostic::OwnPublicShareMismatch => {
+                write!(
+                    f,
+                    "the own entry of pk_vec is not the public point of the share"
+                )
+            }
+            Diagnostic::OwnDecryptionKeyMismatch => write!(
+                f,
+                "the Paillier decryption key does not belong to the own encryption key"
+            ),
+            Diagnostic::PlaceholderSlot { party_index } => write!(
+                f,
+                "slot {} holds a placeholder key and can not sign",
+                party_index
+            ),
+            Diagnostic::QuorumUnavailable {
+                active_slots,
+                threshold,
+            } => write!(
+                f,
+                "only {} slots hold a key, a quorum needs {}",
+                active_slots,
+                threshold + 1
+            ),
+            Diagnostic::PublicKeyMismatch => {
+                write!(f, "pk_vec does not interpolate to the group public key")
+            }
+            Diagnostic::PublicShareOffPolynomial { party_index } => write!(
+                f,
+                "the public share of party {} disagrees with the other public shares",
+                party_index
+            ),
+            Diagnostic::VssParametersMismatch {
+                threshold,
+                share_count,
+            } => write!(
+                f,
+                "vss_scheme shares {} out of {}, not the parameters of the key",
+                threshold, share_count
+            ),
+            Diagnostic::VssDegreeMismatch {
+                commitments,
+                expected,
+            } => write!(
+                f,
+                "vss_scheme has {} commitments, expected {}",
+                commitments, expected
+            ),
+        }
+    }
+}
+
+/// Runs the self-consistency checks of the [module level](self) documentation on `key`: the
+/// threshold and index, the lengths of the per-party vectors, the share against its public
+/// point and `pk_vec`, the own Paillier key, the placeholder slots, the public shares against
+/// each other and against `y_sum_s`, and the parameters and degree of `vss_scheme`. Returns
+/// every problem found, in that order, and an empty list for a consistent key.
+pub fn diagnose_local_key<E: Curve>(key: &LocalKey<E>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let (t, n) = (key.t, key.n);
+    if t >= n {
+        diagnostics.push(Diagnostic::ThresholdNotBelowCommitteeSize { t, n });
+    }
+    let own_index_valid = key.i >= 1 && key.i <= n;
+    if !own_index_valid {
+        diagnostics.push(Diagnostic::PartyIndexOutOfRange { index: key.i, n });
+    }
+
+    let lengths = [
+        ("pk_vec", key.pk_vec.len()),
+        ("paillier_key_vec", key.paillier_key_vec.len()),
+        ("h1_h2_n_tilde_vec", key.h1_h2_n_tilde_vec.len()),
+    ];
+    let mut lengths_valid = true;
+    for (field, len) in lengths {
+        if len != n as usize {
+            lengths_valid = false;
+            diagnostics.push(Diagnostic::VectorLengthMismatch {
+                field: field.to_string(),
+                len,
+                n,
+            });
+        }
+    }
+
+    if Point::<E>::generator() * &key.keys_linear.x_i != key.keys_linear.y {
+        diagnostics.push(Diagnostic::ShareScalarMismatch);
+    }
+    if own_index_valid && lengths_valid {
+        let position = (key.i - 1) as usize;
+        if key.pk_vec[position] != key.keys_linear.y {
+            diagnostics.push(Diagnostic::OwnPublicShareMismatch);
+        }
+        if &key.paillier_dk.p * &key.paillier_dk.q != key.paillier_key_vec[position].n {
+            diagnostics.push(Diagnostic::OwnDecryptionKeyMismatch);
+        }
+    }
+
+    let active = active_indices(key);
+    for party_index in (1..=n).filter(|party_index| !active.contains(party_index)) {
+        diagnostics.push(Diagnostic::PlaceholderSlot { party_index });
+    }
+    if active.len() <= t as usize {
+        diagnostics.push(Diagnostic::QuorumUnavailable {
+            active_slots: active.len() as u16,
+            threshold: t,
+        });
+    } else if key.pk_vec.len() == n as usize {
+        let quorum = &active[..t as usize + 1];
+        if public_share_at(&key.pk_vec, quorum, 0) != key.y_sum_s {
+            diagnostics.push(Diagnostic::PublicKeyMismatch);
+        }
+        for party_index in active[t as usize + 1..].iter() {
+            if public_share_at(&key.pk_vec, quorum, *party_index)
+                != key.pk_vec[(*party_index - 1) as usize]
+            {
+                diagnostics.push(Diagnostic::PublicShareOffPolynomial {
+                    party_index: *party_index,
+                });
+            }
+        }
+    }
+
+    let parameters = &key.vss_scheme.parameters;
+    if parameters.threshold != t || parameters.share_count != n {
+        diagnostics.push(Diagnostic::VssParametersMismatch {
+            threshold: parameters.threshold,
+            share_count: parameters.share_count,
+        });
+    }
+    if key.vss_scheme.commitments.len() != t as usize + 1 {
+        diagnostics.push(Diagnostic::VssDegreeMismatch {
+            commitments: key.vss_scheme.commitments.len(),
+            expected: t as usize + 1,
+        });
+    }
+    diagnostics
+}
@@ -0,0 +1,64 @@
+//! Pre-generation of the dlog statements of joining parties.
+//!
+//! Most of the time of [JoinMessage::distribute](crate::add_party_message::JoinMessage::distribute)
+//! is spent generating the two primes of the `N tilde` modulus of the dlog statement, a Paillier
+//! modulus of [PAILLIER_KEY_SIZE](crate::PAILLIER_KEY_SIZE) bits. They are random primes, not
+//! safe primes. A [DLogStatementPool] generates statements with their composite dlog proofs on a
+//! background thread ahead of time, and
+//! [JoinMessage::distribute_with_pool](crate::add_party_message::JoinMessage::distribute_with_pool)
+//! takes a ready one instead of generating it. The pool holds at most `capacity` statements and
+//! the background thread generates a new one whenever one is taken, so the memory of the pool is
+//! bounded and an idle pool does no work. The thread stops when the pool is dropped.
+//!
+//! Every statement is handed out once: a statement used in two join messages would link the two
+//! joining parties and share the trapdoor of their ring between them.
+
+use crate::add_party_message::{generate_dlog_statement_proofs, KEYGEN_RETRIES};
+use crate::error::{FsDkrError, FsDkrResult};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use zk_paillier::zkproofs::{CompositeDLogProof, DLogStatement};
+
+/// A dlog statement with its composite dlog proofs for the bases `h1` and `h2`.
+pub type DLogStatementProofs = (DLogStatement, CompositeDLogProof, CompositeDLogProof);
+
+/// Dlog statements generated in the background, see the [module level](self) documentation.
+pub struct DLogStatementPool {
+    receiver: Receiver<DLogStatementProofs>,
+}
+
+impl DLogStatementPool {
+    /// Starts a background thread that keeps up to `capacity` statements ready, plus the one it
+    /// has generated and waits to hand over.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        thread::spawn(move || {
+            // the send blocks while the pool is full and fails once the pool is dropped, a
+            // generation that fails stops the thread and closes the pool
+            while let Ok(statement) = generate_dlog_statement_proofs(KEYGEN_RETRIES) {
+                if sender.send(statement).is_err() {
+                    break;
+                }
+            }
+        });
+        DLogStatementPool { receiver }
+    }
+
+    /// Takes a statement, waiting for the background thread if none is ready. Fails with
+    /// [FsDkrError::DLogStatementPoolClosed] if the background thread stopped, i.e. failed to
+    /// generate a statement.
+    pub fn pop(&self) -> FsDkrResult<DLogStatementProofs> {
+        self.receiver
+            .recv()
+            .map_err(|_| FsDkrError::DLogStatementPoolClosed)
+    }
+
+    /// Takes a statement if one is ready, without waiting.
+    pub fn try_pop(&self) -> FsDkrResult<Option<DLogStatementProofs>> {
+        match self.receiver.try_recv() {
+            Ok(statement) => Ok(Some(statement)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(FsDkrError::DLogStatementPoolClosed),
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Entropy sources of the distribute phase.
+//!
+//! [RefreshMessage::distribute_with_rng](crate::refresh_message::RefreshMessage::distribute_with_rng)
+//! and [JoinMessage::distribute_with_rng](crate::add_party_message::JoinMessage::distribute_with_rng)
+//! draw every random value of the message from a caller supplied [CryptoRng]: the Paillier keys,
+//! the ring-Pedersen and dlog statement parameters, the re-sharing polynomial, the encryption
+//! randomness and the nonces of all proofs, including the proof of knowledge of the secret of
+//! the re-sharing polynomial and the composite dlog proofs, which are built in this crate for
+//! that purpose. The same generator state therefore produces the same message. They fail
+//! closed: a draw that the generator can not serve fails the distribute with
+//! [FsDkrError::EntropySourceUnavailable] instead of falling back to another source. The other
+//! distribute functions draw from the operating system generator.
+//!
+//! [HardwareRng], enabled by the `hardware-rng` feature, draws every byte from the RDRAND
+//! instruction. It can only be constructed on a CPU that supports RDRAND and returned a value
+//! when probed, and reports a failed draw to the distribute rather than falling back to another
+//! source if the instruction stops delivering.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::paillier_backend::PaillierBackend;
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::{Curve, Scalar};
+use curv::BigInt;
+use paillier::{DecryptionKey, EncryptionKey};
+#[cfg(feature = "hardware-rng")]
+use rand_core::Error;
+use rand_core::{CryptoRng, RngCore};
+#[cfg(feature = "hardware-rng")]
+use std::num::NonZeroU32;
+
+/// Number of Miller-Rabin rounds a prime candidate of a Paillier key has to pass.
+const PRIME_TEST_ROUNDS: u32 = 40;
+
+/// Where a distribute draws its randomness from, see the [module level](self) documentation.
+pub(crate) enum Entropy<'r> {
+    /// The operating system generator, through the sampling of the dependencies.
+    Os,
+    /// A caller supplied generator, every draw fails closed.
+    Rng(&'r mut dyn RngCore),
+}
+
+impl<'r> Entropy<'r> {
+    pub(crate) fn from_rng<R: RngCore + CryptoRng>(rng: &'r mut R) -> Self {
+        Entropy::Rng(rng)
+    }
+
+    /// Samples uniformly below `upper`, which must be positive.
+    pub(crate) fn sample_below(&mut self, upper: &BigInt) -> FsDkrResult<BigInt> {
+        let rng = match self {
+            Entropy::Os => return Ok(BigInt::sample_below(upper)),
+            Entropy::Rng(rng) => rng,
+        };
+        let bits = upper.bit_length();
+        let mut bytes = vec![0u8; (bits + 7) / 8];
+        // rejection sampling over the bit length of upper accepts at least every second draw
+        loop {
+            rng.try_fill_bytes(&mut bytes)
+                .map_err(|e| FsDkrError::EntropySourceUnavailable {
+                    reason: e.to_string(),
+                })?;
+            if bits % 8 != 0 {
+                bytes[0] &= 0xff >> (8 - bits % 8);
+            }
+            let candidate = BigInt::from_bytes(&bytes);
+            if candidate < *upper {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    /// Samples uniformly from `[lower, upper)`.
+    pub(crate) fn sample_range(&mut self, lower: &BigInt, upper: &BigInt) -> FsDkrResult<BigInt> {
+        if let Entropy::Os = self {
+            return Ok(BigInt::sample_range(lower, upper));
+        }
+        Ok(lower + self.sample_below(&(upper - lower))?)
+    }
+
+    /// Samples a unit of the multiplicative group modulo `n`.
+    pub(crate) fn sample_unit(&mut self, n: &BigInt) -> FsDkrResult<BigInt> {
+        loop {
+            let candidate = self.sample_below(n)?;
+            if candidate.gcd(n) == BigInt::one() {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    pub(crate) fn scalar<E: Curve>(&mut self) -> FsDkrResult<Scalar<E>> {
+        if let Entropy::Os = self {
+            return Ok(Scalar::<E>::random());
+        }
+        Ok(Scalar::<E>::from_bigint(
+            &self.sample_below(Scalar::<E>::group_order())?,
+        ))
+    }
+
+    /// Generates a Paillier key pair with a modulus of `modulus_bits` bits, with `B` when drawing
+    /// from the operating system generator.
+    pub(crate) fn paillier_keypair<B: PaillierBackend>(
+        &mut self,
+        modulus_bits: usize,
+    ) -> FsDkrResult<(EncryptionKey, DecryptionKey)> {
+        if let Entropy::Os = self {
+            return Ok(B::keypair(modulus_bits));
+        }
+        let p = self.sample_prime(modulus_bits / 2)?;
+        let q = loop {
+            let q = self.sample_prime(modulus_bits / 2)?;
+            if q != p {
+                break q;
+            }
+        };
+        let n = &p * &q;
+        let ek = EncryptionKey { nn: &n * &n, n };
+        Ok((ek, DecryptionKey { p, q }))
+    }
+
+    /// Samples a prime of exactly `bits` bits whose two top bits are set, so that the product of
+    /// two of them has exactly `2 * bits` bits.
+    fn sample_prime(&mut self, bits: usize) -> FsDkrResult<BigInt> {
+        let bound = BigInt::one() << bits;
+        loop {
+            let mut candidate = self.sample_below(&bound)?;
+            candidate.set_bit(bits - 1, true);
+            candidate.set_bit(bits - 2, true);
+            candidate.set_bit(0, true);
+            if candidate.is_probable_prime(PRIME_TEST_ROUNDS) {
+                return Ok(candidate);
+            }
+        }
+    }
+}
+
+/// Number of times a failed RDRAND draw is retried before the source is considered broken, as
+/// recommended by Intel.
+#[cfg(feature = "hardware-rng")]
+const RDRAND_RETRIES: usize = 10;
+
+/// An RNG backed by the RDRAND instruction, see the [module level](self) documentation.
+#[cfg(feature = "hardware-rng")]
+#[derive(Debug)]
+pub struct HardwareRng {
+    _private: (),
+}
+
+#[cfg(feature = "hardware-rng")]
+impl HardwareRng {
+    /// Returns the hardware source, or [FsDkrError::EntropySourceUnavailable] if the CPU does not
+    /// support RDRAND or the instruction does not deliver a value.
+    pub fn new() -> FsDkrResult<Self> {
+        if !rdrand_supported() {
+            return Err(FsDkrError::EntropySourceUnavailable {
+                reason: "RDRAND is not supported by this CPU".to_string(),
+            });
+        }
+        if rdrand64().is_none() {
+            return Err(FsDkrError::EntropySourceUnavailable {
+                reason: "RDRAND did not return a value".to_string(),
+            });
+        }
+        Ok(HardwareRng { _private: () })
+    }
+}
+
+#[cfg(feature = "hardware-rng")]
+impl RngCore for HardwareRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rdrand64().expect("RDRAND stopped delivering random values")
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest)
+            .expect("RDRAND stopped delivering random values")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        for chunk in dest.chunks_mut(8) {
+            let value = rdrand64().ok_or_else(|| {
+                Error::from(NonZeroU32::new(Error::CUSTOM_START).expect("non zero error code"))
+            })?;
+            chunk.copy_from_slice(&value.to_le_bytes()[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hardware-rng")]
+impl CryptoRng for HardwareRng {}
+
+#[cfg(all(feature = "hardware-rng", target_arch = "x86_64"))]
+fn rdrand_supported() -> bool {
+    is_x86_feature_detected!("rdrand")
+}
+
+#[cfg(all(feature = "hardware-rng", not(target_arch = "x86_64")))]
+fn rdrand_supported() -> bool {
+    false
+}
+
+#[cfg(all(feature = "hardware-rng", target_arch = "x86_64"))]
+fn rdrand64() -> Option<u64> {
+    if !rdrand_supported() {
+        return None;
+    }
+    // Safety: the CPU was checked to support RDRAND.
+    unsafe { rdrand64_unchecked() }
+}
+
+#[cfg(all(feature = "hardware-rng", not(target_arch = "x86_64")))]
+fn rdrand64() -> Option<u64> {
+    None
+}
+
+#[cfg(all(feature = "hardware-rng", target_arch = "x86_64"))]
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand64_unchecked() -> Option<u64> {
+    let mut value = 0u64;
+    for _ in 0..RDRAND_RETRIES {
+        if core::arch::x86_64::_rdrand64_step(&mut value) == 1 {
+            return Some(value);
+        }
+    }
+    None
+}
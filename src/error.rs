@@ -1,3 +1,4 @@
+use crate::reshuffle::RefreshPlan;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -57,4 +58,187 @@ pub enum FsDkrError {
 
     #[error("Ring pedersen proof failed for party {party_index:?}")]
     RingPedersenProofValidation { party_index: u16 },
+
+    #[error("Party {party_index:?} did not re-share the share committed to in the local key")]
+    ReshareCommitmentMismatch { party_index: u16 },
+
+    #[error("The decrypted share of party {party_index:?} is outside of the expected range")]
+    ShareOutOfRange { party_index: u16 },
+
+    #[error("The cached contribution of party {party_index:?} no longer matches the committee")]
+    StaleContribution { party_index: u16 },
+
+    #[error("No cached contribution for party {party_index:?}")]
+    MissingContribution { party_index: u16 },
+
+    #[error("The refresh message of party {party_index:?} is from epoch {found:?}, expected epoch {expected:?}")]
+    EpochMismatch {
+        party_index: u16,
+        expected: u64,
+        found: u64,
+    },
+
+    #[error("Party {party_index:?} sent inconsistent DLog statements")]
+    InconsistentDLogStatements { party_index: u16 },
+
+    #[error("Party {party_index:?} sent the identity point as a commitment or public key")]
+    InvalidCurvePoint { party_index: u16 },
+
+    #[error("Serialization failed: {reason:?}")]
+    SerializationError { reason: String },
+
+    #[error("Non canonical encoding of {field:?}")]
+    NonCanonicalEncoding { field: String },
+
+    #[error("The ceremony state does not match its phase")]
+    InconsistentCeremonyState,
+
+    #[error("Party index {index:?} is out of range for a committee of {n:?} parties")]
+    PartyIndexOutOfRange { index: u16, n: u16 },
+
+    #[error("Index {index:?} appears more than once in the quorum")]
+    DuplicateQuorumIndex { index: u16 },
+
+    #[error("Refresh message of party {party_index:?} has a malformed {field:?}")]
+    MalformedRefreshMessage { party_index: u16, field: String },
+
+    #[error("Invalid committee reshuffle: {reason:?}")]
+    InvalidReshuffle { reason: String },
+
+    #[error("The decryption key is not a valid Paillier key")]
+    InvalidDecryptionKey,
+
+    #[error("The decryption of the new share of party {party_index:?} could not be verified")]
+    DecryptionVerificationFailed { party_index: u16 },
+
+    #[error("The signature on the message of party {party_index:?} does not verify")]
+    MessageSignatureInvalid { party_index: u16 },
+
+    #[error("No verifying key is registered for party {party_index:?}")]
+    UnknownSigner { party_index: u16 },
+
+    #[error("A committee of {n:?} parties exceeds the maximum of {max:?}")]
+    CommitteeTooLarge { n: usize, max: usize },
+
+    #[error("Message version {found:?} is not supported, expected version {supported:?}")]
+    UnsupportedMessageVersion { found: u16, supported: u16 },
+
+    #[error("Only {remaining:?} parties would remain, a threshold of {threshold:?} needs more")]
+    QuorumWouldBeUnreachable { remaining: u16, threshold: u16 },
+
+    #[error("Entropy source is unavailable: {reason:?}")]
+    EntropySourceUnavailable { reason: String },
+
+    #[error("Expected the public key {expected:?}, the refresh messages carry {got:?}")]
+    UnexpectedPublicKey { expected: String, got: String },
+
+    #[error("Validation failed for the refresh messages at positions {failures:?}")]
+    ValidationFailed { failures: Vec<(usize, FsDkrError)> },
+
+    #[error("The sealed key is malformed: {reason:?}")]
+    InvalidSealedKey { reason: String },
+
+    #[error("The sealed key could not be decrypted, wrong passphrase or modified key")]
+    UnsealFailed,
+
+    #[error("The coefficient commitments of party {party_index:?} are inconsistent with the other messages")]
+    CoefficientCommitmentMismatch { party_index: u16 },
+
+    #[error("The new public shares do not reconstruct the broadcasted public key")]
+    PublicKeyReconstructionMismatch,
+
+    #[error("The background thread of the dlog statement pool stopped")]
+    DLogStatementPoolClosed,
+
+    #[error("The DLog statement of party {party_index:?} is degenerate")]
+    DegenerateDLogStatement { party_index: u16 },
+
+    #[error("Key generation failed after {attempts:?} attempts")]
+    KeyGenerationFailed { attempts: usize },
+
+    #[error("Threshold {t:?} out of {n:?} parties violates the honest majority")]
+    HonestMajorityViolation { t: u16, n: u16 },
+
+    #[error(
+        "Party {party_index:?} can not update its own encryption key without the decryption key"
+    )]
+    OwnEncryptionKeyUpdate { party_index: u16 },
+
+    #[error("The message of party {party_index:?} is not fresh")]
+    StaleMessage { party_index: u16 },
+
+    #[error("The message of party {party_index:?} was seen before")]
+    ReplayedMessage { party_index: u16 },
+
+    #[error("Party {party_index:?} has no key in the committee and can not sign")]
+    PlaceholderSlot { party_index: u16 },
+
+    #[error("Round {round:?} of the refresh chain does not verify: {reason:?}")]
+    RefreshChainBroken { round: usize, reason: String },
+
+    #[error("The share attestation of party {party_index:?} does not verify")]
+    ShareAttestationInvalid { party_index: u16 },
+
+    #[error("Simulation failed: {reason:?}")]
+    SimulationError { reason: String },
+
+    #[error("Party index {party_index:?} was used by more than one message")]
+    DuplicatePartyIndex { party_index: u16 },
+
+    #[error("Expected {expected:?} committee keys, got {paillier_keys:?} Paillier keys and {dlog_statements:?} DLog statements")]
+    CommitteeKeysLengthMismatch {
+        expected: usize,
+        paillier_keys: usize,
+        dlog_statements: usize,
+    },
+
+    #[error("The Paillier key passed to collect does not belong to party {party_index:?}")]
+    KeyPartyMismatch { party_index: u16 },
+
+    #[error("Invalid identity set: {reason:?}")]
+    InvalidIdentitySet { reason: String },
+
+    #[error("Expected {expected:?} public shares of existing slots, got {got:?}")]
+    PublicSharesLengthMismatch { expected: usize, got: usize },
+
+    #[error("The collected committee {actual:?} does not match the planned one {expected:?}")]
+    CommitteeMismatch {
+        expected: RefreshPlan,
+        actual: RefreshPlan,
+    },
+
+    #[error("The message source ran dry after {received:?} of {expected:?} refresh messages")]
+    MessageSourceExhausted { received: usize, expected: usize },
+
+    #[error("A freshly generated modulus has {bits:?} bits, expected {expected:?} bits")]
+    WeakModulusGenerated { bits: usize, expected: usize },
+
+    #[error("The share of slot {slot:?} from party {party_index:?} does not match its commitment")]
+    DecryptedShareMismatch { party_index: u16, slot: usize },
+
+    #[error("The Paillier moduli size of party: {party_index:?} is {moduli_size:?} bits, below the required {min_bits:?} bits")]
+    ModulusBelowMinimum {
+        party_index: u16,
+        moduli_size: usize,
+        min_bits: usize,
+    },
+
+    #[error("The share party {party_index:?} held before its removal is a share of the new key")]
+    RemovedShareStillValid { party_index: u16 },
+
+    #[error("Got {received:?} partial decryptions, more than {threshold:?} are needed")]
+    PartialDecryptionsBelowThreshold { threshold: u16, received: usize },
+
+    #[error("The delta was computed from another key")]
+    DeltaBaseMismatch,
+
+    #[error("Party {party_index:?} sent a message of another child committee of the split")]
+    SplitChildMismatch {
+        party_index: u16,
+        expected: u8,
+        found: Option<u8>,
+    },
+
+    #[error("The message source delivered more than {limit:?} duplicate refresh messages")]
+    MessageSourceFlooded { limit: usize },
 }
@@ -0,0 +1,66 @@
+//! Up front estimate of the cost of a refresh, for capacity planning.
+//!
+//! [estimate_work] counts the expensive operations a single existing party performs when it
+//! collects a refresh at the default [Strictness](crate::strictness::Strictness), following the
+//! code path of [RefreshMessage::collect](crate::refresh_message::RefreshMessage::collect): the
+//! verification of every proof, the decryption of the new share and the curve arithmetic on the
+//! commitments. The counts do not depend on the keys, only on the shape of the committee.
+
+use crate::params::{ShareCount, Threshold};
+use serde::{Deserialize, Serialize};
+
+/// Operation counts of a collect, see the [module level](self) documentation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkEstimate {
+    /// Paillier decryptions, the one of the new share.
+    pub paillier_decryptions: usize,
+    /// PDLwSlack proofs, one per encrypted share of every refresh message.
+    pub pdl_with_slack_verifications: usize,
+    /// Range proofs, one per encrypted share of every refresh message.
+    pub range_proof_verifications: usize,
+    /// Ring-Pedersen proofs, one per refresh and join message.
+    pub ring_pedersen_verifications: usize,
+    /// Proofs of correct Paillier key generation, one per refresh and join message.
+    pub correct_key_verifications: usize,
+    /// Composite dlog proofs, two per join message.
+    pub composite_dlog_verifications: usize,
+    /// Curve scalar multiplications outside of the proofs: checking the committed points against
+    /// the committed polynomials, combining the commitments of the quorum and deriving the new
+    /// public shares.
+    pub curve_scalar_multiplications: usize,
+}
+
+/// Estimates the work of an existing party collecting a refresh of a `t` out of `n` committee,
+/// where all `n` parties send a refresh message and `num_joiners` parties join.
+pub fn estimate_work(t: Threshold, n: ShareCount, num_joiners: u16) -> WorkEstimate {
+    let t = *t as usize;
+    let n = *n as usize;
+    let num_joiners = num_joiners as usize;
+    let new_n = n + num_joiners;
+    let quorum = t + 1;
+
+    // every committed point is checked against a polynomial with t + 1 coefficients
+    let public_share_checks = n * new_n * quorum;
+    // the constant terms of the quorum are combined into the public key
+    let aggregate_commitments = quorum;
+    // every new public share combines one committed point of each member of the quorum
+    let new_public_shares = new_n * quorum;
+    // t + 1 new public shares are interpolated into the public key
+    let public_key_reconstruction = quorum;
+    // the public share of the party's own new share
+    let own_public_share = 1;
+
+    WorkEstimate {
+        paillier_decryptions: 1,
+        pdl_with_slack_verifications: n * new_n,
+        range_proof_verifications: n * new_n,
+        ring_pedersen_verifications: n + num_joiners,
+        correct_key_verifications: n + num_joiners,
+        composite_dlog_verifications: 2 * num_joiners,
+        curve_scalar_multiplications: public_share_checks
+            + aggregate_commitments
+            + new_public_shares
+            + public_key_reconstruction
+            + own_public_share,
+    }
+}
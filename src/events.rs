@@ -0,0 +1,127 @@
+//! Membership changes of a refresh round as a stream of events.
+//!
+//! [RefreshMessage::collect_with_events] and [RefreshMessage::collect_reshuffle_with_events]
+//! return the [RefreshEvent]s of the collected round, so that a system built on event sourcing
+//! can rebuild the state of the committee by replaying them instead of diffing [LocalKey]
+//! snapshots. The events of a round come in a fixed order: the removed parties by their index in
+//! the old committee, the added parties by their index in the new committee, the change of
+//! threshold, and the parties that rotated their Paillier key by their index in the new
+//! committee. The events carry only public material.
+//!
+//! [LocalKey]: multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey
+
+use crate::add_party_message::JoinMessage;
+use crate::error::FsDkrResult;
+use crate::refresh_message::RefreshMessage;
+use crate::reshuffle::Reshuffle;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{DecryptionKey, EncryptionKey};
+use serde::{Deserialize, Serialize};
+
+/// A change to the committee made by a refresh round, see the [module level](self)
+/// documentation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RefreshEvent {
+    PartyAdded { index: u16, ek: EncryptionKey },
+    PartyRemoved { index: u16 },
+    ThresholdChanged { from: u16, to: u16 },
+    PaillierRotated { index: u16 },
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Same as [RefreshMessage::collect], also returning the [RefreshEvent]s of the round. The
+    /// removed parties are the `remove_party_indices` of the refresh messages.
+    pub fn collect_with_events(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<Vec<RefreshEvent>> {
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)?;
+        let removed: Vec<u16> = refresh_messages
+            .iter()
+            .flat_map(|refresh_message| refresh_message.remove_party_indices.iter().copied())
+            .collect();
+        round_events(
+            refresh_messages,
+            join_messages,
+            &removed,
+            local_key.t,
+            local_key.t,
+        )
+    }
+
+    /// Same as [RefreshMessage::collect_reshuffle], also returning the [RefreshEvent]s of the
+    /// round.
+    pub fn collect_reshuffle_with_events(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        reshuffle: &Reshuffle,
+    ) -> FsDkrResult<Vec<RefreshEvent>> {
+        let old_t = local_key.t;
+        RefreshMessage::collect_reshuffle(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            reshuffle,
+        )?;
+        round_events(
+            refresh_messages,
+            join_messages,
+            &reshuffle.remove,
+            old_t,
+            reshuffle.new_threshold,
+        )
+    }
+}
+
+fn round_events<E: Curve, H: Digest + Clone, const M: usize>(
+    refresh_messages: &[RefreshMessage<E, H, M>],
+    join_messages: &[JoinMessage<E, H, M>],
+    removed: &[u16],
+    old_t: u16,
+    new_t: u16,
+) -> FsDkrResult<Vec<RefreshEvent>> {
+    let mut removed = removed.to_vec();
+    removed.sort_unstable();
+    removed.dedup();
+
+    let mut added = join_messages
+        .iter()
+        .map(|join_message| Ok((join_message.get_party_index()?, join_message.ek.clone())))
+        .collect::<FsDkrResult<Vec<_>>>()?;
+    added.sort_by_key(|(index, _)| *index);
+
+    let mut rotated: Vec<u16> = refresh_messages
+        .iter()
+        .map(|refresh_message| refresh_message.party_index)
+        .collect();
+    rotated.sort_unstable();
+
+    let mut events: Vec<RefreshEvent> = removed
+        .into_iter()
+        .map(|index| RefreshEvent::PartyRemoved { index })
+        .collect();
+    events.extend(
+        added
+            .into_iter()
+            .map(|(index, ek)| RefreshEvent::PartyAdded { index, ek }),
+    );
+    if old_t != new_t {
+        events.push(RefreshEvent::ThresholdChanged {
+            from: old_t,
+            to: new_t,
+        });
+    }
+    events.extend(
+        rotated
+            .into_iter()
+            .map(|index| RefreshEvent::PaillierRotated { index }),
+    );
+    Ok(events)
+}
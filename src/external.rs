@@ -0,0 +1,153 @@
+//! Verification of the proofs of a round by an external service.
+//!
+//! Some deployments route all proof verification through a central, audited service instead of
+//! verifying in-process. [JoinMessage::proofs] and [RefreshMessage::proofs] extract the
+//! `(statement, proof)` pairs of a message as [ProofItem]s, which serialize on their own, so
+//! that they can be shipped to the service. [RefreshMessage::collect_with_verifier] then asks a
+//! [ProofVerifier] for the verdict on every item in place of verifying it, and trusts it.
+//!
+//! Only the proofs listed by [ProofItem] are routed: the PDLwSlack and range proofs of the
+//! encrypted shares, the ring-pedersen proofs, the size of the Paillier moduli and the
+//! consistency of the dlog statements are still verified in-process, as are the checks of the
+//! commitments against each other and against the public key.
+
+use crate::add_party_message::{verify_paillier_key_size_of, JoinMessage};
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::paillier_backend::{KzenPaillier, PaillierBackend};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::{MessageProofs, RefreshMessage, ShareDecryption};
+use crate::strictness::Strictness;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{DecryptionKey, EncryptionKey};
+use serde::{Deserialize, Serialize};
+use zk_paillier::zkproofs::{CompositeDLogProof, DLogStatement, NiCorrectKeyProof};
+
+/// A statement and the proof of it carried by a message, see the [module level](self)
+/// documentation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ProofItem {
+    /// A composite dlog proof of the `h1`, `h2`, `N tilde` parameters of a joining party.
+    DLog(DLogStatement, CompositeDLogProof),
+    /// The proof that a Paillier key was generated correctly.
+    PaillierKey(EncryptionKey, NiCorrectKeyProof),
+}
+
+impl ProofItem {
+    /// Verifies the proof in-process, as collect does, for a service that runs this crate.
+    pub fn verify(&self) -> bool {
+        match self {
+            ProofItem::DLog(statement, proof) => proof.verify(statement).is_ok(),
+            ProofItem::PaillierKey(ek, proof) => KzenPaillier::verify_correct_key(ek, proof),
+        }
+    }
+
+    /// The error collect reports for a rejected item of party `party_index`.
+    fn rejection(&self, party_index: u16) -> FsDkrError {
+        match self {
+            ProofItem::DLog(..) => FsDkrError::DLogProofValidation { party_index },
+            ProofItem::PaillierKey(..) => FsDkrError::PaillierVerificationError { party_index },
+        }
+    }
+}
+
+/// The verdicts of an external verification service.
+pub trait ProofVerifier {
+    /// Returns true if `item`, carried by the message of party `party_index` in the new
+    /// committee, was verified.
+    fn verify(&self, party_index: u16, item: &ProofItem) -> bool;
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
+    /// The proofs of the message that can be verified externally: the proof of the Paillier
+    /// key, then the composite dlog proofs with base `h1` and base `h2`.
+    pub fn proofs(&self) -> Vec<ProofItem> {
+        vec![
+            ProofItem::PaillierKey(self.ek.clone(), self.dk_correctness_proof.clone()),
+            ProofItem::DLog(
+                self.dlog_statement.clone(),
+                self.composite_dlog_proof_base_h1.clone(),
+            ),
+            ProofItem::DLog(
+                self.dlog_statement_base_h2(),
+                self.composite_dlog_proof_base_h2.clone(),
+            ),
+        ]
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// The proofs of the message that can be verified externally: the proof of the new Paillier
+    /// key of the sender.
+    pub fn proofs(&self) -> Vec<ProofItem> {
+        vec![ProofItem::PaillierKey(
+            self.ek.clone(),
+            self.dk_correctness_proof.clone(),
+        )]
+    }
+
+    /// Same as [RefreshMessage::collect], with the verdict on every [ProofItem] of the messages
+    /// taken from `verifier` instead of verified in-process, see the [module level](self)
+    /// documentation.
+    pub fn collect_with_verifier(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        verifier: &dyn ProofVerifier,
+    ) -> FsDkrResult<()> {
+        let new_n = refresh_messages.len() + join_messages.len();
+        // the structural checks come first, so that the proofs are indexed within bounds
+        RefreshMessage::validate_collect(
+            refresh_messages,
+            Threshold(local_key.t),
+            ShareCount(new_n as u16),
+        )?;
+        for join_message in join_messages.iter() {
+            join_message.get_party_index_in_committee(new_n as u16)?;
+        }
+
+        let verify_items = |party_index: u16, items: Vec<ProofItem>| {
+            items.iter().try_for_each(|item| {
+                if verifier.verify(party_index, item) {
+                    Ok(())
+                } else {
+                    Err(item.rejection(party_index))
+                }
+            })
+        };
+
+        for refresh_message in refresh_messages.iter() {
+            let party_index = refresh_message.party_index;
+            refresh_message.verify_share_proofs(
+                &local_key.paillier_key_vec,
+                &local_key.h1_h2_n_tilde_vec,
+                new_n,
+            )?;
+            refresh_message.verify_ring_pedersen()?;
+            verify_paillier_key_size_of(&refresh_message.ek, party_index)?;
+            verify_items(party_index, refresh_message.proofs())?;
+        }
+
+        for join_message in join_messages.iter() {
+            let party_index = join_message.get_party_index()?;
+            join_message.verify_ring_pedersen()?;
+            join_message.verify_paillier_key_size()?;
+            join_message.verify_dlog_statements_consistency()?;
+            verify_items(party_index, join_message.proofs())?;
+        }
+
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::Verified,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+}
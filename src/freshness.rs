@@ -0,0 +1,194 @@
+//! Rejecting messages that are too old or seen before.
+//!
+//! Epochs keep messages of different refreshes of a committee apart, but a complete transcript
+//! recorded by an adversary is consistent on its own and could be replayed later to force a
+//! refresh nobody asked for. A party can stamp its message with a [Freshness], the time it was
+//! created and a random nonce, with [RefreshMessage::stamp] and [JoinMessage::stamp], and the
+//! collecting parties pass a [FreshnessGuard] to [RefreshMessage::collect_fresh] or
+//! [JoinMessage::collect_fresh]. The guard rejects messages older than its `max_age` with
+//! [FsDkrError::StaleMessage], and messages whose nonce it has already accepted within that
+//! window with [FsDkrError::ReplayedMessage].
+//!
+//! The stamp is not bound by the proofs of the message, so it must be authenticated by the
+//! transport, e.g. by signing the stamped message with
+//! [JoinMessage::sign](crate::add_party_message::JoinMessage::sign). Stamping is optional, the
+//! messages of [RefreshMessage::distribute] carry none and are rejected by a guard.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use curv::arithmetic::{Converter, Samplable};
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Bit length of the nonce of a [Freshness].
+pub(crate) const NONCE_BITS: usize = 128;
+
+/// When a message was created, see the [module level](self) documentation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Freshness {
+    /// Seconds since the Unix epoch at which the message was stamped.
+    pub created_at: u64,
+    /// Random nonce, hex encoded.
+    pub nonce: String,
+}
+
+impl Freshness {
+    /// A stamp with the current time and a fresh nonce.
+    pub fn now() -> Self {
+        Freshness {
+            created_at: unix_time(),
+            nonce: BigInt::sample(NONCE_BITS).to_hex(),
+        }
+    }
+}
+
+/// The window of accepted message ages and the nonces accepted within it, kept by a collecting
+/// party across rounds.
+#[derive(Clone, Debug)]
+pub struct FreshnessGuard {
+    max_age: Duration,
+    // nonce of every accepted message, with its creation time
+    seen: HashMap<String, u64>,
+}
+
+impl FreshnessGuard {
+    pub fn new(max_age: Duration) -> Self {
+        FreshnessGuard {
+            max_age,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Checks the stamps of the messages of a round at `now`, seconds since the Unix epoch. A
+    /// message is fresh if it is stamped no more than `max_age` before or after `now`, the latter
+    /// allowing for the clock skew between the parties, and carries a nonce that was neither
+    /// accepted before nor used by another message of the round.
+    fn check(&self, stamps: &[(u16, Option<&Freshness>)], now: u64) -> FsDkrResult<()> {
+        let max_age = self.max_age.as_secs();
+        let mut nonces = HashSet::new();
+        for &(party_index, freshness) in stamps.iter() {
+            let freshness = freshness.ok_or(FsDkrError::StaleMessage { party_index })?;
+            if freshness.created_at.saturating_add(max_age) < now
+                || freshness.created_at > now.saturating_add(max_age)
+            {
+                return Err(FsDkrError::StaleMessage { party_index });
+            }
+            if self.seen.contains_key(&freshness.nonce) || !nonces.insert(&freshness.nonce) {
+                return Err(FsDkrError::ReplayedMessage { party_index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers the nonces of an accepted round, and forgets the nonces that fell out of the
+    /// window at `now`, messages carrying them are rejected as stale.
+    fn accept(&mut self, stamps: &[(u16, Option<&Freshness>)], now: u64) {
+        let max_age = self.max_age.as_secs();
+        self.seen
+            .retain(|_, created_at| created_at.saturating_add(max_age) >= now);
+        for freshness in stamps.iter().filter_map(|(_, freshness)| *freshness) {
+            self.seen
+                .insert(freshness.nonce.clone(), freshness.created_at);
+        }
+    }
+
+    /// Runs `collect` if the messages of the round are fresh, and remembers their nonces if it
+    /// succeeds. A round that fails can be collected again.
+    fn collect_checked<T>(
+        &mut self,
+        stamps: &[(u16, Option<&Freshness>)],
+        collect: impl FnOnce() -> FsDkrResult<T>,
+    ) -> FsDkrResult<T> {
+        let now = unix_time();
+        self.check(stamps, now)?;
+        let collected = collect()?;
+        self.accept(stamps, now);
+        Ok(collected)
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Stamps the message with [Freshness::now].
+    pub fn stamp(&mut self) {
+        self.freshness = Some(Freshness::now());
+    }
+
+    pub fn freshness(&self) -> Option<&Freshness> {
+        self.freshness.as_ref()
+    }
+
+    /// Same as [RefreshMessage::collect], rejecting messages that are not fresh according to
+    /// `guard`, see the [module level](self) documentation.
+    pub fn collect_fresh(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        guard: &mut FreshnessGuard,
+    ) -> FsDkrResult<()> {
+        let stamps = round_stamps(refresh_messages, join_messages)?;
+        guard.collect_checked(&stamps, || {
+            RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)
+        })
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
+    /// Stamps the message with [Freshness::now]. A message that is signed must be stamped
+    /// before.
+    pub fn stamp(&mut self) {
+        self.freshness = Some(Freshness::now());
+    }
+
+    pub fn freshness(&self) -> Option<&Freshness> {
+        self.freshness.as_ref()
+    }
+
+    /// Same as [JoinMessage::collect], rejecting messages that are not fresh according to
+    /// `guard`, see the [module level](self) documentation.
+    pub fn collect_fresh(
+        &self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: Threshold,
+        n: ShareCount,
+        guard: &mut FreshnessGuard,
+    ) -> FsDkrResult<LocalKey<E>> {
+        let stamps = round_stamps(refresh_messages, join_messages)?;
+        guard.collect_checked(&stamps, || {
+            self.collect(refresh_messages, paillier_key, join_messages, t, n)
+        })
+    }
+}
+
+/// The stamps of the messages of a round, with the party index of their sender.
+fn round_stamps<'a, E: Curve, H: Digest + Clone, const M: usize>(
+    refresh_messages: &'a [RefreshMessage<E, H, M>],
+    join_messages: &'a [JoinMessage<E, H, M>],
+) -> FsDkrResult<Vec<(u16, Option<&'a Freshness>)>> {
+    let mut stamps: Vec<(u16, Option<&Freshness>)> = refresh_messages
+        .iter()
+        .map(|refresh_message| (refresh_message.party_index, refresh_message.freshness()))
+        .collect();
+    for join_message in join_messages.iter() {
+        stamps.push((join_message.get_party_index()?, join_message.freshness()));
+    }
+    Ok(stamps)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -15,13 +15,99 @@
 //!
 
 pub mod add_party_message;
+pub mod attestation;
+pub mod audit;
+pub mod ceremony;
+pub mod ceremony_log;
+pub mod chain;
+pub mod compact;
+pub mod decryption;
+pub mod delta;
+pub mod diagnose;
+pub mod dlog_pool;
+pub mod entropy;
 pub mod error;
+pub mod estimate;
+pub mod events;
+pub mod external;
+pub mod freshness;
+pub mod local_key_ext;
+pub mod monitor;
+pub mod paillier_backend;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod params;
+pub mod party_index;
+pub mod point_to_point;
+pub mod progress;
+pub mod proxy;
 pub mod range_proofs;
+pub mod reencryption;
 pub mod refresh_message;
+pub mod relay;
+pub mod reshuffle;
 pub mod ring_pedersen_proof;
+#[cfg(feature = "sealing")]
+pub mod sealing;
+pub mod signed_message;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+pub mod size_hint;
+pub mod source;
+pub mod split;
+pub mod strictness;
+pub mod threshold_decryption;
 pub mod zk_pdl_with_slack;
 
 mod test;
 
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::arithmetic::BitManipulation;
+use curv::elliptic::curves::{Curve, Scalar};
+use curv::BigInt;
+
 pub const PAILLIER_KEY_SIZE: usize = 2048;
 pub const M_SECURITY: usize = 256;
+
+/// Largest committee the library operates on. Verifying and collecting a round takes work
+/// quadratic in the committee size, the bound keeps a forged message from claiming an
+/// arbitrarily large committee.
+pub const MAX_COMMITTEE_SIZE: usize = 1024;
+
+/// The order `q` of the group of the curve `E`. Decrypted shares are reduced modulo `q`, and the
+/// range checks on the encrypted shares are expressed in powers of `q`.
+pub fn curve_order<E: Curve>() -> BigInt {
+    Scalar::<E>::group_order().clone()
+}
+
+/// Bits a freshly generated modulus may fall short of its target size: the product of two
+/// primes of `b / 2` bits has `b - 1` or `b` bits.
+const MODULUS_SLACK_BITS: usize = 1;
+
+/// Checks that a freshly generated `modulus` has at least `expected_bits` bits, up to
+/// [MODULUS_SLACK_BITS]. A shorter modulus points to a failure of the randomness the primes were
+/// drawn from and is rejected with [FsDkrError::WeakModulusGenerated] before it is used.
+pub(crate) fn validate_generated_modulus(
+    modulus: &BigInt,
+    expected_bits: usize,
+) -> FsDkrResult<()> {
+    let bits = modulus.bit_length();
+    if bits + MODULUS_SLACK_BITS < expected_bits {
+        return Err(FsDkrError::WeakModulusGenerated {
+            bits,
+            expected: expected_bits,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that a committee of `n` parties is within [MAX_COMMITTEE_SIZE].
+pub(crate) fn validate_committee_size(n: usize) -> FsDkrResult<()> {
+    if n > MAX_COMMITTEE_SIZE {
+        return Err(FsDkrError::CommitteeTooLarge {
+            n,
+            max: MAX_COMMITTEE_SIZE,
+        });
+    }
+    Ok(())
+}
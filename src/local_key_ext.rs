@@ -0,0 +1,401 @@
+//! Refresh aware helpers for the [LocalKey] produced by keygen and by the refresh protocol.
+
+use crate::add_party_message::{JoinMessage, KeyPolicy};
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::arithmetic::{Converter, One, Zero};
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::ShamirSecretSharing;
+use curv::elliptic::curves::{Curve, Point, Scalar, Secp256k1};
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::{
+    verify, Keys, SignatureRecid,
+};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashSet;
+use zeroize::Zeroize;
+
+/// Domain separation tag of [FsDkrLocalKeyExt::committee_commitment].
+const COMMITTEE_COMMITMENT_DOMAIN: &[u8] = b"fs-dkr/committee-commitment";
+
+/// Extension methods on [LocalKey] scoped to what the refresh protocol cares about.
+pub trait FsDkrLocalKeyExt<E: Curve> {
+    /// The group public key `y_sum_s`, left unchanged by every refresh. Method call syntax
+    /// resolves to the inherent `LocalKey::public_key`, which returns an owned copy, call
+    /// `FsDkrLocalKeyExt::public_key(&key)` to borrow it instead.
+    fn public_key(&self) -> &Point<E>;
+
+    /// The secret share of the party, rotated by every refresh.
+    fn share_scalar(&self) -> &Scalar<E>;
+
+    /// Number of parties `n` of the committee.
+    fn committee_size(&self) -> u16;
+
+    /// Threshold `t`, any `t + 1` parties can sign and are needed to refresh.
+    fn threshold(&self) -> u16;
+
+    /// The sharing parameters `t` out of `n` of the key, as used by curv's secret sharing.
+    fn shamir_params(&self) -> ShamirSecretSharing;
+
+    /// Returns true if `other` holds a share of the same group key: same public key, threshold,
+    /// committee size and sharing parameters. Material rotated by a refresh (the secret share,
+    /// the Paillier keys and the party's own re-share commitments in `vss_scheme`) is ignored,
+    /// since the constant term of `vss_scheme` commits to the party's share and not to the
+    /// group secret.
+    fn same_group_as(&self, other: &LocalKey<E>) -> bool;
+
+    /// Returns true if `other` has the same public state: index, threshold, committee size,
+    /// group public key, public shares, `vss_scheme` commitments and parameters, Paillier
+    /// encryption keys and dlog statements. The secret share `keys_linear.x_i` and the Paillier
+    /// decryption key are not compared, which makes this suited to comparing the keys of
+    /// different nodes.
+    fn public_eq(&self, other: &LocalKey<E>) -> bool;
+
+    /// SEC1 compressed encoding of the group public key `y_sum_s`.
+    fn public_key_compressed(&self) -> Vec<u8>;
+
+    /// SEC1 uncompressed encoding of the group public key `y_sum_s`, e.g. the input of an
+    /// Ethereum address derivation (after dropping the leading `0x04` tag).
+    fn public_key_uncompressed(&self) -> Vec<u8>;
+
+    /// Product of the Paillier moduli `n` of all active entries of `paillier_key_vec`. The zero
+    /// keys a joining party's collect fills in for slots without a contribution are skipped.
+    fn aggregate_modulus_product(&self) -> BigInt;
+
+    /// Replaces the Paillier key and the dlog statement of another party that rotated them out
+    /// of band, without a refresh. The party broadcasts a [JoinMessage] for its own index, which
+    /// is verified like the message of a new party, see [JoinMessage::validate_for_committee],
+    /// before the slot in `paillier_key_vec` and `h1_h2_n_tilde_vec` is replaced. The party's
+    /// own slot is rejected with [FsDkrError::OwnEncryptionKeyUpdate], it needs the new
+    /// decryption key, see [FsDkrLocalKeyExt::update_own_encryption_key].
+    fn update_party_encryption_key<H: Digest + Clone, const M: usize>(
+        &mut self,
+        join_message: &JoinMessage<E, H, M>,
+    ) -> FsDkrResult<()>;
+
+    /// Same as [FsDkrLocalKeyExt::update_party_encryption_key] for the party's own rotation,
+    /// given the [Keys] the [JoinMessage] was distributed with. The old decryption key is
+    /// zeroized.
+    fn update_own_encryption_key<H: Digest + Clone, const M: usize>(
+        &mut self,
+        join_message: &JoinMessage<E, H, M>,
+        paillier_keys: Keys,
+    ) -> FsDkrResult<()>;
+
+    /// Positions in `paillier_key_vec` and `h1_h2_n_tilde_vec` of the signing `participants`,
+    /// given by party index, in the order of `participants`. Fails with
+    /// [FsDkrError::PartyIndexOutOfRange] for an index outside of the committee,
+    /// [FsDkrError::DuplicatePartyIndex] for a participant listed twice and
+    /// [FsDkrError::PlaceholderSlot] for a slot a joining party's collect filled with a zero key,
+    /// which can not take part in signing.
+    fn signing_key_indices(&self, participants: &[u16]) -> FsDkrResult<Vec<usize>>;
+
+    /// Whether the committee of the key can still sign with the party in it, see
+    /// [FsDkrLocalKeyExt::signing_viability].
+    fn is_signing_viable(&self) -> bool;
+
+    /// Counts the active slots of the committee, the ones that are not placeholders, and
+    /// whether the party's own slot is one of them, e.g. to detect after a removal that the
+    /// party was removed or that fewer than `t + 1` parties remain.
+    fn signing_viability(&self) -> ViabilityReport;
+
+    /// The public data a light client needs to verify the signatures of the committee, see
+    /// [VerificationBundle].
+    fn verification_bundle(&self) -> VerificationBundle;
+
+    /// SHA-256 commitment to the public state every party of the committee agrees on after a
+    /// refresh: `t`, `n`, the group public key and the public shares `pk_vec`, each point in its
+    /// compressed encoding. The Paillier keys, dlog statements and `vss_scheme` are left out,
+    /// they are not the same in the keys of different parties. Parties comparing their
+    /// commitments out of band detect a refresh that diverged.
+    fn committee_commitment(&self) -> [u8; 32];
+}
+
+/// The signing capacity of a committee, see [FsDkrLocalKeyExt::signing_viability].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViabilityReport {
+    /// Number of slots holding a real key.
+    pub active_slots: u16,
+    pub threshold: u16,
+    pub my_slot_active: bool,
+}
+
+impl ViabilityReport {
+    /// True if at least `t + 1` slots are active, the party's own among them.
+    pub fn is_viable(&self) -> bool {
+        self.my_slot_active && self.active_slots > self.threshold
+    }
+}
+
+/// What a light client verifying the signatures of a committee needs, exported by
+/// [FsDkrLocalKeyExt::verification_bundle]. The bundle is secret free: it holds no share, no
+/// Paillier key and no dlog statement, only values public to the whole committee, and can be
+/// distributed to verifiers instead of the [LocalKey].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationBundle {
+    /// SEC1 compressed encoding of the group public key `y_sum_s`.
+    pub public_key: Vec<u8>,
+    pub t: u16,
+    pub n: u16,
+    /// SHA-256 of the SEC1 compressed commitments of `vss_scheme`, in order. The commitments
+    /// are the ones of the party's last re-sharing, so the hash changes with every refresh while
+    /// the rest of the bundle does not.
+    pub vss_commitments_hash: Vec<u8>,
+}
+
+impl<E: Curve> FsDkrLocalKeyExt<E> for LocalKey<E> {
+    fn public_key(&self) -> &Point<E> {
+        &self.y_sum_s
+    }
+
+    fn share_scalar(&self) -> &Scalar<E> {
+        &self.keys_linear.x_i
+    }
+
+    fn committee_size(&self) -> u16 {
+        self.n
+    }
+
+    fn threshold(&self) -> u16 {
+        self.t
+    }
+
+    fn shamir_params(&self) -> ShamirSecretSharing {
+        ShamirSecretSharing {
+            threshold: self.t,
+            share_count: self.n,
+        }
+    }
+
+    fn same_group_as(&self, other: &LocalKey<E>) -> bool {
+        self.y_sum_s == other.y_sum_s
+            && self.t == other.t
+            && self.n == other.n
+            && self.vss_scheme.parameters.threshold == other.vss_scheme.parameters.threshold
+            && self.vss_scheme.parameters.share_count == other.vss_scheme.parameters.share_count
+    }
+
+    fn public_eq(&self, other: &LocalKey<E>) -> bool {
+        let same_statements = self.h1_h2_n_tilde_vec.len() == other.h1_h2_n_tilde_vec.len()
+            && self
+                .h1_h2_n_tilde_vec
+                .iter()
+                .zip(other.h1_h2_n_tilde_vec.iter())
+                .all(|(a, b)| a.N == b.N && a.g == b.g && a.ni == b.ni);
+        self.i == other.i
+            && self.t == other.t
+            && self.n == other.n
+            && self.y_sum_s == other.y_sum_s
+            && self.pk_vec == other.pk_vec
+            && self.keys_linear.y == other.keys_linear.y
+            && self.vss_scheme.commitments == other.vss_scheme.commitments
+            && self.vss_scheme.parameters.threshold == other.vss_scheme.parameters.threshold
+            && self.vss_scheme.parameters.share_count == other.vss_scheme.parameters.share_count
+            && self.paillier_key_vec == other.paillier_key_vec
+            && same_statements
+    }
+
+    fn public_key_compressed(&self) -> Vec<u8> {
+        self.y_sum_s.to_bytes(true).to_vec()
+    }
+
+    fn public_key_uncompressed(&self) -> Vec<u8> {
+        self.y_sum_s.to_bytes(false).to_vec()
+    }
+
+    fn aggregate_modulus_product(&self) -> BigInt {
+        self.paillier_key_vec
+            .iter()
+            .filter(|ek| is_active_key(ek))
+            .fold(BigInt::one(), |product, ek| product * &ek.n)
+    }
+
+    fn update_party_encryption_key<H: Digest + Clone, const M: usize>(
+        &mut self,
+        join_message: &JoinMessage<E, H, M>,
+    ) -> FsDkrResult<()> {
+        join_message.validate_for_committee(&[], self.n, &KeyPolicy::default())?;
+        let party_index = join_message.get_party_index()?;
+        if party_index == self.i {
+            return Err(FsDkrError::OwnEncryptionKeyUpdate { party_index });
+        }
+        replace_slot(self, join_message, party_index)
+    }
+
+    fn update_own_encryption_key<H: Digest + Clone, const M: usize>(
+        &mut self,
+        join_message: &JoinMessage<E, H, M>,
+        paillier_keys: Keys,
+    ) -> FsDkrResult<()> {
+        join_message.validate_for_committee(&[], self.n, &KeyPolicy::default())?;
+        let party_index = join_message.get_party_index()?;
+        if party_index != self.i || paillier_keys.ek.n != join_message.ek.n {
+            return Err(FsDkrError::PaillierVerificationError { party_index });
+        }
+        replace_slot(self, join_message, party_index)?;
+        self.paillier_dk.p.zeroize();
+        self.paillier_dk.q.zeroize();
+        self.paillier_dk = paillier_keys.dk;
+        Ok(())
+    }
+
+    fn signing_key_indices(&self, participants: &[u16]) -> FsDkrResult<Vec<usize>> {
+        let mut seen = HashSet::new();
+        participants
+            .iter()
+            .map(|party_index| {
+                let party_index = *party_index;
+                if party_index == 0 || party_index > self.n {
+                    return Err(FsDkrError::PartyIndexOutOfRange {
+                        index: party_index,
+                        n: self.n,
+                    });
+                }
+                if !seen.insert(party_index) {
+                    return Err(FsDkrError::DuplicatePartyIndex { party_index });
+                }
+                let position = (party_index - 1) as usize;
+                if !is_active_slot(self, position) {
+                    return Err(FsDkrError::PlaceholderSlot { party_index });
+                }
+                Ok(position)
+            })
+            .collect()
+    }
+
+    fn is_signing_viable(&self) -> bool {
+        self.signing_viability().is_viable()
+    }
+
+    fn signing_viability(&self) -> ViabilityReport {
+        let active_slots = active_indices(self).len() as u16;
+        ViabilityReport {
+            active_slots,
+            threshold: self.t,
+            my_slot_active: self.i >= 1 && is_active_slot(self, (self.i - 1) as usize),
+        }
+    }
+
+    fn verification_bundle(&self) -> VerificationBundle {
+        let vss_commitments_hash = self
+            .vss_scheme
+            .commitments
+            .iter()
+            .fold(Sha256::new(), |hasher, commitment| {
+                hasher.chain(&commitment.to_bytes(true))
+            })
+            .finalize()
+            .to_vec();
+        VerificationBundle {
+            public_key: self.public_key_compressed(),
+            t: self.t,
+            n: self.n,
+            vss_commitments_hash,
+        }
+    }
+
+    fn committee_commitment(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new()
+            .chain(COMMITTEE_COMMITMENT_DOMAIN)
+            .chain(self.t.to_be_bytes())
+            .chain(self.n.to_be_bytes())
+            .chain((self.pk_vec.len() as u32).to_be_bytes());
+        for point in std::iter::once(&self.y_sum_s).chain(self.pk_vec.iter()) {
+            let encoded = point.to_bytes(true);
+            hasher = hasher
+                .chain((encoded.len() as u32).to_be_bytes())
+                .chain(&encoded);
+        }
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&hasher.finalize());
+        commitment
+    }
+}
+
+// the zero keys a joining party's collect fills in for slots without a contribution are
+// placeholders
+fn is_active_key(ek: &EncryptionKey) -> bool {
+    ek.n != BigInt::zero()
+}
+
+// whether the slot at `position` (0 based) holds a real Paillier key and a dlog statement
+fn is_active_slot<E: Curve>(local_key: &LocalKey<E>, position: usize) -> bool {
+    local_key.h1_h2_n_tilde_vec.get(position).is_some()
+        && matches!(local_key.paillier_key_vec.get(position), Some(ek) if is_active_key(ek))
+}
+
+// party indices of the slots holding a real Paillier key and a dlog statement, ascending
+pub(crate) fn active_indices<E: Curve>(local_key: &LocalKey<E>) -> Vec<u16> {
+    (1..=local_key.n)
+        .filter(|party_index| is_active_slot(local_key, (party_index - 1) as usize))
+        .collect()
+}
+
+// the public share at `x` of the sharing holding the public shares `pk_vec`, interpolated from
+// the slots at the distinct indices `quorum`
+pub(crate) fn public_share_at<E: Curve>(pk_vec: &[Point<E>], quorum: &[u16], x: u16) -> Point<E> {
+    let point = |index: u16| Scalar::<E>::from(&BigInt::from(index as u64));
+    let x = point(x);
+    quorum.iter().fold(Point::<E>::zero(), |acc, j| {
+        let xj = point(*j);
+        let (num, den) = quorum.iter().filter(|m| *m != j).fold(
+            (
+                Scalar::<E>::from(&BigInt::one()),
+                Scalar::<E>::from(&BigInt::one()),
+            ),
+            |(num, den), m| {
+                let xm = point(*m);
+                (num * (&x - &xm), den * (&xj - &xm))
+            },
+        );
+        let lj = num * den.invert().expect("quorum indices are distinct");
+        acc + &pk_vec[(*j - 1) as usize] * &lj
+    })
+}
+
+// replaces the slot of `party_index`, after checking the committee keys have one entry per party
+fn replace_slot<E: Curve, H: Digest + Clone, const M: usize>(
+    local_key: &mut LocalKey<E>,
+    join_message: &JoinMessage<E, H, M>,
+    party_index: u16,
+) -> FsDkrResult<()> {
+    let n = local_key.n as usize;
+    if local_key.paillier_key_vec.len() != n || local_key.h1_h2_n_tilde_vec.len() != n {
+        return Err(FsDkrError::CommitteeKeysLengthMismatch {
+            expected: n,
+            paillier_keys: local_key.paillier_key_vec.len(),
+            dlog_statements: local_key.h1_h2_n_tilde_vec.len(),
+        });
+    }
+    let slot = (party_index - 1) as usize;
+    local_key.paillier_key_vec[slot] = join_message.ek.clone();
+    local_key.h1_h2_n_tilde_vec[slot] = join_message.dlog_statement.clone();
+    Ok(())
+}
+
+/// Returns true if the refresh from `old` to `new` rotated the key material while keeping the
+/// group key: the Paillier decryption key changed, the commitments of `vss_scheme` above the
+/// constant term differ, i.e. the share was re-shared over a fresh polynomial, and `y_sum_s` is
+/// unchanged. A refresh for which this is false provided no proactive security.
+pub fn refresh_changed_material<E: Curve>(old: &LocalKey<E>, new: &LocalKey<E>) -> bool {
+    let paillier_rotated =
+        old.paillier_dk.p != new.paillier_dk.p || old.paillier_dk.q != new.paillier_dk.q;
+    let coefficients_rotated =
+        old.vss_scheme.commitments.get(1..) != new.vss_scheme.commitments.get(1..);
+    paillier_rotated && coefficients_rotated && old.y_sum_s == new.y_sum_s
+}
+
+/// Returns true if `signature` is a valid ECDSA signature of the SHA-256 digest of `message`
+/// under the group public key `y_sum_s` of `local_key`, the digest signed by
+/// [sign_with_quorum](crate::sim::sign_with_quorum). Since a refresh keeps `y_sum_s`, a
+/// signature of the committee before the refresh verifies against the refreshed key as well.
+pub fn verify_signature(
+    local_key: &LocalKey<Secp256k1>,
+    message: &[u8],
+    signature: &SignatureRecid,
+) -> FsDkrResult<bool> {
+    let message = BigInt::from_bytes(&Sha256::digest(message));
+    Ok(verify(signature, &local_key.y_sum_s, &message).is_ok())
+}
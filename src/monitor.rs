@@ -0,0 +1,129 @@
+//! Continuous monitoring of the health of a committee.
+//!
+//! A party that stays online across refreshes can feed every contribution it receives to a
+//! [HealthMonitor]. Each message is verified as it arrives, with the same checks as
+//! [audit_transcript](crate::audit::audit_transcript), and the outcome is recorded against the
+//! sender's index, so that the monitor keeps a running view of which members of the committee
+//! behave and which sent material that failed verification.
+
+use crate::add_party_message::JoinMessage;
+use crate::audit::{audit_join_message, audit_refresh_message};
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Verification history of a single party index.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PartyHealth {
+    /// Number of messages that passed verification.
+    pub verified: u64,
+    /// Number of messages that failed verification.
+    pub failed: u64,
+    /// Highest epoch of a refresh message received from the party.
+    pub last_seen_epoch: Option<u64>,
+    /// The error of the most recent failed message.
+    pub last_failure: Option<FsDkrError>,
+}
+
+/// Running per index view of the committee, see the [module level](self) documentation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HealthMonitor {
+    parties: BTreeMap<u16, PartyHealth>,
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verifies `refresh_message` and records the outcome for its sender. `local_key` is the key
+    /// of the monitoring party for the round the message belongs to, after it ran its own
+    /// distribute, so that it holds the keys the shares were encrypted to. Returns the
+    /// verification result.
+    pub fn ingest_refresh_message<E: Curve, H: Digest + Clone, const M: usize>(
+        &mut self,
+        refresh_message: &RefreshMessage<E, H, M>,
+        local_key: &LocalKey<E>,
+    ) -> FsDkrResult<()> {
+        let n = refresh_message.points_committed_vec.len();
+        let result = HealthMonitor::verify_refresh_message(refresh_message, local_key, n);
+
+        let health = self.record(refresh_message.party_index, &result);
+        health.last_seen_epoch = health.last_seen_epoch.max(Some(refresh_message.epoch()));
+        result
+    }
+
+    /// Verifies `join_message`, which must have been assigned its index, and records the outcome
+    /// for that index. Returns the verification result.
+    pub fn ingest_join_message<E: Curve, H: Digest + Clone, const M: usize>(
+        &mut self,
+        join_message: &JoinMessage<E, H, M>,
+    ) -> FsDkrResult<()> {
+        let party_index = join_message.get_party_index()?;
+        let result = audit_join_message(join_message).map(|_| ());
+        self.record(party_index, &result);
+        result
+    }
+
+    /// The verification history of `party_index`, `None` if no message was received from it.
+    pub fn health(&self, party_index: u16) -> Option<&PartyHealth> {
+        self.parties.get(&party_index)
+    }
+
+    /// Indices of the parties whose messages all passed verification, in ascending order.
+    pub fn healthy_indices(&self) -> Vec<u16> {
+        self.parties
+            .iter()
+            .filter(|(_, health)| health.failed == 0)
+            .map(|(party_index, _)| *party_index)
+            .collect()
+    }
+
+    /// Indices of the parties that sent at least one message that failed verification, in
+    /// ascending order.
+    pub fn flagged_indices(&self) -> Vec<u16> {
+        self.parties
+            .iter()
+            .filter(|(_, health)| health.failed > 0)
+            .map(|(party_index, _)| *party_index)
+            .collect()
+    }
+
+    fn verify_refresh_message<E: Curve, H: Digest + Clone, const M: usize>(
+        refresh_message: &RefreshMessage<E, H, M>,
+        local_key: &LocalKey<E>,
+        n: usize,
+    ) -> FsDkrResult<()> {
+        refresh_message.validate_lengths(0, n)?;
+        if local_key.paillier_key_vec.len() < n || local_key.h1_h2_n_tilde_vec.len() < n {
+            return Err(FsDkrError::CommitteeKeysLengthMismatch {
+                expected: n,
+                paillier_keys: local_key.paillier_key_vec.len(),
+                dlog_statements: local_key.h1_h2_n_tilde_vec.len(),
+            });
+        }
+        audit_refresh_message(
+            refresh_message,
+            &local_key.y_sum_s,
+            &local_key.paillier_key_vec,
+            &local_key.h1_h2_n_tilde_vec,
+            n as u16,
+        )
+    }
+
+    fn record(&mut self, party_index: u16, result: &FsDkrResult<()>) -> &mut PartyHealth {
+        let health = self.parties.entry(party_index).or_default();
+        match result {
+            Ok(()) => health.verified += 1,
+            Err(error) => {
+                health.failed += 1;
+                health.last_failure = Some(error.clone());
+            }
+        }
+        health
+    }
+}
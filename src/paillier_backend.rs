@@ -0,0 +1,113 @@
+//! The Paillier operations of a refresh behind a trait.
+//!
+//! Distribute and collect of an existing party generate a Paillier key pair and prove it
+//! correct, encrypt the new shares, verify the key proofs of the other parties, combine the
+//! received ciphertexts homomorphically and decrypt the sum. [PaillierBackend] abstracts these
+//! operations, so that
+//! [RefreshMessage::distribute_with_backend](crate::refresh_message::RefreshMessage::distribute_with_backend)
+//! and
+//! [RefreshMessage::collect_with_backend](crate::refresh_message::RefreshMessage::collect_with_backend)
+//! can run on an audited implementation. Keys and ciphertexts keep the encoding of the
+//! `paillier` crate, which is part of the GG20 [LocalKey], and [KzenPaillier], the backend of
+//! every other entry point, is the `paillier` crate itself. The PDLwSlack and range proofs, the
+//! joining party's flow and [DecryptionContext] are not covered by the trait.
+//!
+//! [LocalKey]: multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey
+
+use crate::decryption::DecryptionContext;
+use crate::error::FsDkrResult;
+use curv::BigInt;
+use paillier::{
+    Add, DecryptionKey, EncryptWithChosenRandomness, EncryptionKey, KeyGeneration, Mul, Paillier,
+    Randomness, RawCiphertext, RawPlaintext,
+};
+use zk_paillier::zkproofs::{NiCorrectKeyProof, SALT_STRING};
+
+/// The Paillier operations of distribute and collect, see the [module level](self)
+/// documentation.
+pub trait PaillierBackend {
+    /// Generates a key pair with a modulus of `modulus_bits` bits.
+    fn keypair(modulus_bits: usize) -> (EncryptionKey, DecryptionKey);
+
+    /// Encrypts `plaintext` under `ek` with the chosen `randomness`.
+    fn encrypt(ek: &EncryptionKey, plaintext: &BigInt, randomness: &BigInt) -> BigInt;
+
+    /// The encryption of the sum of the plaintexts of `c1` and `c2`.
+    fn add(ek: &EncryptionKey, c1: &BigInt, c2: &BigInt) -> BigInt;
+
+    /// The encryption of the product of the plaintext of `c` and `k`.
+    fn mul(ek: &EncryptionKey, c: &BigInt, k: &BigInt) -> BigInt;
+
+    /// Decrypts `c` with `dk`, the decryption key of `ek`. An implementation must fail with
+    /// [FsDkrError::DecryptionVerificationFailed](crate::error::FsDkrError::DecryptionVerificationFailed),
+    /// reporting `party_index`, if `dk` does not belong to `ek`, instead of returning a wrong
+    /// plaintext.
+    fn decrypt(
+        dk: &DecryptionKey,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        party_index: u16,
+    ) -> FsDkrResult<BigInt>;
+
+    /// Proves that the key pair of `dk` was generated correctly.
+    fn prove_correct_key(dk: &DecryptionKey) -> NiCorrectKeyProof;
+
+    /// Verifies a proof produced by [PaillierBackend::prove_correct_key].
+    fn verify_correct_key(ek: &EncryptionKey, proof: &NiCorrectKeyProof) -> bool;
+}
+
+/// The `paillier` crate, the default backend.
+pub struct KzenPaillier;
+
+impl PaillierBackend for KzenPaillier {
+    fn keypair(modulus_bits: usize) -> (EncryptionKey, DecryptionKey) {
+        Paillier::keypair_with_modulus_size(modulus_bits).keys()
+    }
+
+    fn encrypt(ek: &EncryptionKey, plaintext: &BigInt, randomness: &BigInt) -> BigInt {
+        Paillier::encrypt_with_chosen_randomness(
+            ek,
+            RawPlaintext::from(plaintext.clone()),
+            &Randomness::from(randomness.clone()),
+        )
+        .0
+        .into_owned()
+    }
+
+    fn add(ek: &EncryptionKey, c1: &BigInt, c2: &BigInt) -> BigInt {
+        Paillier::add(
+            ek,
+            RawCiphertext::from(c1.clone()),
+            RawCiphertext::from(c2.clone()),
+        )
+        .0
+        .into_owned()
+    }
+
+    fn mul(ek: &EncryptionKey, c: &BigInt, k: &BigInt) -> BigInt {
+        Paillier::mul(
+            ek,
+            RawCiphertext::from(c.clone()),
+            RawPlaintext::from(k.clone()),
+        )
+        .0
+        .into_owned()
+    }
+
+    fn decrypt(
+        dk: &DecryptionKey,
+        ek: &EncryptionKey,
+        c: &BigInt,
+        party_index: u16,
+    ) -> FsDkrResult<BigInt> {
+        DecryptionContext::new(dk)?.decrypt_verified(ek, c, party_index)
+    }
+
+    fn prove_correct_key(dk: &DecryptionKey) -> NiCorrectKeyProof {
+        NiCorrectKeyProof::proof(dk, None)
+    }
+
+    fn verify_correct_key(ek: &EncryptionKey, proof: &NiCorrectKeyProof) -> bool {
+        proof.verify(ek, SALT_STRING).is_ok()
+    }
+}
@@ -0,0 +1,90 @@
+//! Collect with the proofs verified in parallel.
+//!
+//! The verification of the proofs of a round is CPU bound and independent per message and per
+//! encrypted share, while the single decryption of the new share may go through a device that
+//! serializes its calls, such as an HSM holding the Paillier key. [RefreshMessage::collect_parallel]
+//! verifies the PDLwSlack and range proof of every encrypted share and the key and ring-pedersen
+//! proofs of every message on the global rayon thread pool, waits for all of them, and then
+//! continues on the calling thread exactly as [RefreshMessage::collect] does: the commitments are
+//! checked against each other and against the public key, and the share is decrypted once,
+//! from the calling thread. No two decryptions are ever issued concurrently by a collect, so a
+//! caller that collects one round at a time does not contend for the device.
+//!
+//! The size of the thread pool is the one of rayon, configurable with `RAYON_NUM_THREADS` or
+//! [rayon::ThreadPoolBuilder::build_global]. When several messages fail, the error returned is
+//! the one of any failing message, not necessarily the first in order.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::FsDkrResult;
+use crate::paillier_backend::KzenPaillier;
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::{MessageProofs, RefreshMessage, ShareDecryption};
+use crate::strictness::Strictness;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use rayon::prelude::*;
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M>
+where
+    Self: Sync,
+    JoinMessage<E, H, M>: Sync,
+{
+    /// Same as [RefreshMessage::collect], verifying the proofs of the messages in parallel, see
+    /// the [module level](self) documentation.
+    pub fn collect_parallel(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        let new_n = refresh_messages.len() + join_messages.len();
+        // the structural checks come first, so that the proofs are indexed within bounds
+        RefreshMessage::validate_collect(
+            refresh_messages,
+            Threshold(local_key.t),
+            ShareCount(new_n as u16),
+        )?;
+        for join_message in join_messages.iter() {
+            join_message.get_party_index_in_committee(new_n as u16)?;
+        }
+
+        let paillier_key_vec = &local_key.paillier_key_vec;
+        let h1_h2_n_tilde_vec = &local_key.h1_h2_n_tilde_vec;
+        refresh_messages
+            .par_iter()
+            .flat_map(|refresh_message| {
+                (0..new_n)
+                    .into_par_iter()
+                    .map(move |i| (refresh_message, i))
+            })
+            .try_for_each(|(refresh_message, i)| {
+                refresh_message.verify_share_proof(i, paillier_key_vec, h1_h2_n_tilde_vec)
+            })?;
+        refresh_messages
+            .par_iter()
+            .try_for_each(|refresh_message| {
+                refresh_message.verify_ring_pedersen()?;
+                refresh_message.verify_paillier_key()
+            })?;
+        join_messages.par_iter().try_for_each(|join_message| {
+            join_message.verify_ring_pedersen()?;
+            join_message.verify_paillier_key_correctness()?;
+            join_message.verify_paillier_key_size()?;
+            join_message.verify_dlog_proofs()
+        })?;
+
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::Verified,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+}
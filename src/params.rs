@@ -0,0 +1,59 @@
+//! Typed parameters of a committee.
+//!
+//! The entry points taking both the threshold `t` and the number of parties `n` of a committee
+//! take them as a [Threshold] and a [ShareCount], so that a call with the two swapped does not
+//! compile. Both are built from and dereference to the `u16` the rest of the crate and the GG20
+//! [LocalKey] use, e.g. `Threshold(key.t)` or `ShareCount::from(key.n)`.
+//!
+//! [LocalKey]: multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey
+
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+
+/// The threshold `t` of a committee, any `t + 1` parties can sign and are needed to refresh.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Threshold(pub u16);
+
+/// The number of parties `n` of a committee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ShareCount(pub u16);
+
+impl From<u16> for Threshold {
+    fn from(t: u16) -> Self {
+        Threshold(t)
+    }
+}
+
+impl From<Threshold> for u16 {
+    fn from(t: Threshold) -> Self {
+        t.0
+    }
+}
+
+impl Deref for Threshold {
+    type Target = u16;
+
+    fn deref(&self) -> &u16 {
+        &self.0
+    }
+}
+
+impl From<u16> for ShareCount {
+    fn from(n: u16) -> Self {
+        ShareCount(n)
+    }
+}
+
+impl From<ShareCount> for u16 {
+    fn from(n: ShareCount) -> Self {
+        n.0
+    }
+}
+
+impl Deref for ShareCount {
+    type Target = u16;
+
+    fn deref(&self) -> &u16 {
+        &self.0
+    }
+}
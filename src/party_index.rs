@@ -0,0 +1,65 @@
+//! Party indices derived from long-term identity keys, without a coordinator.
+//!
+//! [derive_party_index] maps the encoding of a party's identity public key to its preferred
+//! index in `1..=n`, the SHA-256 digest of the key under a domain tag reduced modulo `n`. Two
+//! identities may prefer the same index, [derive_party_indices] resolves the collisions of a
+//! whole identity set by linear probing: the identities are taken in ascending byte order, and
+//! each one is assigned the first free index at or after its preferred index, wrapping from `n`
+//! back to 1. The assignment only depends on the set of identities, so every party derives the
+//! same indices whatever the order it learned the identities in, but the index of a party that
+//! collided depends on the other identities: a party knows its index only once it knows the full
+//! set.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use curv::cryptographic_primitives::hashing::Digest;
+use std::collections::HashSet;
+
+/// Domain separation tag of the digest of an identity key.
+const PARTY_INDEX_DOMAIN: &[u8] = b"fs-dkr/party-index";
+
+/// The preferred index in `1..=n` of the party with the encoded identity public key
+/// `identity_pubkey`, see the [module level](self) documentation. `n` must be non zero.
+pub fn derive_party_index(identity_pubkey: &[u8], n: usize) -> usize {
+    let digest = sha2::Sha256::new()
+        .chain(PARTY_INDEX_DOMAIN)
+        .chain(identity_pubkey)
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % n as u64) as usize + 1
+}
+
+/// The indices of the parties of a committee of `n` with the encoded identity public keys
+/// `identities`, in the order of `identities`, with the collisions resolved by linear probing,
+/// see the [module level](self) documentation. Fails with [FsDkrError::InvalidIdentitySet] if
+/// an identity appears twice or there are more identities than indices.
+pub fn derive_party_indices<I: AsRef<[u8]>>(identities: &[I], n: usize) -> FsDkrResult<Vec<usize>> {
+    crate::validate_committee_size(n)?;
+    if identities.len() > n {
+        return Err(FsDkrError::InvalidIdentitySet {
+            reason: format!("{} identities for {} indices", identities.len(), n),
+        });
+    }
+
+    let mut order: Vec<usize> = (0..identities.len()).collect();
+    order.sort_by(|a, b| identities[*a].as_ref().cmp(identities[*b].as_ref()));
+    if order
+        .windows(2)
+        .any(|pair| identities[pair[0]].as_ref() == identities[pair[1]].as_ref())
+    {
+        return Err(FsDkrError::InvalidIdentitySet {
+            reason: "an identity appears more than once".to_string(),
+        });
+    }
+
+    let mut taken = HashSet::new();
+    let mut indices = vec![0; identities.len()];
+    for position in order {
+        let mut index = derive_party_index(identities[position].as_ref(), n);
+        while !taken.insert(index) {
+            index = index % n + 1;
+        }
+        indices[position] = index;
+    }
+    Ok(indices)
+}
@@ -0,0 +1,155 @@
+//! Splitting a [RefreshMessage] into its broadcast and point-to-point parts.
+//!
+//! Apart from the encrypted shares, everything in a refresh message is needed by every party:
+//! the commitments, the proofs, the new Paillier key and the public key. The encrypted share in
+//! slot `i` is only ever decrypted by party `i`, so [RefreshMessage::split] separates the
+//! message into a [RefreshBroadcast] for the broadcast channel and one [EncryptedShare] per
+//! recipient, to be sent point-to-point, and [RefreshMessage::collect_point_to_point] reassembles
+//! them on the receiving side.
+//!
+//! A party that receives only its own encrypted shares can verify the proofs of its own slot but
+//! not the ones of the other slots. Transcript level verification, e.g.
+//! [audit_transcript](crate::audit::audit_transcript), needs the full messages.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use crate::strictness::Strictness;
+use curv::arithmetic::Zero;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use serde::{Deserialize, Serialize};
+
+/// The broadcast part of a [RefreshMessage]: all of it except the encrypted shares.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(bound = "E: Curve, H: Digest + Clone")]
+pub struct RefreshBroadcast<E: Curve, H: Digest + Clone, const M: usize> {
+    message: RefreshMessage<E, H, M>,
+}
+
+/// The point-to-point part of a [RefreshMessage]: the share `sender` encrypted to `recipient`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EncryptedShare {
+    pub sender: u16,
+    pub recipient: u16,
+    pub ciphertext: BigInt,
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Splits the message into the part to broadcast and the encrypted share of every recipient,
+    /// see the [module level](crate::point_to_point) documentation.
+    pub fn split(&self) -> (RefreshBroadcast<E, H, M>, Vec<EncryptedShare>) {
+        let mut message = self.clone();
+        let shares = std::mem::take(&mut message.points_encrypted_vec)
+            .into_iter()
+            .zip(1..)
+            .map(|(ciphertext, recipient)| EncryptedShare {
+                sender: self.party_index,
+                recipient,
+                ciphertext,
+            })
+            .collect();
+        (RefreshBroadcast { message }, shares)
+    }
+
+    /// Same as [RefreshMessage::collect] for messages received as the [RefreshBroadcast]s of the
+    /// senders and the [EncryptedShare]s addressed to this party. Only the proofs of the party's
+    /// own slot are verified.
+    pub fn collect_point_to_point(
+        broadcasts: &[RefreshBroadcast<E, H, M>],
+        shares: &[EncryptedShare],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        let refresh_messages = RefreshBroadcast::assemble_all(broadcasts, shares, local_key.i)?;
+        RefreshMessage::collect_own_share(
+            &refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+        )
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
+    /// Same as [JoinMessage::collect] for refresh messages received as the [RefreshBroadcast]s
+    /// of the senders and the [EncryptedShare]s addressed to the new party.
+    pub fn collect_point_to_point(
+        &self,
+        broadcasts: &[RefreshBroadcast<E, H, M>],
+        shares: &[EncryptedShare],
+        paillier_key: Keys,
+        join_messages: &[JoinMessage<E, H, M>],
+        t: Threshold,
+        n: ShareCount,
+    ) -> FsDkrResult<LocalKey<E>> {
+        let refresh_messages =
+            RefreshBroadcast::assemble_all(broadcasts, shares, self.get_party_index()?)?;
+        self.collect(&refresh_messages, paillier_key, join_messages, t, n)
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshBroadcast<E, H, M> {
+    /// Party index of the sender in the new committee.
+    pub fn party_index(&self) -> u16 {
+        self.message.party_index
+    }
+
+    /// Rebuilds the refresh message of the sender as seen by `recipient`: the encrypted shares
+    /// of every other slot are left as zero, so the result can only be collected by
+    /// `recipient`.
+    pub fn assemble(
+        &self,
+        share: &EncryptedShare,
+        recipient: u16,
+    ) -> FsDkrResult<RefreshMessage<E, H, M>> {
+        let n = self.message.points_committed_vec.len() as u16;
+        if recipient == 0 || recipient > n {
+            return Err(FsDkrError::PartyIndexOutOfRange {
+                index: recipient,
+                n,
+            });
+        }
+        if share.sender != self.message.party_index || share.recipient != recipient {
+            return Err(FsDkrError::MalformedRefreshMessage {
+                party_index: self.message.party_index,
+                field: "points_encrypted_vec".to_string(),
+            });
+        }
+
+        let mut message = self.message.clone();
+        message.points_encrypted_vec = vec![BigInt::zero(); n as usize];
+        message.points_encrypted_vec[(recipient - 1) as usize] = share.ciphertext.clone();
+        Ok(message)
+    }
+
+    /// Assembles the message of every broadcast with the share its sender addressed to
+    /// `recipient`, failing with [FsDkrError::MissingContribution] if one is missing.
+    pub(crate) fn assemble_all(
+        broadcasts: &[Self],
+        shares: &[EncryptedShare],
+        recipient: u16,
+    ) -> FsDkrResult<Vec<RefreshMessage<E, H, M>>> {
+        broadcasts
+            .iter()
+            .map(|broadcast| {
+                let share = shares
+                    .iter()
+                    .find(|share| {
+                        share.sender == broadcast.party_index() && share.recipient == recipient
+                    })
+                    .ok_or(FsDkrError::MissingContribution {
+                        party_index: broadcast.party_index(),
+                    })?;
+                broadcast.assemble(share, recipient)
+            })
+            .collect()
+    }
+}
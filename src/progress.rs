@@ -0,0 +1,31 @@
+//! Progress reporting of the verification done by collect.
+//!
+//! Verifying the proofs of a large committee can take seconds.
+//! [RefreshMessage::collect_with_progress](crate::refresh_message::RefreshMessage::collect_with_progress)
+//! calls an observer with a [VerificationEvent] as the material of each party is verified, so that
+//! an interactive tool can render the progress of the collect. The observer can not influence the
+//! verification.
+
+use serde::{Deserialize, Serialize};
+
+/// The proofs verified for every party during collect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProofKind {
+    /// The PDLwSlack and range proofs binding the encrypted shares to the committed points.
+    EncryptedShares,
+    /// The proof of the ring-Pedersen parameters.
+    RingPedersen,
+    /// The proof that the new Paillier key was generated correctly.
+    PaillierKey,
+    /// The composite dlog proofs of the `h1`, `h2`, `N tilde` parameters of a joining party.
+    DLogStatement,
+}
+
+/// Events reported while collect verifies the received messages. Refresh messages and join
+/// messages are identified by the party index of their sender in the new committee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationEvent {
+    StartedParty(u16),
+    ProofVerified(u16, ProofKind),
+    PartyComplete(u16),
+}
@@ -0,0 +1,83 @@
+//! Replay of cached contributions for parties that are offline during a refresh.
+//!
+//! A coordinator keeps the most recent [RefreshMessage] of every party. If a party is offline
+//! during a refresh, its cached contribution can be replayed into the round instead of leaving
+//! its slot out, as long as the contribution still re-shares the share the party currently holds
+//! and encrypts to the Paillier keys the committee currently uses. Any refresh in between changes
+//! both, so a contribution produced before it is stale and rejected. A contribution is also only
+//! replayed into the epoch it was produced for, one from an earlier round of the same epoch can
+//! be reused but one tagged with another epoch is rejected even if it still matches the shares.
+//!
+//! Collect installs the Paillier key carried by the replayed message, so the offline party has to
+//! keep the [DecryptionKey](paillier::DecryptionKey) it got when producing the cached message and
+//! use it when it collects the round.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use std::collections::HashMap;
+
+/// The most recent contribution of every party, indexed by party index.
+pub struct ContributionCache<E: Curve, H: Digest + Clone, const M: usize> {
+    contributions: HashMap<u16, RefreshMessage<E, H, M>>,
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> Default for ContributionCache<E, H, M> {
+    fn default() -> Self {
+        ContributionCache {
+            contributions: HashMap::new(),
+        }
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> ContributionCache<E, H, M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches `refresh_message`, replacing any older contribution of the same party.
+    pub fn insert(&mut self, refresh_message: RefreshMessage<E, H, M>) {
+        self.contributions
+            .insert(refresh_message.party_index, refresh_message);
+    }
+
+    pub fn get(&self, party_index: u16) -> Option<&RefreshMessage<E, H, M>> {
+        self.contributions.get(&party_index)
+    }
+
+    /// Returns the cached contributions of `offline_party_indices`, to be collected together with
+    /// the messages of the online parties in a refresh to `new_n` parties at `epoch`. `local_key`
+    /// is the key of any online party before the round, only its public part is used. Fails with
+    /// [FsDkrError::EpochMismatch] if a contribution was produced for another epoch, and with
+    /// [FsDkrError::StaleContribution] if it no longer matches the committee.
+    pub fn replay(
+        &self,
+        offline_party_indices: &[u16],
+        local_key: &LocalKey<E>,
+        new_n: usize,
+        epoch: u64,
+    ) -> FsDkrResult<Vec<RefreshMessage<E, H, M>>> {
+        crate::validate_committee_size(new_n)?;
+        offline_party_indices
+            .iter()
+            .map(|party_index| {
+                let refresh_message =
+                    self.get(*party_index)
+                        .ok_or(FsDkrError::MissingContribution {
+                            party_index: *party_index,
+                        })?;
+                if refresh_message.epoch != epoch {
+                    return Err(FsDkrError::EpochMismatch {
+                        party_index: *party_index,
+                        expected: epoch,
+                        found: refresh_message.epoch,
+                    });
+                }
+                refresh_message.validate_replay(local_key, new_n)?;
+                Ok(refresh_message.clone())
+            })
+            .collect()
+    }
+}
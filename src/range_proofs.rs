@@ -11,6 +11,8 @@
 
 // TODO: Verify this matches (if possible) range proofs from multi-party-ecdsa
 
+use crate::entropy::Entropy;
+use crate::error::FsDkrResult;
 use curv::arithmetic::traits::*;
 use curv::cryptographic_primitives::hashing::{Digest, DigestExt};
 use curv::elliptic::curves::Point;
@@ -43,7 +45,8 @@ impl AliceZkpRound1 {
         dlog_statement: &DLogStatement,
         a: &BigInt,
         q: &BigInt,
-    ) -> Self {
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<Self> {
         assert!(
             q.bit_length() <= 256,
             "We use SHA256 so we don't currently support moduli bigger than 256"
@@ -51,17 +54,17 @@ impl AliceZkpRound1 {
         let h1 = &dlog_statement.g;
         let h2 = &dlog_statement.ni;
         let N_tilde = &dlog_statement.N;
-        let alpha = BigInt::sample_below(&q.pow(3));
-        let beta = BigInt::from_paillier_key(alice_ek);
-        let gamma = BigInt::sample_below(&(q.pow(3) * N_tilde));
-        let ro = BigInt::sample_below(&(q * N_tilde));
+        let alpha = entropy.sample_below(&q.pow(3))?;
+        let beta = entropy.sample_unit(&alice_ek.n)?;
+        let gamma = entropy.sample_below(&(q.pow(3) * N_tilde))?;
+        let ro = entropy.sample_below(&(q * N_tilde))?;
         let z = (BigInt::mod_pow(h1, &a, N_tilde) * BigInt::mod_pow(h2, &ro, N_tilde)) % N_tilde;
         let u = ((alpha.borrow() * &alice_ek.n + 1)
             * BigInt::mod_pow(&beta, &alice_ek.n, &alice_ek.nn))
             % &alice_ek.nn;
         let w =
             (BigInt::mod_pow(h1, &alpha, N_tilde) * BigInt::mod_pow(h2, &gamma, N_tilde)) % N_tilde;
-        Self {
+        Ok(Self {
             alpha,
             beta,
             gamma,
@@ -69,7 +72,7 @@ impl AliceZkpRound1 {
             z,
             u,
             w,
-        }
+        })
     }
 }
 
@@ -172,12 +175,25 @@ impl<E: Curve, H: Digest + Clone> AliceProof<E, H> {
         dlog_statement: &DLogStatement,
         r: &BigInt,
     ) -> Self {
+        Self::generate_with(a, cipher, alice_ek, dlog_statement, r, &mut Entropy::Os)
+            .expect("the operating system generator does not fail")
+    }
+
+    /// Same as [AliceProof::generate], drawing the nonces from `entropy`.
+    pub(crate) fn generate_with(
+        a: &BigInt,
+        cipher: &BigInt,
+        alice_ek: &EncryptionKey,
+        dlog_statement: &DLogStatement,
+        r: &BigInt,
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<Self> {
         let q = Scalar::<E>::group_order();
         assert!(
             q.bit_length() <= 256,
             "We use SHA256 so we don't currently support moduli bigger than 256"
         );
-        let round1 = AliceZkpRound1::from(alice_ek, dlog_statement, a, &q);
+        let round1 = AliceZkpRound1::from(alice_ek, dlog_statement, a, &q, entropy)?;
 
         let Gen = alice_ek.n.borrow() + 1;
         let e = H::new()
@@ -191,14 +207,14 @@ impl<E: Curve, H: Digest + Clone> AliceProof<E, H> {
 
         let round2 = AliceZkpRound2::from(alice_ek, &round1, &e, a, r);
 
-        Self {
+        Ok(Self {
             z: round1.z.clone(),
             e,
             s: round2.s,
             s1: round2.s1,
             s2: round2.s2,
             _phantom: PhantomData,
-        }
+        })
     }
 }
 
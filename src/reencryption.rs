@@ -0,0 +1,102 @@
+//! Re-encryption of a received share to a Paillier key rotated mid-ceremony.
+//!
+//! The shares of the refresh messages a party received are encrypted to the Paillier key of its
+//! slot at the time they were distributed. If the party rotates its key before it collects, see
+//! [FsDkrLocalKeyExt::update_own_encryption_key], it can no longer decrypt them.
+//! [RefreshMessage::reencrypt_own_share] moves the share of the party's own slot of a message to
+//! the new key without the sender re-sending: the share is decrypted with the old key and
+//! encrypted again to the new one.
+//!
+//! Re-encrypting needs the plaintext, so it can only be done by the recipient, with the
+//! [LocalKey] that still holds the decryption key of the slot, which is checked. The share proofs
+//! of the slot are verified before the share is re-encrypted and are stale afterwards, they are
+//! stated over the old key. The messages are then collected with the rotated key by
+//! [RefreshMessage::collect_reencrypted], which verifies everything [RefreshMessage::collect]
+//! does except the share proofs of the party's own slot, and instead checks the decrypted new
+//! share against the points committed for the slot. A re-encrypted message must not be forwarded
+//! to other parties: its share proofs no longer verify for the slot.
+//!
+//! [FsDkrLocalKeyExt::update_own_encryption_key]: crate::local_key_ext::FsDkrLocalKeyExt::update_own_encryption_key
+
+use crate::add_party_message::{verify_paillier_key_size_of, JoinMessage};
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::paillier_backend::{KzenPaillier, PaillierBackend};
+use crate::refresh_message::{MessageProofs, RefreshMessage, ShareDecryption};
+use crate::strictness::Strictness;
+use curv::arithmetic::{Modulo, Samplable};
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::{Curve, Scalar};
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{DecryptionKey, EncryptionKey};
+use zeroize::Zeroize;
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Re-encrypts the share of the own slot of `local_key` to `new_ek`, see the
+    /// [module level](self) documentation. `local_key` is the key before the rotation, its
+    /// decryption key must belong to the key of the slot, otherwise
+    /// [FsDkrError::KeyPartyMismatch] is returned. The share proofs of the slot are verified and
+    /// the decrypted share is checked against its committed point before it is re-encrypted.
+    pub fn reencrypt_own_share(
+        &mut self,
+        local_key: &LocalKey<E>,
+        new_ek: &EncryptionKey,
+    ) -> FsDkrResult<()> {
+        RefreshMessage::validate_own_decryption_key(local_key)?;
+        verify_paillier_key_size_of(new_ek, local_key.i)?;
+        let slot = (local_key.i - 1) as usize;
+        if slot >= self.points_encrypted_vec.len() {
+            return Err(FsDkrError::MalformedRefreshMessage {
+                party_index: self.party_index,
+                field: "points_encrypted_vec".to_string(),
+            });
+        }
+        self.verify_share_proof(
+            slot,
+            &local_key.paillier_key_vec,
+            &local_key.h1_h2_n_tilde_vec,
+        )?;
+
+        let mut share = KzenPaillier::decrypt(
+            &local_key.paillier_dk,
+            &local_key.paillier_key_vec[slot],
+            &self.points_encrypted_vec[slot],
+            local_key.i,
+        )?;
+        let share_fe = Scalar::<E>::from(&BigInt::modulus(&share, &crate::curve_order::<E>()));
+        if let Err(e) = self.verify_decrypted_share(slot, &share_fe) {
+            share.zeroize();
+            return Err(e);
+        }
+
+        let randomness = BigInt::sample_below(&new_ek.n);
+        self.points_encrypted_vec[slot] = KzenPaillier::encrypt(new_ek, &share, &randomness);
+        share.zeroize();
+        Ok(())
+    }
+
+    /// Same as [RefreshMessage::collect] for messages whose share of the own slot of `local_key`
+    /// was moved to its rotated key with [RefreshMessage::reencrypt_own_share]. `local_key`
+    /// holds the rotated key, the share proofs of its own slot are not verified again. The new
+    /// share is checked against the points committed for the slot instead, which fails with
+    /// [FsDkrError::PublicShareValidationError] if a share of the slot was not re-encrypted by
+    /// [RefreshMessage::reencrypt_own_share].
+    pub fn collect_reencrypted(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::OtherShares,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+}
@@ -1,31 +1,157 @@
-use crate::add_party_message::JoinMessage;
+use crate::add_party_message::{dlog_proof_with_nonce, verify_paillier_key_with, JoinMessage};
+use crate::decryption::DecryptionContext;
+use crate::entropy::Entropy;
 use crate::error::{FsDkrError, FsDkrResult};
+use crate::freshness::Freshness;
+use crate::paillier_backend::{KzenPaillier, PaillierBackend};
+use crate::params::{ShareCount, Threshold};
+use crate::progress::{ProofKind, VerificationEvent};
 use crate::range_proofs::AliceProof;
+use crate::reshuffle::Reshuffle;
+use crate::signed_message::SignedJoinMessage;
+use crate::strictness::Strictness;
 use crate::zk_pdl_with_slack::{PDLwSlackProof, PDLwSlackStatement, PDLwSlackWitness};
-use curv::arithmetic::{BitManipulation, Samplable, Zero};
-use curv::cryptographic_primitives::hashing::Digest;
+use curv::arithmetic::{BasicOps, Modulo, NumberTests, One, Zero};
+use curv::cryptographic_primitives::hashing::{Digest, DigestExt};
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
     ShamirSecretSharing, VerifiableSS,
 };
 use curv::elliptic::curves::{Curve, Point, Scalar};
 use curv::BigInt;
 use curv::HashChoice;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::SharedKeys;
 use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
 pub use paillier::DecryptionKey;
-use paillier::{
-    Add, Decrypt, Encrypt, EncryptWithChosenRandomness, EncryptionKey, KeyGeneration, Mul,
-    Paillier, Randomness, RawCiphertext, RawPlaintext,
-};
+use paillier::{EncryptionKey, RawCiphertext};
+use rand_core::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
-use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use zeroize::Zeroize;
-use zk_paillier::zkproofs::{DLogStatement, NiCorrectKeyProof, SALT_STRING};
+use zk_paillier::zkproofs::{DLogStatement, NiCorrectKeyProof};
 
 use crate::ring_pedersen_proof::{RingPedersenProof, RingPedersenStatement};
 
-// Everything here can be broadcasted
+/// Domain separation tag of the re-sharing coefficients derived from a randomness beacon.
+const BEACON_DOMAIN: &[u8] = b"fs-dkr/beacon-coefficients";
+
+/// The proofs of the individual refresh and join messages verified by a collect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MessageProofs {
+    /// All the proofs of every message.
+    All,
+    /// Same as [MessageProofs::All], verifying the share proofs of the party's own slot only.
+    OwnShare,
+    /// Same as [MessageProofs::All], skipping the share proofs of the party's own slot, which
+    /// were verified before its share was re-encrypted to a rotated key, see
+    /// [RefreshMessage::reencrypt_own_share].
+    OtherShares,
+    /// None, the caller verified them. The checks of the commitments against each other and
+    /// against the public key still run.
+    Verified,
+}
+
+/// How a collect obtains the plaintext of the ciphertext sum it combines into the new share.
+#[derive(Clone, Copy)]
+pub(crate) enum ShareDecryption<'a> {
+    /// Decrypted with the `paillier_dk` of the key.
+    Key,
+    /// Decrypted with a context precomputed for the `paillier_dk` of the key.
+    Context(&'a DecryptionContext),
+    /// Decrypted outside of the collect, e.g. by a quorum of the holders of shares of the
+    /// decryption key, see [crate::threshold_decryption]. The `paillier_dk` of the key is not
+    /// used and the plaintext is always checked against the points committed for the slot.
+    Plaintext(&'a BigInt),
+}
+
+/// Hex encoding of the SEC1 compressed encoding of `point`, as reported by
+/// [FsDkrError::UnexpectedPublicKey].
+fn hex_point<E: Curve>(point: &Point<E>) -> String {
+    point
+        .to_bytes(true)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Lagrange coefficients at zero of the parties at `indices`, 0-based like the indices of
+/// [VerifiableSS::map_share_to_new_params]. Every product is a [Scalar] product, so each
+/// intermediate is reduced modulo the curve order however many parties the quorum holds, and the
+/// coefficients do not depend on the size of the committee: a quorum whose indices exceed the
+/// size of the new committee, as left by a reshuffle removing low indices, maps its shares
+/// without indexing past it.
+pub(crate) fn lagrange_coefficients<E: Curve>(indices: &[u16]) -> Vec<Scalar<E>> {
+    let point = |index: u16| Scalar::<E>::from(&BigInt::from(index as u64 + 1));
+    indices
+        .iter()
+        .map(|index| {
+            let xi = point(*index);
+            let (num, den) = indices.iter().filter(|other| *other != index).fold(
+                (
+                    Scalar::<E>::from(&BigInt::one()),
+                    Scalar::<E>::from(&BigInt::one()),
+                ),
+                |(num, den), other| {
+                    let xj = point(*other);
+                    let diff = &xj - &xi;
+                    (num * &xj, den * diff)
+                },
+            );
+            num * den.invert().expect("quorum indices are distinct")
+        })
+        .collect()
+}
+
+/// Describes the homomorphic sum of lagrange weighted ciphertexts that is decrypted into a new
+/// share. Before reduction modulo the curve order, the decrypted plaintext is bounded by the
+/// number of summands times the largest coefficient times the bound on a single share.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CiphertextSumInfo {
+    /// Number of weighted ciphertexts that are added together.
+    pub summands: usize,
+    /// Largest lagrange coefficient used as a weight.
+    pub max_coefficient: BigInt,
+}
+
+impl CiphertextSumInfo {
+    pub(crate) fn from_coefficients<E: Curve>(li_vec: &[Scalar<E>]) -> Self {
+        CiphertextSumInfo {
+            summands: li_vec.len(),
+            max_coefficient: li_vec
+                .iter()
+                .map(|li| li.to_bigint())
+                .max()
+                .unwrap_or_else(BigInt::zero),
+        }
+    }
+
+    /// Exclusive upper bound on the decrypted sum, given that every summand encrypts a value
+    /// below `share_bound`.
+    pub fn plaintext_bound(&self, share_bound: &BigInt) -> BigInt {
+        BigInt::from(self.summands as u64) * &self.max_coefficient * share_bound
+    }
+}
+
+/// Outcome of [RefreshMessage::validate_collect_report].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Party indices of the senders of the validated messages, in the order they were given.
+    pub validated: Vec<u16>,
+}
+
+/// Records which parties supplied the material a collect combined: the refresh messages of the
+/// quorum, see [RefreshMessage::quorum], and the join messages whose keys were installed.
+/// Surplus refresh messages are verified but do not appear in [ParticipationRecord::quorum].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParticipationRecord {
+    /// Party indices of the senders of the refresh messages in the quorum, sorted.
+    pub quorum: Vec<u16>,
+    /// Party indices assigned to the joining parties, in the order of the join messages.
+    pub joiners: Vec<u16>,
+}
+
+// Everything here can be broadcasted, the encrypted shares can also be sent point-to-point,
+// see crate::point_to_point
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(bound = "E: Curve, H: Digest + Clone")]
 pub struct RefreshMessage<E: Curve, H: Digest + Clone, const M: usize> {
@@ -33,35 +159,261 @@ pub struct RefreshMessage<E: Curve, H: Digest + Clone, const M: usize> {
     pub(crate) party_index: u16,
     pdl_proof_vec: Vec<PDLwSlackProof<E, H>>,
     range_proofs: Vec<AliceProof<E, H>>,
-    coefficients_committed_vec: VerifiableSS<E, sha2::Sha256>,
+    // Feldman commitments to the coefficients of the re-sharing polynomial. They can not be
+    // replaced by hiding (Pedersen) commitments: `points_committed_vec` publishes `g^s_i` for
+    // every share, from which the Feldman commitments follow by interpolation in the exponent,
+    // the PDL proofs are stated over these points, and `LocalKey::vss_scheme` is a Feldman
+    // `VerifiableSS` in GG20.
+    pub(crate) coefficients_committed_vec: VerifiableSS<E, sha2::Sha256>,
     pub(crate) points_committed_vec: Vec<Point<E>>,
-    points_encrypted_vec: Vec<BigInt>,
-    dk_correctness_proof: NiCorrectKeyProof,
+    pub(crate) points_encrypted_vec: Vec<BigInt>,
+    pub(crate) dk_correctness_proof: NiCorrectKeyProof,
     pub(crate) dlog_statement: DLogStatement,
     pub(crate) ek: EncryptionKey,
     pub(crate) remove_party_indices: Vec<u16>,
     pub(crate) public_key: Point<E>,
     pub(crate) ring_pedersen_statement: RingPedersenStatement<E, H>,
     pub(crate) ring_pedersen_proof: RingPedersenProof<E, H, M>,
+    // messages encoded before the epoch was added belong to the first refresh
+    #[serde(default)]
+    pub(crate) epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) freshness: Option<Freshness>,
+    // the child committee of a split the message belongs to, see crate::split
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) split_child: Option<u8>,
     #[serde(skip)]
     pub hash_choice: HashChoice<H>,
 }
 
 impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Same as [RefreshMessage::distribute_with_epoch] for callers that do not track epochs, the
+    /// message is produced at epoch 0.
     pub fn distribute(
         old_party_index: u16,
         local_key: &mut LocalKey<E>,
         new_n: u16,
     ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
-        assert!(local_key.t <= new_n / 2);
+        RefreshMessage::distribute_with_epoch(old_party_index, local_key, new_n, 0)
+    }
+
+    /// Distribute phase of a refresh of `local_key`, which has already gone through `epoch`
+    /// refreshes. The epoch is carried by the message so that collect can reject mixing
+    /// contributions from different refreshes.
+    pub fn distribute_with_epoch(
+        old_party_index: u16,
+        local_key: &mut LocalKey<E>,
+        new_n: u16,
+        epoch: u64,
+    ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
+        let t = local_key.t;
+        RefreshMessage::distribute_with_threshold::<KzenPaillier>(
+            old_party_index,
+            local_key,
+            t,
+            new_n,
+            epoch,
+            None,
+        )
+    }
+
+    /// Same as [RefreshMessage::distribute_with_epoch], drawing the re-sharing polynomial, the
+    /// encryption randomness, the new Paillier key, the ring-Pedersen parameters and the nonces of
+    /// the proofs from `rng`, e.g. the `HardwareRng` of the `hardware-rng` feature, see the
+    /// [entropy](crate::entropy) module. Fails with [FsDkrError::EntropySourceUnavailable] if
+    /// `rng` fails to deliver, leaving `local_key` unchanged.
+    pub fn distribute_with_rng<R: RngCore + CryptoRng>(
+        old_party_index: u16,
+        local_key: &mut LocalKey<E>,
+        new_n: u16,
+        epoch: u64,
+        rng: &mut R,
+    ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
+        let t = local_key.t;
+        RefreshMessage::distribute_with_entropy::<KzenPaillier>(
+            old_party_index,
+            local_key,
+            t,
+            new_n,
+            epoch,
+            None,
+            &mut Entropy::from_rng(rng),
+        )
+    }
+
+    /// Same as [RefreshMessage::distribute_with_epoch], deriving the coefficients of the
+    /// re-sharing polynomial above the constant term from the public randomness `beacon`, e.g. a
+    /// drand round, instead of the party's RNG, see [RefreshMessage::beacon_coefficients]. The
+    /// constant term is the party's share as in every refresh. Since the derivation also takes
+    /// the share, the coefficients are unpredictable to anyone who does not hold it. For the same
+    /// reason they are not publicly checkable: only the holder of the share can confirm with
+    /// [RefreshMessage::verify_beacon_coefficients] that a message used them, a third party can
+    /// not audit it.
+    pub fn distribute_with_beacon(
+        old_party_index: u16,
+        local_key: &mut LocalKey<E>,
+        new_n: u16,
+        epoch: u64,
+        beacon: &[u8],
+    ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
+        let t = local_key.t;
+        let coefficients = RefreshMessage::<E, H, M>::beacon_coefficients(
+            &local_key.keys_linear.x_i,
+            local_key.i,
+            epoch,
+            t,
+            beacon,
+        );
+        RefreshMessage::distribute_with_threshold::<KzenPaillier>(
+            old_party_index,
+            local_key,
+            t,
+            new_n,
+            epoch,
+            Some(coefficients),
+        )
+    }
+
+    /// Coefficients of the polynomial of degree `t` over which the party at `party_index`
+    /// re-shares `secret` at `epoch` with [RefreshMessage::distribute_with_beacon], in ascending
+    /// order of degree. The coefficient of degree `k > 0` is the SHA-256 digest of a domain tag,
+    /// `beacon`, `secret`, `party_index`, `epoch` and `k`, reduced to a scalar.
+    pub fn beacon_coefficients(
+        secret: &Scalar<E>,
+        party_index: u16,
+        epoch: u64,
+        t: u16,
+        beacon: &[u8],
+    ) -> Vec<Scalar<E>> {
+        std::iter::once(secret.clone())
+            .chain((1..=t).map(|k| {
+                sha2::Sha256::new()
+                    .chain(BEACON_DOMAIN)
+                    .chain(beacon)
+                    .chain_scalar(secret)
+                    .chain(party_index.to_be_bytes())
+                    .chain(epoch.to_be_bytes())
+                    .chain(k.to_be_bytes())
+                    .result_scalar()
+            }))
+            .collect()
+    }
+
+    /// Checks that the message re-shares `secret` over the polynomial with the
+    /// [RefreshMessage::beacon_coefficients] of `beacon`, by comparing its coefficient
+    /// commitments. Fails with [FsDkrError::CoefficientCommitmentMismatch] otherwise. The check
+    /// needs the secret share, so only its holder can run it.
+    pub fn verify_beacon_coefficients(&self, secret: &Scalar<E>, beacon: &[u8]) -> FsDkrResult<()> {
+        let commitments = &self.coefficients_committed_vec.commitments;
+        let degree = commitments.len().saturating_sub(1) as u16;
+        let expected: Vec<Point<E>> = RefreshMessage::<E, H, M>::beacon_coefficients(
+            secret,
+            self.party_index,
+            self.epoch,
+            degree,
+            beacon,
+        )
+        .iter()
+        .map(|coefficient| Point::<E>::generator() * coefficient)
+        .collect();
+        if *commitments != expected {
+            return Err(FsDkrError::CoefficientCommitmentMismatch {
+                party_index: self.party_index,
+            });
+        }
+        Ok(())
+    }
+
+    /// Shares `coefficients[0]` over the polynomial with the given coefficients, like
+    /// [VerifiableSS::share] does over a random one. `nonce` is the nonce of the proof of
+    /// knowledge of the secret.
+    fn share_over_polynomial(
+        n: u16,
+        coefficients: &[Scalar<E>],
+        nonce: &Scalar<E>,
+    ) -> FsDkrResult<(VerifiableSS<E, sha2::Sha256>, Vec<Scalar<E>>)> {
+        let commitments = coefficients
+            .iter()
+            .map(|coefficient| Point::<E>::generator() * coefficient)
+            .collect();
+        let shares = (1..=n)
+            .map(|x| {
+                let x = Scalar::<E>::from(&BigInt::from(x));
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(Scalar::<E>::zero(), |acc, coefficient| {
+                        acc * &x + coefficient
+                    })
+            })
+            .collect();
+        let vss_scheme = VerifiableSS {
+            parameters: ShamirSecretSharing {
+                threshold: (coefficients.len() - 1) as u16,
+                share_count: n,
+            },
+            commitments,
+            proof: dlog_proof_with_nonce(&coefficients[0], nonce)?,
+        };
+        Ok((vss_scheme, shares))
+    }
+
+    /// Distribute phase re-sharing the share of `local_key` over a polynomial of degree `new_t`,
+    /// the threshold of the new committee. The polynomial is random, unless its `coefficients`
+    /// are derived from a randomness beacon.
+    fn distribute_with_threshold<B: PaillierBackend>(
+        old_party_index: u16,
+        local_key: &mut LocalKey<E>,
+        new_t: u16,
+        new_n: u16,
+        epoch: u64,
+        coefficients: Option<Vec<Scalar<E>>>,
+    ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
+        RefreshMessage::distribute_with_entropy::<B>(
+            old_party_index,
+            local_key,
+            new_t,
+            new_n,
+            epoch,
+            coefficients,
+            &mut Entropy::Os,
+        )
+    }
+
+    /// Same as [RefreshMessage::distribute_with_threshold], drawing every random value from
+    /// `entropy`. The key is only updated once the message is complete, so that a distribute
+    /// failing on the entropy source leaves it unchanged.
+    fn distribute_with_entropy<B: PaillierBackend>(
+        old_party_index: u16,
+        local_key: &mut LocalKey<E>,
+        new_t: u16,
+        new_n: u16,
+        epoch: u64,
+        coefficients: Option<Vec<Scalar<E>>>,
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
+        crate::validate_committee_size(new_n as usize)?;
+        // the only t = n - 1 committee within the honest majority is 1 out of 2, in which the
+        // quorum is the whole committee
+        if new_t > new_n / 2 {
+            return Err(FsDkrError::HonestMajorityViolation { t: new_t, n: new_n });
+        }
         let secret = local_key.keys_linear.x_i.clone();
         // secret share old key
-        if new_n <= local_key.t {
+        if new_n <= new_t {
             return Err(FsDkrError::NewPartyUnassignedIndexError);
         }
-        let (vss_scheme, secret_shares) = VerifiableSS::<E, sha2::Sha256>::share(local_key.t, new_n, &secret);
-
-        local_key.vss_scheme = vss_scheme.clone();
+        let coefficients = match coefficients {
+            Some(coefficients) => coefficients,
+            None => std::iter::once(Ok(secret))
+                .chain((0..new_t).map(|_| entropy.scalar::<E>()))
+                .collect::<FsDkrResult<Vec<_>>>()?,
+        };
+        let (vss_scheme, secret_shares) = RefreshMessage::<E, H, M>::share_over_polynomial(
+            new_n,
+            &coefficients,
+            &entropy.scalar()?,
+        )?;
 
         // commit to points on the polynomial
         let points_committed_vec: Vec<_> = (0..secret_shares.len())
@@ -71,16 +423,16 @@ impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
         // encrypt points on the polynomial using Paillier keys
         let (points_encrypted_vec, randomness_vec): (Vec<_>, Vec<_>) = (0..secret_shares.len())
             .map(|i| {
-                let randomness = BigInt::sample_below(&local_key.paillier_key_vec[i].n);
-                let ciphertext = Paillier::encrypt_with_chosen_randomness(
+                let randomness = entropy.sample_below(&local_key.paillier_key_vec[i].n)?;
+                let ciphertext = B::encrypt(
                     &local_key.paillier_key_vec[i],
-                    RawPlaintext::from(secret_shares[i].to_bigint()),
-                    &Randomness::from(randomness.clone()),
-                )
-                .0
-                .into_owned();
-                (ciphertext, randomness)
+                    &secret_shares[i].to_bigint(),
+                    &randomness,
+                );
+                Ok((ciphertext, randomness))
             })
+            .collect::<FsDkrResult<Vec<_>>>()?
+            .into_iter()
             .unzip();
 
         // generate PDL proofs for each {point_committed, point_encrypted} pair
@@ -99,29 +451,37 @@ impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
                     h2: local_key.h1_h2_n_tilde_vec[i].ni.clone(),
                     N_tilde: local_key.h1_h2_n_tilde_vec[i].N.clone(),
                 };
-                PDLwSlackProof::prove(&witness, &statement)
+                PDLwSlackProof::prove_with(&witness, &statement, entropy)
             })
-            .collect();
+            .collect::<FsDkrResult<_>>()?;
 
         let range_proofs = (0..secret_shares.len())
             .map(|i| {
-                AliceProof::generate(
+                AliceProof::generate_with(
                     &secret_shares[i].to_bigint(),
                     &points_encrypted_vec[i],
                     &local_key.paillier_key_vec[i],
                     &local_key.h1_h2_n_tilde_vec[i],
                     &randomness_vec[i],
+                    entropy,
                 )
             })
-            .collect();
+            .collect::<FsDkrResult<_>>()?;
+
+        let (ek, dk) = entropy.paillier_keypair::<B>(crate::PAILLIER_KEY_SIZE)?;
+        crate::validate_generated_modulus(&ek.n, crate::PAILLIER_KEY_SIZE)?;
+        let dk_correctness_proof = B::prove_correct_key(&dk);
 
-        let (ek, dk) = Paillier::keypair_with_modulus_size(crate::PAILLIER_KEY_SIZE).keys();
-        let dk_correctness_proof = NiCorrectKeyProof::proof(&dk, None);
+        let (ring_pedersen_statement, ring_pedersen_witness) =
+            RingPedersenStatement::generate_with(entropy)?;
 
-        let (ring_pedersen_statement, ring_pedersen_witness) = RingPedersenStatement::generate();
+        let ring_pedersen_proof = RingPedersenProof::prove_with(
+            &ring_pedersen_witness,
+            &ring_pedersen_statement,
+            entropy,
+        )?;
 
-        let ring_pedersen_proof =
-            RingPedersenProof::prove(&ring_pedersen_witness, &ring_pedersen_statement);
+        local_key.vss_scheme = vss_scheme.clone();
         Ok((
             RefreshMessage {
                 old_party_index,
@@ -138,309 +498,1589 @@ impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
                 public_key: local_key.y_sum_s.clone(),
                 ring_pedersen_statement,
                 ring_pedersen_proof,
+                epoch,
+                freshness: None,
+                split_child: None,
                 hash_choice: HashChoice::new(),
             },
             dk,
         ))
     }
 
-    pub fn validate_collect(refresh_messages: &[Self], t: u16, n: u16) -> FsDkrResult<()> {
-        // check we got at least threshold t refresh messages
-        if refresh_messages.len() <= t.into() {
-            return Err(FsDkrError::PartiesThresholdViolation {
-                threshold: t,
-                refreshed_keys: refresh_messages.len(),
-            });
-        }
-
-        // check all vectors are of same length
-        let reference_len = refresh_messages[0].pdl_proof_vec.len();
+    pub fn validate_collect(
+        refresh_messages: &[Self],
+        t: Threshold,
+        n: ShareCount,
+    ) -> FsDkrResult<()> {
+        RefreshMessage::validate_collect_with_strictness(
+            refresh_messages,
+            t,
+            n,
+            Strictness::default(),
+        )
+    }
 
-        for (k, refresh_message) in refresh_messages.iter().enumerate() {
-            let pdl_proof_len = refresh_message.pdl_proof_vec.len();
-            let points_commited_len = refresh_message.points_committed_vec.len();
-            let points_encrypted_len = refresh_message.points_encrypted_vec.len();
+    /// Same as [RefreshMessage::validate_collect], with the amount of verification selected by
+    /// `strictness`.
+    pub fn validate_collect_with_strictness(
+        refresh_messages: &[Self],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+        strictness: Strictness,
+    ) -> FsDkrResult<()> {
+        crate::validate_committee_size(n as usize)?;
+        RefreshMessage::validate_structure(refresh_messages, t)?;
+        for refresh_message in refresh_messages.iter() {
+            refresh_message.validate_slot_count(n)?;
+        }
 
-            if !(pdl_proof_len == reference_len
-                && points_commited_len == reference_len
-                && points_encrypted_len == reference_len)
-            {
-                return Err(FsDkrError::SizeMismatchError {
-                    refresh_message_index: k,
-                    pdl_proof_len,
-                    points_commited_len,
-                    points_encrypted_len,
-                });
+        if strictness.verifies_proofs() {
+            for refresh_message in refresh_messages.iter() {
+                refresh_message.validate_public_shares(n)?;
             }
         }
 
-        for refresh_message in refresh_messages.iter() {
-            for i in 0..n as usize {
-                //TODO: we should handle the case of t<i<n
-                if refresh_message
-                    .coefficients_committed_vec
-                    .validate_share_public(&refresh_message.points_committed_vec[i], i as u16 + 1)
-                    .is_err()
-                {
-                    return Err(FsDkrError::PublicShareValidationError);
-                }
+        if strictness.is_paranoid() {
+            for refresh_message in refresh_messages.iter() {
+                refresh_message.validate_polynomial_degree(t)?;
             }
         }
 
         Ok(())
     }
 
-    pub(crate) fn get_ciphertext_sum<'a>(
-        refresh_messages: &'a [Self],
-        party_index: u16,
-        parameters: &'a ShamirSecretSharing,
-        ek: &'a EncryptionKey,
-    ) -> (RawCiphertext<'a>, Vec<Scalar<E>>) {
-        // TODO: check we have large enough qualified set , at least t+1
-        //decrypt the new share
-        // we first homomorphically add all ciphertext encrypted using our encryption key
-        let ciphertext_vec: Vec<_> = (0..refresh_messages.len())
-            .map(|k| refresh_messages[k].points_encrypted_vec[(party_index - 1) as usize].clone())
-            .collect();
-
-        let indices: Vec<u16> = (0..(parameters.threshold + 1) as usize)
-            .map(|i| refresh_messages[i].old_party_index - 1)
-            .collect();
-
-        // optimization - one decryption
-        let li_vec: Vec<_> = (0..parameters.threshold as usize + 1)
-            .map(|i| {
-                VerifiableSS::<E, sha2::Sha256>::map_share_to_new_params(
-                    parameters.clone().borrow(),
-                    indices[i],
-                    &indices,
-                )
-            })
-            .collect();
+    /// Runs the checks of [RefreshMessage::validate_collect] on every message instead of stopping
+    /// at the first failure. If any message fails, [FsDkrError::ValidationFailed] lists the
+    /// position of every failed message in `refresh_messages` with the first check it failed.
+    /// Failures of the transcript as a whole, too few messages or a committee that is too large,
+    /// are returned directly.
+    pub fn validate_collect_report(
+        refresh_messages: &[Self],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<ValidationReport> {
+        crate::validate_committee_size(n as usize)?;
+        if refresh_messages.len() <= t.into() {
+            return Err(FsDkrError::PartiesThresholdViolation {
+                threshold: t,
+                refreshed_keys: refresh_messages.len(),
+            });
+        }
 
-        let ciphertext_vec_at_indices_mapped: Vec<_> = (0..(parameters.threshold + 1) as usize)
-            .map(|i| {
-                Paillier::mul(
-                    ek,
-                    RawCiphertext::from(ciphertext_vec[i].clone()),
-                    RawPlaintext::from(li_vec[i].to_bigint()),
-                )
+        let failures: Vec<(usize, FsDkrError)> = refresh_messages
+            .iter()
+            .enumerate()
+            .filter_map(|(k, refresh_message)| {
+                refresh_message
+                    .validate_collect_message(k, &refresh_messages[0], t, n, Strictness::default())
+                    .err()
+                    .map(|error| (k, error))
             })
             .collect();
 
-        let ciphertext_sum = ciphertext_vec_at_indices_mapped.iter().fold(
-            Paillier::encrypt(ek, RawPlaintext::from(BigInt::zero())),
-            |acc, x| Paillier::add(ek, acc, x.clone()),
-        );
-
-        (ciphertext_sum, li_vec)
-    }
-
-    pub fn replace(
-        new_parties: &[JoinMessage<E, H, M>],
-        key: &mut LocalKey<E>,
-        old_to_new_map: &HashMap<u16, u16>,
-        new_n: u16,
-    ) -> FsDkrResult<(Self, DecryptionKey)> {
-        let current_len = key.paillier_key_vec.len() as u16;
-        let mut paillier_key_h1_h2_n_tilde_hash_map: HashMap<u16, (EncryptionKey, DLogStatement)> =
-            HashMap::new();
-        for old_party_index in old_to_new_map.keys() {
-            let paillier_key = key
-                .paillier_key_vec
-                .get((old_party_index - 1) as usize)
-                .unwrap()
-                .clone();
-            let h1_h2_n_tilde = key
-                .h1_h2_n_tilde_vec
-                .get((old_party_index - 1) as usize)
-                .unwrap()
-                .clone();
-            paillier_key_h1_h2_n_tilde_hash_map.insert(
-                old_to_new_map.get(old_party_index).unwrap().clone(),
-                (paillier_key, h1_h2_n_tilde),
-            );
+        if !failures.is_empty() {
+            return Err(FsDkrError::ValidationFailed { failures });
         }
+        Ok(ValidationReport {
+            validated: refresh_messages
+                .iter()
+                .map(|refresh_message| refresh_message.party_index)
+                .collect(),
+        })
+    }
 
-        for new_party_index in paillier_key_h1_h2_n_tilde_hash_map.keys() {
-            if new_party_index.clone() <= current_len {
-                key.paillier_key_vec[(new_party_index - 1) as usize] =
-                    paillier_key_h1_h2_n_tilde_hash_map
-                        .get(new_party_index)
-                        .unwrap()
-                        .clone()
-                        .0;
-                key.h1_h2_n_tilde_vec[(new_party_index - 1) as usize] =
-                    paillier_key_h1_h2_n_tilde_hash_map
-                        .get(new_party_index)
-                        .unwrap()
-                        .clone()
-                        .1;
-            } else {
-                key.paillier_key_vec.insert(
-                    (new_party_index - 1) as usize,
-                    paillier_key_h1_h2_n_tilde_hash_map
-                        .get(new_party_index)
-                        .unwrap()
-                        .clone()
-                        .0,
-                );
-                key.h1_h2_n_tilde_vec.insert(
-                    (new_party_index - 1) as usize,
-                    paillier_key_h1_h2_n_tilde_hash_map
-                        .get(new_party_index)
-                        .unwrap()
-                        .clone()
-                        .1,
-                );
-            }
+    /// The checks of [RefreshMessage::validate_collect_with_strictness] for the message at
+    /// position `refresh_message_index`, compared to the `reference` message of the transcript.
+    fn validate_collect_message(
+        &self,
+        refresh_message_index: usize,
+        reference: &Self,
+        t: u16,
+        n: u16,
+        strictness: Strictness,
+    ) -> FsDkrResult<()> {
+        self.validate_lengths(refresh_message_index, reference.pdl_proof_vec.len())?;
+        RefreshMessage::validate_epoch(std::slice::from_ref(self), reference.epoch)?;
+        self.validate_slot_count(n)?;
+        if strictness.verifies_proofs() {
+            self.validate_public_shares(n)?;
         }
-
-        for join_message in new_parties.iter() {
-            let party_index = join_message.get_party_index()?;
-            if party_index <= current_len {
-                key.paillier_key_vec[(party_index - 1) as usize] = join_message.ek.clone();
-                key.h1_h2_n_tilde_vec[(party_index - 1) as usize] =
-                    join_message.dlog_statement.clone();
-            } else {
-                key.paillier_key_vec
-                    .insert((party_index - 1) as usize, join_message.ek.clone());
-                key.h1_h2_n_tilde_vec.insert(
-                    (party_index - 1) as usize,
-                    join_message.dlog_statement.clone(),
-                );
-            }
+        if strictness.is_paranoid() {
+            self.validate_polynomial_degree(t)?;
         }
-        let old_party_index = key.i;
-        key.i = *old_to_new_map.get(&key.i).unwrap();
-        key.n = new_n;
-
-        RefreshMessage::distribute(old_party_index, key, new_n as u16)
+        Ok(())
     }
 
-    pub fn collect(
+    /// Runs every check that does not need a decryption key on the messages a party is about to
+    /// collect, failing on the first violation: the [Strictness::Paranoid] structural checks,
+    /// unique party indices, agreement on the public key, non identity commitments and all the
+    /// proofs. `local_key` is the key of an existing party, it is used to verify the encrypted
+    /// shares and the re-shared commitments. A joining party passes `None`, since it does not
+    /// know the keys the shares were encrypted to.
+    pub(crate) fn verify_transcript(
         refresh_messages: &[Self],
-        mut local_key: &mut LocalKey<E>,
-        new_dk: DecryptionKey,
         join_messages: &[JoinMessage<E, H, M>],
+        local_key: Option<&LocalKey<E>>,
+        t: u16,
+        n: u16,
     ) -> FsDkrResult<()> {
-        let new_n = refresh_messages.len() + join_messages.len();
-        RefreshMessage::validate_collect(refresh_messages, local_key.t, new_n as u16)?;
+        RefreshMessage::validate_collect_with_strictness(
+            refresh_messages,
+            Threshold(t),
+            ShareCount(n),
+            Strictness::Paranoid,
+        )?;
+
+        RefreshMessage::validate_unique_senders(refresh_messages, join_messages, n)?;
 
+        let public_key = match local_key {
+            Some(local_key) => &local_key.y_sum_s,
+            None => &refresh_messages[0].public_key,
+        };
         for refresh_message in refresh_messages.iter() {
-            for i in 0..(new_n as usize) {
-                let statement = PDLwSlackStatement {
-                    ciphertext: refresh_message.points_encrypted_vec[i].clone(),
-                    ek: local_key.paillier_key_vec[i].clone(),
-                    Q: refresh_message.points_committed_vec[i].clone(),
-                    G: Point::<E>::generator().to_point(),
-                    h1: local_key.h1_h2_n_tilde_vec[i].g.clone(),
-                    h2: local_key.h1_h2_n_tilde_vec[i].ni.clone(),
-                    N_tilde: local_key.h1_h2_n_tilde_vec[i].N.clone(),
-                };
-                refresh_message.pdl_proof_vec[i].verify(&statement)?;
-                if !refresh_message.range_proofs[i].verify(
-                    &statement.ciphertext,
-                    &statement.ek,
-                    &local_key.h1_h2_n_tilde_vec[i],
-                ) {
-                    return Err(FsDkrError::RangeProof { party_index: i });
-                }
+            if refresh_message.public_key != *public_key {
+                return Err(FsDkrError::BroadcastedPublicKeyError);
             }
+            refresh_message.validate_curve_points()?;
         }
 
-        // Verify ring-pedersen parameters
         for refresh_message in refresh_messages.iter() {
-            RingPedersenProof::verify(
-                &refresh_message.ring_pedersen_proof,
-                &refresh_message.ring_pedersen_statement,
-            )?;
+            if let Some(local_key) = local_key {
+                refresh_message.verify_share_proofs(
+                    &local_key.paillier_key_vec,
+                    &local_key.h1_h2_n_tilde_vec,
+                    n as usize,
+                )?;
+                refresh_message.validate_reshared_share(&local_key.pk_vec)?;
+            }
+            refresh_message.verify_ring_pedersen()?;
+            refresh_message.verify_paillier_key()?;
         }
 
         for join_message in join_messages.iter() {
-            RingPedersenProof::verify(
-                &join_message.ring_pedersen_proof,
-                &join_message.ring_pedersen_statement,
-            )?;
+            let party_index = join_message.get_party_index()?;
+            join_message
+                .verify_ring_pedersen()
+                .map_err(|_| FsDkrError::RingPedersenProofValidation { party_index })?;
+            join_message.verify_paillier_key_correctness()?;
+            join_message.verify_dlog_proofs()?;
+            join_message.verify_paillier_key_size()?;
         }
 
-        let old_ek = local_key.paillier_key_vec[(local_key.i - 1) as usize].clone();
-        let (cipher_text_sum, li_vec) = RefreshMessage::get_ciphertext_sum(
-            refresh_messages,
-            local_key.i,
-            &local_key.vss_scheme.parameters,
-            &old_ek,
-        );
+        Ok(())
+    }
 
+    /// Checks that no two of the refresh and join messages of a round were sent by the same
+    /// party of the committee of `n`, e.g. a message delivered twice by the transport.
+    pub(crate) fn validate_unique_senders(
+        refresh_messages: &[Self],
+        join_messages: &[JoinMessage<E, H, M>],
+        n: u16,
+    ) -> FsDkrResult<()> {
+        let mut seen_indices = HashSet::new();
         for refresh_message in refresh_messages.iter() {
-            if refresh_message
-                .dk_correctness_proof
-                .verify(&refresh_message.ek, SALT_STRING)
-                .is_err()
-            {
-                return Err(FsDkrError::PaillierVerificationError {
+            if !seen_indices.insert(refresh_message.party_index) {
+                return Err(FsDkrError::DuplicatePartyIndex {
                     party_index: refresh_message.party_index,
                 });
             }
-            let n_length = refresh_message.ek.n.bit_length();
-            if n_length > crate::PAILLIER_KEY_SIZE || n_length < crate::PAILLIER_KEY_SIZE - 1 {
-                return Err(FsDkrError::ModuliTooSmall {
-                    party_index: refresh_message.party_index,
-                    moduli_size: n_length,
-                });
+        }
+        for join_message in join_messages.iter() {
+            let party_index = join_message.get_party_index_in_committee(n)?;
+            if !seen_indices.insert(party_index) {
+                return Err(FsDkrError::DuplicatePartyIndex { party_index });
             }
+        }
+        Ok(())
+    }
 
-            // if the proof checks, we add the new paillier public key to the key
-            local_key.paillier_key_vec[(refresh_message.party_index - 1) as usize] =
-                refresh_message.ek.clone();
+    /// Checks that neither the broadcasted public key nor any commitment is the identity point.
+    pub(crate) fn validate_curve_points(&self) -> FsDkrResult<()> {
+        if self.public_key.is_zero()
+            || self
+                .coefficients_committed_vec
+                .commitments
+                .iter()
+                .chain(self.points_committed_vec.iter())
+                .any(|point| point.is_zero())
+        {
+            return Err(FsDkrError::InvalidCurvePoint {
+                party_index: self.party_index,
+            });
         }
+        Ok(())
+    }
 
-        for join_message in join_messages {
-            let party_index = join_message.get_party_index()?;
+    /// Checks that the public share the collected key holds for itself in `pk_vec` commits to
+    /// its new secret share.
+    pub(crate) fn validate_own_public_share(local_key: &LocalKey<E>) -> FsDkrResult<()> {
+        match local_key.pk_vec.get((local_key.i - 1) as usize) {
+            Some(public_share) if *public_share == local_key.keys_linear.y => Ok(()),
+            _ => Err(FsDkrError::PublicShareValidationError),
+        }
+    }
 
-            if join_message
-                .dk_correctness_proof
-                .verify(&join_message.ek, SALT_STRING)
-                .is_err()
-            {
-                return Err(FsDkrError::PaillierVerificationError { party_index });
-            }
+    /// Checks that there are more than `t` refresh messages and that all of them carry the same
+    /// number of proofs, committed points and encrypted points.
+    pub(crate) fn validate_structure(refresh_messages: &[Self], t: u16) -> FsDkrResult<()> {
+        // check we got at least threshold t refresh messages
+        if refresh_messages.len() <= t.into() {
+            return Err(FsDkrError::PartiesThresholdViolation {
+                threshold: t,
+                refreshed_keys: refresh_messages.len(),
+            });
+        }
 
-            // creating an inverse dlog statement
-            let dlog_statement_base_h2 = DLogStatement {
-                N: join_message.dlog_statement.N.clone(),
-                g: join_message.dlog_statement.ni.clone(),
-                ni: join_message.dlog_statement.g.clone(),
-            };
-            if join_message
-                .composite_dlog_proof_base_h1
-                .verify(&join_message.dlog_statement)
+        // check all messages were produced in the same epoch
+        RefreshMessage::validate_epoch(refresh_messages, refresh_messages[0].epoch)?;
+
+        // check all vectors are of same length
+        let reference_len = refresh_messages[0].pdl_proof_vec.len();
+
+        for (k, refresh_message) in refresh_messages.iter().enumerate() {
+            refresh_message.validate_lengths(k, reference_len)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the message carries exactly one committed point, encrypted point and pair of
+    /// proofs for each of the `n` slots of the committee.
+    pub(crate) fn validate_slot_count(&self, n: u16) -> FsDkrResult<()> {
+        let fields = [
+            ("points_committed_vec", self.points_committed_vec.len()),
+            ("points_encrypted_vec", self.points_encrypted_vec.len()),
+            ("pdl_proof_vec", self.pdl_proof_vec.len()),
+            ("range_proofs", self.range_proofs.len()),
+        ];
+        for (field, len) in fields {
+            if len != n as usize {
+                return Err(FsDkrError::MalformedRefreshMessage {
+                    party_index: self.party_index,
+                    field: field.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the proofs, committed points and encrypted points of the message all have
+    /// `expected_len` entries, one per receiving party. `refresh_message_index` identifies the
+    /// message in the reported error.
+    pub(crate) fn validate_lengths(
+        &self,
+        refresh_message_index: usize,
+        expected_len: usize,
+    ) -> FsDkrResult<()> {
+        let pdl_proof_len = self.pdl_proof_vec.len();
+        let points_commited_len = self.points_committed_vec.len();
+        let points_encrypted_len = self.points_encrypted_vec.len();
+
+        if !(pdl_proof_len == expected_len
+            && self.range_proofs.len() == expected_len
+            && points_commited_len == expected_len
+            && points_encrypted_len == expected_len)
+        {
+            return Err(FsDkrError::SizeMismatchError {
+                refresh_message_index,
+                pdl_proof_len,
+                points_commited_len,
+                points_encrypted_len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that every refresh message was produced at `expected_epoch`.
+    pub(crate) fn validate_epoch(
+        refresh_messages: &[Self],
+        expected_epoch: u64,
+    ) -> FsDkrResult<()> {
+        for refresh_message in refresh_messages.iter() {
+            if refresh_message.epoch != expected_epoch {
+                return Err(FsDkrError::EpochMismatch {
+                    party_index: refresh_message.party_index,
+                    expected: expected_epoch,
+                    found: refresh_message.epoch,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of refreshes the sender's key had gone through when this message was produced.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Checks that every committed point lies on the polynomial committed to by
+    /// `coefficients_committed_vec`.
+    pub(crate) fn validate_public_shares(&self, n: u16) -> FsDkrResult<()> {
+        for i in 0..n as usize {
+            //TODO: we should handle the case of t<i<n
+            if self
+                .coefficients_committed_vec
+                .validate_share_public(&self.points_committed_vec[i], i as u16 + 1)
                 .is_err()
-                || join_message
-                    .composite_dlog_proof_base_h2
-                    .verify(&dlog_statement_base_h2)
-                    .is_err()
             {
-                return Err(FsDkrError::DLogProofValidation { party_index });
+                return Err(FsDkrError::PublicShareValidationError);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `share`, decrypted from the encrypted share of slot `my_index` (0 based, the
+    /// party index minus one), is the one the sender committed to in `points_committed_vec`.
+    /// Fails with [FsDkrError::DecryptedShareMismatch] if the sender encrypted a different value.
+    pub fn verify_decrypted_share(&self, my_index: usize, share: &Scalar<E>) -> FsDkrResult<()> {
+        let committed = self.points_committed_vec.get(my_index).ok_or_else(|| {
+            FsDkrError::MalformedRefreshMessage {
+                party_index: self.party_index,
+                field: "points_committed_vec".to_string(),
             }
+        })?;
+        if Point::<E>::generator() * share != *committed {
+            return Err(FsDkrError::DecryptedShareMismatch {
+                party_index: self.party_index,
+                slot: my_index,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the committed polynomial has exactly `t + 1` coefficients.
+    pub(crate) fn validate_polynomial_degree(&self, t: u16) -> FsDkrResult<()> {
+        if self.coefficients_committed_vec.commitments.len() != t as usize + 1 {
+            return Err(FsDkrError::PublicShareValidationError);
+        }
+        Ok(())
+    }
+
+    /// Checks that every refresh message carries the group public key `expected_pk`.
+    pub(crate) fn validate_expected_public_key(
+        refresh_messages: &[Self],
+        expected_pk: &Point<E>,
+    ) -> FsDkrResult<()> {
+        match refresh_messages
+            .iter()
+            .find(|refresh_message| refresh_message.public_key != *expected_pk)
+        {
+            Some(refresh_message) => Err(FsDkrError::UnexpectedPublicKey {
+                expected: hex_point(expected_pk),
+                got: hex_point(&refresh_message.public_key),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the decryption key of `local_key` belongs to the encryption key of its own
+    /// slot, the key the shares of the slot are encrypted to. The decryption key of another party
+    /// would silently decrypt to a wrong share.
+    pub(crate) fn validate_own_decryption_key(local_key: &LocalKey<E>) -> FsDkrResult<()> {
+        let expected_ek = local_key
+            .paillier_key_vec
+            .get((local_key.i - 1) as usize)
+            .ok_or(FsDkrError::PartyIndexOutOfRange {
+                index: local_key.i,
+                n: local_key.paillier_key_vec.len() as u16,
+            })?;
+        if &local_key.paillier_dk.p * &local_key.paillier_dk.q != expected_ek.n {
+            return Err(FsDkrError::KeyPartyMismatch {
+                party_index: local_key.i,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the sender re-shared the share it is known to hold, i.e. that the constant
+    /// term of its committed polynomial is its public share in `pk_vec`.
+    pub(crate) fn validate_reshared_share(&self, pk_vec: &[Point<E>]) -> FsDkrResult<()> {
+        let expected = pk_vec.get((self.old_party_index - 1) as usize);
+        match (
+            expected,
+            self.coefficients_committed_vec.commitments.first(),
+        ) {
+            (Some(expected), Some(committed)) if expected == committed => Ok(()),
+            _ => Err(FsDkrError::ReshareCommitmentMismatch {
+                party_index: self.party_index,
+            }),
+        }
+    }
+
+    /// Checks that a previously produced contribution can still be used in a refresh to `new_n`
+    /// parties of the committee described by `local_key`: the sender's share and the keys the
+    /// shares were encrypted to must not have changed since the message was produced.
+    pub(crate) fn validate_replay(&self, local_key: &LocalKey<E>, new_n: usize) -> FsDkrResult<()> {
+        let stale = FsDkrError::StaleContribution {
+            party_index: self.party_index,
+        };
+        if self.pdl_proof_vec.len() != new_n
+            || self.range_proofs.len() != new_n
+            || self.points_committed_vec.len() != new_n
+            || self.points_encrypted_vec.len() != new_n
+            || local_key.paillier_key_vec.len() < new_n
+            || local_key.h1_h2_n_tilde_vec.len() < new_n
+        {
+            return Err(stale);
+        }
+        self.validate_reshared_share(&local_key.pk_vec)
+            .map_err(|_| stale.clone())?;
+        self.verify_share_proofs(
+            &local_key.paillier_key_vec,
+            &local_key.h1_h2_n_tilde_vec,
+            new_n,
+        )
+        .map_err(|_| stale)
+    }
+
+    /// Selects the quorum whose contributions are combined into the new shares: the first `t + 1`
+    /// refresh messages after sorting by `party_index`. Every party derives the same quorum from
+    /// the same set of messages regardless of the order they were received in, and both the
+    /// ciphertext sum and the aggregation of the committed points use only this quorum, surplus
+    /// messages are verified but not combined. The caller must have checked that there are at
+    /// least `t + 1` messages, see [RefreshMessage::validate_collect].
+    ///
+    /// The lagrange coefficients are undefined over a repeated evaluation point, so the quorum
+    /// must not contain two messages with the same index, e.g. after merging the message sets of
+    /// several relays.
+    pub(crate) fn quorum(refresh_messages: &[Self], t: u16) -> FsDkrResult<Vec<&Self>> {
+        RefreshMessage::quorum_of(refresh_messages.iter().collect(), t)
+    }
 
-            let n_length = join_message.ek.n.bit_length();
-            if n_length > crate::PAILLIER_KEY_SIZE || n_length < crate::PAILLIER_KEY_SIZE - 1 {
-                return Err(FsDkrError::ModuliTooSmall {
-                    party_index: join_message.get_party_index()?,
-                    moduli_size: n_length,
+    /// [RefreshMessage::quorum] over a selection of the messages.
+    pub(crate) fn quorum_of(mut quorum: Vec<&Self>, t: u16) -> FsDkrResult<Vec<&Self>> {
+        quorum.sort_by_key(|refresh_message| refresh_message.party_index);
+        quorum.truncate(t as usize + 1);
+
+        let mut party_indices = HashSet::new();
+        let mut old_party_indices = HashSet::new();
+        for refresh_message in quorum.iter() {
+            if !party_indices.insert(refresh_message.party_index) {
+                return Err(FsDkrError::DuplicateQuorumIndex {
+                    index: refresh_message.party_index,
+                });
+            }
+            if !old_party_indices.insert(refresh_message.old_party_index) {
+                return Err(FsDkrError::DuplicateQuorumIndex {
+                    index: refresh_message.old_party_index,
                 });
             }
+        }
+        Ok(quorum)
+    }
+
+    /// Computes the public commitment to the new share of the party at `index` (0 based), by
+    /// combining the committed points of the quorum with the lagrange coefficients `li_vec`.
+    pub(crate) fn aggregate_committed_point(
+        quorum: &[&Self],
+        li_vec: &[Scalar<E>],
+        index: usize,
+    ) -> Point<E> {
+        li_vec
+            .iter()
+            .zip(quorum.iter())
+            .fold(Point::<E>::zero(), |acc, (li, refresh_message)| {
+                acc + refresh_message.points_committed_vec[index].clone() * li.clone()
+            })
+    }
+
+    /// Checks that the coefficient commitments of the quorum combine into a well formed commitment
+    /// to the new polynomial: all of them commit to polynomials of the same degree, and the
+    /// lagrange weighted sum of their constant terms, the constant term of the new polynomial,
+    /// is the group public key. The latter can not be attributed to a single message, it is
+    /// reported with party index 0. An existing party can attribute it with
+    /// [Strictness::Paranoid], which checks every constant term against `pk_vec`.
+    pub(crate) fn validate_aggregate_commitments(
+        quorum: &[&Self],
+        li_vec: &[Scalar<E>],
+        public_key: &Point<E>,
+    ) -> FsDkrResult<()> {
+        let degree = quorum[0].coefficients_committed_vec.commitments.len();
+        if let Some(refresh_message) = quorum.iter().find(|refresh_message| {
+            refresh_message.coefficients_committed_vec.commitments.len() != degree
+        }) {
+            return Err(FsDkrError::CoefficientCommitmentMismatch {
+                party_index: refresh_message.party_index,
+            });
+        }
+
+        let constant_term = li_vec.iter().zip(quorum.iter()).fold(
+            Point::<E>::zero(),
+            |acc, (li, refresh_message)| {
+                acc + &refresh_message.coefficients_committed_vec.commitments[0] * li
+            },
+        );
+        if constant_term != *public_key {
+            return Err(FsDkrError::CoefficientCommitmentMismatch { party_index: 0 });
+        }
+        Ok(())
+    }
+
+    /// The new public shares of the `n` parties of the new committee, combined from the
+    /// committed points of the quorum with the lagrange coefficients `li_vec`.
+    pub(crate) fn public_shares(quorum: &[&Self], li_vec: &[Scalar<E>], n: usize) -> Vec<Point<E>> {
+        (0..n)
+            .map(|i| RefreshMessage::aggregate_committed_point(quorum, li_vec, i))
+            .collect()
+    }
+
+    /// Interpolates the new public shares `pk_vec` in the exponent at zero, giving the public key
+    /// they share. The degree of the new polynomial is the one the quorum committed to.
+    pub(crate) fn reconstruct_public_key(
+        quorum: &[&Self],
+        pk_vec: &[Point<E>],
+    ) -> FsDkrResult<Point<E>> {
+        let new_t = match quorum[0].coefficients_committed_vec.commitments.len() {
+            0 => return Err(FsDkrError::PublicKeyReconstructionMismatch),
+            len => len - 1,
+        };
+        if pk_vec.len() <= new_t {
+            return Err(FsDkrError::PublicKeyReconstructionMismatch);
+        }
+
+        let indices: Vec<u16> = (0..=new_t as u16).collect();
+        let li_vec = lagrange_coefficients::<E>(&indices);
+        Ok(indices
+            .iter()
+            .zip(li_vec.iter())
+            .fold(Point::<E>::zero(), |acc, (index, li)| {
+                acc + &pk_vec[*index as usize] * li
+            }))
+    }
+
+    /// Checks that the new public shares `pk_vec`, combined from the committed points of the
+    /// quorum, interpolate in the exponent to `public_key`, the key the quorum claims to share.
+    pub(crate) fn validate_public_key_reconstruction(
+        quorum: &[&Self],
+        pk_vec: &[Point<E>],
+        public_key: &Point<E>,
+    ) -> FsDkrResult<()> {
+        if RefreshMessage::reconstruct_public_key(quorum, pk_vec)? != *public_key {
+            return Err(FsDkrError::PublicKeyReconstructionMismatch);
+        }
+        Ok(())
+    }
+
+    /// Verifies the PDLwSlack and range proofs binding each encrypted share to its committed
+    /// point. `paillier_key_vec` and `h1_h2_n_tilde_vec` are the keys the shares were encrypted
+    /// to, indexed by the receiving party.
+    pub(crate) fn verify_share_proofs(
+        &self,
+        paillier_key_vec: &[EncryptionKey],
+        h1_h2_n_tilde_vec: &[DLogStatement],
+        n: usize,
+    ) -> FsDkrResult<()> {
+        for i in 0..n {
+            self.verify_share_proof(i, paillier_key_vec, h1_h2_n_tilde_vec)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies the PDL and range proofs of the encrypted share in slot `i` (0 based).
+    pub(crate) fn verify_share_proof(
+        &self,
+        i: usize,
+        paillier_key_vec: &[EncryptionKey],
+        h1_h2_n_tilde_vec: &[DLogStatement],
+    ) -> FsDkrResult<()> {
+        let statement = PDLwSlackStatement {
+            ciphertext: self.points_encrypted_vec[i].clone(),
+            ek: paillier_key_vec[i].clone(),
+            Q: self.points_committed_vec[i].clone(),
+            G: Point::<E>::generator().to_point(),
+            h1: h1_h2_n_tilde_vec[i].g.clone(),
+            h2: h1_h2_n_tilde_vec[i].ni.clone(),
+            N_tilde: h1_h2_n_tilde_vec[i].N.clone(),
+        };
+        self.pdl_proof_vec[i].verify(&statement)?;
+        if !self.range_proofs[i].verify(&statement.ciphertext, &statement.ek, &h1_h2_n_tilde_vec[i])
+        {
+            return Err(FsDkrError::RangeProof { party_index: i });
+        }
+        Ok(())
+    }
+
+    /// `paillier_key_vec` is positional, the key at position `i` belongs to party index `i + 1`,
+    /// and signing relies on it. Checks in debug builds that the keys installed by a collect
+    /// ended up at the positions of their senders.
+    pub(crate) fn debug_assert_key_positions(
+        paillier_key_vec: &[EncryptionKey],
+        refresh_messages: &[Self],
+        join_messages: &[JoinMessage<E, H, M>],
+    ) {
+        for refresh_message in refresh_messages.iter() {
+            debug_assert!(
+                paillier_key_vec[(refresh_message.party_index - 1) as usize] == refresh_message.ek
+            );
+        }
+        for join_message in join_messages.iter() {
+            if let Some(party_index) = join_message.party_index {
+                debug_assert!(paillier_key_vec[(party_index - 1) as usize] == join_message.ek);
+            }
+        }
+    }
+
+    /// Verifies the ring-pedersen parameters broadcasted by the sender.
+    pub(crate) fn verify_ring_pedersen(&self) -> FsDkrResult<()> {
+        RingPedersenProof::verify(&self.ring_pedersen_proof, &self.ring_pedersen_statement)
+    }
+
+    /// Verifies the correctness proof and the modulus size of the sender's new Paillier key.
+    pub(crate) fn verify_paillier_key(&self) -> FsDkrResult<()> {
+        self.verify_paillier_key_with::<KzenPaillier>()
+    }
+
+    /// Same as [RefreshMessage::verify_paillier_key], verifying the proof with `B`.
+    pub(crate) fn verify_paillier_key_with<B: PaillierBackend>(&self) -> FsDkrResult<()> {
+        verify_paillier_key_with::<B>(&self.ek, &self.dk_correctness_proof, self.party_index)
+    }
+
+    pub(crate) fn get_ciphertext_sum<'a, B: PaillierBackend>(
+        quorum: &'a [&'a Self],
+        party_index: u16,
+        parameters: &'a ShamirSecretSharing,
+        ek: &'a EncryptionKey,
+    ) -> (RawCiphertext<'a>, Vec<Scalar<E>>, CiphertextSumInfo) {
+        //decrypt the new share
+        // we first homomorphically add all ciphertext of the quorum encrypted using our encryption key
+        let ciphertext_vec: Vec<_> = (0..quorum.len())
+            .map(|k| quorum[k].points_encrypted_vec[(party_index - 1) as usize].clone())
+            .collect();
+
+        // optimization - one decryption
+        let li_vec = RefreshMessage::quorum_lagrange_coefficients(quorum, parameters);
+        let sum_info = CiphertextSumInfo::from_coefficients(&li_vec);
+
+        let ciphertext_vec_at_indices_mapped: Vec<_> = (0..(parameters.threshold + 1) as usize)
+            .map(|i| B::mul(ek, &ciphertext_vec[i], &li_vec[i].to_bigint()))
+            .collect();
+
+        // start from the neutral ciphertext 1 rather than a fresh encryption of zero, so that the
+        // sum is a deterministic function of the quorum and can be recomputed by an auditor
+        let ciphertext_sum = ciphertext_vec_at_indices_mapped
+            .iter()
+            .fold(BigInt::one(), |acc, x| B::add(ek, &acc, x));
+
+        (RawCiphertext::from(ciphertext_sum), li_vec, sum_info)
+    }
+
+    /// Computes the lagrange coefficients mapping the shares of the quorum, see
+    /// [RefreshMessage::quorum], to the new sharing parameters. Only the first `threshold + 1`
+    /// members of the quorum are weighted, see [lagrange_coefficients].
+    pub(crate) fn quorum_lagrange_coefficients(
+        quorum: &[&Self],
+        parameters: &ShamirSecretSharing,
+    ) -> Vec<Scalar<E>> {
+        let indices: Vec<u16> = (0..(parameters.threshold + 1) as usize)
+            .map(|i| quorum[i].old_party_index - 1)
+            .collect();
+        lagrange_coefficients::<E>(&indices)
+    }
+
+    /// Recomputes the homomorphic sum of the quorum's ciphertexts that the party at `party_index`
+    /// decrypts into its new share, at threshold `t` and under its current Paillier key `ek`, the
+    /// key the shares were encrypted to. Matches the sum returned by
+    /// [RefreshMessage::collect_with_ciphertext_sum] for the same messages.
+    pub fn ciphertext_sum(
+        refresh_messages: &[Self],
+        party_index: u16,
+        t: u16,
+        ek: &EncryptionKey,
+    ) -> FsDkrResult<BigInt> {
+        RefreshMessage::validate_structure(refresh_messages, t)?;
+        crate::validate_committee_size(refresh_messages[0].points_encrypted_vec.len())?;
+        let n = refresh_messages[0].points_encrypted_vec.len() as u16;
+        if party_index == 0 || party_index > n {
+            return Err(FsDkrError::PartyIndexOutOfRange {
+                index: party_index,
+                n,
+            });
+        }
+        let parameters = ShamirSecretSharing {
+            threshold: t,
+            share_count: n,
+        };
+        let quorum = RefreshMessage::quorum(refresh_messages, t)?;
+        let (ciphertext_sum, _, _) = RefreshMessage::get_ciphertext_sum::<KzenPaillier>(
+            &quorum,
+            party_index,
+            &parameters,
+            ek,
+        );
+        Ok(ciphertext_sum.0.into_owned())
+    }
 
-            // if the proof checks, we add the new paillier public key to the key
-            local_key.paillier_key_vec[(party_index - 1) as usize] = join_message.ek.clone();
+    /// Describes the homomorphic sum a party computes from `refresh_messages` before decrypting
+    /// its new share, see [CiphertextSumInfo].
+    pub fn ciphertext_sum_info(
+        refresh_messages: &[Self],
+        parameters: &ShamirSecretSharing,
+    ) -> FsDkrResult<CiphertextSumInfo> {
+        crate::validate_committee_size(parameters.share_count as usize)?;
+        let quorum = RefreshMessage::quorum(refresh_messages, parameters.threshold)?;
+        Ok(CiphertextSumInfo::from_coefficients(
+            &RefreshMessage::quorum_lagrange_coefficients(&quorum, parameters),
+        ))
+    }
+
+    /// Checks that a decrypted share is below the bound implied by `sum_info`. Every summand
+    /// carries a range proof bounding its plaintext by `q^3`, anything larger means that the
+    /// ciphertext sum was not formed from the proven ciphertexts.
+    pub(crate) fn validate_share_range(
+        new_share: &BigInt,
+        sum_info: &CiphertextSumInfo,
+        party_index: u16,
+    ) -> FsDkrResult<()> {
+        let share_bound = crate::curve_order::<E>().pow(3);
+        if new_share.is_negative() || *new_share >= sum_info.plaintext_bound(&share_bound) {
+            return Err(FsDkrError::ShareOutOfRange { party_index });
         }
+        Ok(())
+    }
+
+    pub fn replace(
+        new_parties: &[JoinMessage<E, H, M>],
+        key: &mut LocalKey<E>,
+        old_to_new_map: &HashMap<u16, u16>,
+        new_n: u16,
+    ) -> FsDkrResult<(Self, DecryptionKey)> {
+        RefreshMessage::replace_with_epoch(new_parties, key, old_to_new_map, new_n, 0)
+    }
 
-        let new_share = Paillier::decrypt(&local_key.paillier_dk, cipher_text_sum)
-            .0
-            .into_owned();
+    /// Same as [RefreshMessage::replace], producing the refresh message at `epoch`, see
+    /// [RefreshMessage::distribute_with_epoch].
+    pub fn replace_with_epoch(
+        new_parties: &[JoinMessage<E, H, M>],
+        key: &mut LocalKey<E>,
+        old_to_new_map: &HashMap<u16, u16>,
+        new_n: u16,
+        epoch: u64,
+    ) -> FsDkrResult<(Self, DecryptionKey)> {
+        // the parties in old_to_new_map are the ones that stay, they must still form a quorum
+        let remaining = old_to_new_map.len() as u16;
+        if remaining <= key.t {
+            return Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining,
+                threshold: key.t,
+            });
+        }
+        let current_len = key.paillier_key_vec.len() as u16;
+        let mut paillier_key_h1_h2_n_tilde_hash_map: HashMap<u16, (EncryptionKey, DLogStatement)> =
+            HashMap::new();
+        for old_party_index in old_to_new_map.keys() {
+            let paillier_key = key
+                .paillier_key_vec
+                .get((old_party_index - 1) as usize)
+                .unwrap()
+                .clone();
+            let h1_h2_n_tilde = key
+                .h1_h2_n_tilde_vec
+                .get((old_party_index - 1) as usize)
+                .unwrap()
+                .clone();
+            paillier_key_h1_h2_n_tilde_hash_map.insert(
+                old_to_new_map.get(old_party_index).unwrap().clone(),
+                (paillier_key, h1_h2_n_tilde),
+            );
+        }
 
-        let new_share_fe: Scalar<E> = Scalar::<E>::from(&new_share);
+        for new_party_index in paillier_key_h1_h2_n_tilde_hash_map.keys() {
+            if new_party_index.clone() <= current_len {
+                key.paillier_key_vec[(new_party_index - 1) as usize] =
+                    paillier_key_h1_h2_n_tilde_hash_map
+                        .get(new_party_index)
+                        .unwrap()
+                        .clone()
+                        .0;
+                key.h1_h2_n_tilde_vec[(new_party_index - 1) as usize] =
+                    paillier_key_h1_h2_n_tilde_hash_map
+                        .get(new_party_index)
+                        .unwrap()
+                        .clone()
+                        .1;
+            } else {
+                key.paillier_key_vec.insert(
+                    (new_party_index - 1) as usize,
+                    paillier_key_h1_h2_n_tilde_hash_map
+                        .get(new_party_index)
+                        .unwrap()
+                        .clone()
+                        .0,
+                );
+                key.h1_h2_n_tilde_vec.insert(
+                    (new_party_index - 1) as usize,
+                    paillier_key_h1_h2_n_tilde_hash_map
+                        .get(new_party_index)
+                        .unwrap()
+                        .clone()
+                        .1,
+                );
+            }
+        }
+
+        for join_message in new_parties.iter() {
+            let party_index = join_message.get_party_index()?;
+            if party_index <= current_len {
+                key.paillier_key_vec[(party_index - 1) as usize] = join_message.ek.clone();
+                key.h1_h2_n_tilde_vec[(party_index - 1) as usize] =
+                    join_message.dlog_statement.clone();
+            } else {
+                key.paillier_key_vec
+                    .insert((party_index - 1) as usize, join_message.ek.clone());
+                key.h1_h2_n_tilde_vec.insert(
+                    (party_index - 1) as usize,
+                    join_message.dlog_statement.clone(),
+                );
+            }
+        }
+        let old_party_index = key.i;
+        key.i = *old_to_new_map.get(&key.i).unwrap();
+        key.n = new_n;
+
+        RefreshMessage::distribute_with_epoch(old_party_index, key, new_n as u16, epoch)
+    }
+
+    /// Distribute phase of a [Reshuffle] of the committee `key` belongs to. The Paillier keys and
+    /// the statements of the remaining parties are moved to their new indices, the ones of the
+    /// joining parties are taken from `join_messages`, which must carry exactly the indices of
+    /// `reshuffle.add`, and the share of the party is re-shared at the new threshold. A party
+    /// that is removed by the reshuffle does not take part in the round. `key` is not modified,
+    /// it is moved to the new committee by [RefreshMessage::collect_reshuffle].
+    pub fn reshuffle(
+        join_messages: &[JoinMessage<E, H, M>],
+        key: &LocalKey<E>,
+        reshuffle: &Reshuffle,
+    ) -> FsDkrResult<(Self, DecryptionKey)> {
+        let mut reshuffled_key = RefreshMessage::reshuffled_key(join_messages, key, reshuffle)?;
+        let new_n = reshuffled_key.n;
+        RefreshMessage::distribute_with_threshold::<KzenPaillier>(
+            key.i,
+            &mut reshuffled_key,
+            reshuffle.new_threshold,
+            new_n,
+            0,
+            None,
+        )
+    }
+
+    /// A copy of `key` moved to the committee after `reshuffle`: the index, the size and the
+    /// Paillier keys and statements of the committee are the ones of the new committee, the share
+    /// and the threshold are still the ones of the current committee.
+    fn reshuffled_key(
+        join_messages: &[JoinMessage<E, H, M>],
+        key: &LocalKey<E>,
+        reshuffle: &Reshuffle,
+    ) -> FsDkrResult<LocalKey<E>> {
+        reshuffle.validate(Threshold(key.t), ShareCount(key.n))?;
+        let old_to_new_map = reshuffle.old_to_new_map(key.n)?;
+        let new_party_index =
+            *old_to_new_map
+                .get(&key.i)
+                .ok_or_else(|| FsDkrError::InvalidReshuffle {
+                    reason: "the distributing party is removed".to_string(),
+                })?;
+        let new_n = reshuffle.new_n(key.n)?;
+
+        let mut join_indices = join_messages
+            .iter()
+            .map(|join_message| join_message.get_party_index())
+            .collect::<FsDkrResult<Vec<u16>>>()?;
+        let mut added = reshuffle.add.clone();
+        join_indices.sort_unstable();
+        added.sort_unstable();
+        if join_indices != added {
+            return Err(FsDkrError::InvalidReshuffle {
+                reason: "the join messages do not match the added indices".to_string(),
+            });
+        }
+
+        let mut committee: Vec<Option<(EncryptionKey, DLogStatement)>> = vec![None; new_n as usize];
+        for (old_party_index, new_party_index) in old_to_new_map.iter() {
+            committee[(new_party_index - 1) as usize] = Some((
+                key.paillier_key_vec[(old_party_index - 1) as usize].clone(),
+                key.h1_h2_n_tilde_vec[(old_party_index - 1) as usize].clone(),
+            ));
+        }
+        for join_message in join_messages.iter() {
+            committee[(join_message.get_party_index()? - 1) as usize] =
+                Some((join_message.ek.clone(), join_message.dlog_statement.clone()));
+        }
+        let (paillier_key_vec, h1_h2_n_tilde_vec) = committee
+            .into_iter()
+            .enumerate()
+            .map(|(i, slot)| {
+                slot.ok_or(FsDkrError::MissingContribution {
+                    party_index: i as u16 + 1,
+                })
+            })
+            .collect::<FsDkrResult<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+
+        let mut reshuffled_key = key.clone();
+        reshuffled_key.paillier_key_vec = paillier_key_vec;
+        reshuffled_key.h1_h2_n_tilde_vec = h1_h2_n_tilde_vec;
+        reshuffled_key.i = new_party_index;
+        reshuffled_key.n = new_n;
+        Ok(reshuffled_key)
+    }
+
+    pub fn collect(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_with_strictness(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+        )
+    }
+
+    /// Recommended entry point of the collect phase for an existing party. The full verification
+    /// suite runs on all the received messages before anything is decrypted, on success the
+    /// refreshed key is returned and `local_key` is left untouched, so that on failure the caller
+    /// still holds its previous key. The caller should replace `local_key` with the returned key.
+    /// [RefreshMessage::collect] is left for callers that verified the messages separately.
+    pub fn collect_verified(
+        refresh_messages: &[Self],
+        local_key: &LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<LocalKey<E>> {
+        let new_n = refresh_messages.len() + join_messages.len();
+        RefreshMessage::verify_transcript(
+            refresh_messages,
+            join_messages,
+            Some(local_key),
+            local_key.t,
+            new_n as u16,
+        )?;
+
+        let mut new_key = local_key.clone();
+        RefreshMessage::collect_with_strictness(
+            refresh_messages,
+            &mut new_key,
+            new_dk,
+            join_messages,
+            Strictness::Structural,
+        )?;
+        RefreshMessage::validate_own_public_share(&new_key)?;
+        Ok(new_key)
+    }
+
+    /// Same as [RefreshMessage::collect] for join messages received over an unauthenticated
+    /// channel: every join message must carry a valid signature by the key `registry` holds for
+    /// its index, see [SignedJoinMessage::verify_all].
+    pub fn collect_signed(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        signed_join_messages: &[SignedJoinMessage<E, H, M>],
+        registry: &HashMap<u16, Point<E>>,
+    ) -> FsDkrResult<()> {
+        let join_messages = SignedJoinMessage::verify_all(signed_join_messages, registry)?;
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, &join_messages)
+    }
+
+    /// Same as [RefreshMessage::collect], refusing to refresh a key of another group than the one
+    /// of the public key `expected_pk`, e.g. the output of the original keygen. Fails with
+    /// [FsDkrError::UnexpectedPublicKey] if `local_key` or one of the refresh messages carries
+    /// another public key.
+    pub fn collect_expecting(
+        expected_pk: &Point<E>,
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        RefreshMessage::validate_expected_public_key(refresh_messages, expected_pk)?;
+        if local_key.y_sum_s != *expected_pk {
+            return Err(FsDkrError::UnexpectedPublicKey {
+                expected: hex_point(expected_pk),
+                got: hex_point(&local_key.y_sum_s),
+            });
+        }
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)
+    }
+
+    /// Same as [RefreshMessage::collect], additionally requiring that all refresh messages were
+    /// produced at `expected_epoch`, the epoch of `local_key`. Returns the epoch of the refreshed
+    /// key.
+    pub fn collect_with_epoch(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        expected_epoch: u64,
+    ) -> FsDkrResult<u64> {
+        RefreshMessage::validate_epoch(refresh_messages, expected_epoch)?;
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)?;
+        Ok(expected_epoch + 1)
+    }
+
+    /// Describes which parties a collect of `refresh_messages` and `join_messages` at threshold
+    /// `t` combines, without running the collect.
+    pub fn participation(
+        refresh_messages: &[Self],
+        join_messages: &[JoinMessage<E, H, M>],
+        t: u16,
+    ) -> FsDkrResult<ParticipationRecord> {
+        RefreshMessage::validate_structure(refresh_messages, t)?;
+        let quorum = RefreshMessage::quorum(refresh_messages, t)?
+            .iter()
+            .map(|refresh_message| refresh_message.party_index)
+            .collect();
+        let joiners = join_messages
+            .iter()
+            .map(|join_message| join_message.get_party_index())
+            .collect::<FsDkrResult<Vec<u16>>>()?;
+        Ok(ParticipationRecord { quorum, joiners })
+    }
+
+    /// Same as [RefreshMessage::collect], additionally returning the [ParticipationRecord] of
+    /// the parties whose material was combined into the refreshed key.
+    pub fn collect_with_participation(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<ParticipationRecord> {
+        let participation =
+            RefreshMessage::participation(refresh_messages, join_messages, local_key.t)?;
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)?;
+        Ok(participation)
+    }
+
+    /// Collect phase of a [Reshuffle], for a remaining party that distributed with
+    /// [RefreshMessage::reshuffle]. The quorum is formed at the threshold of the current
+    /// committee and every message must re-share over a polynomial of the new degree. The
+    /// collect runs on a copy of `local_key` moved to the new committee, `local_key` is replaced
+    /// by it only if the whole collect succeeds and is left unchanged otherwise.
+    pub fn collect_reshuffle(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        reshuffle: &Reshuffle,
+    ) -> FsDkrResult<()> {
+        for refresh_message in refresh_messages.iter() {
+            refresh_message.validate_polynomial_degree(reshuffle.new_threshold)?;
+        }
+        let mut new_key = RefreshMessage::reshuffled_key(join_messages, local_key, reshuffle)?;
+        RefreshMessage::collect(refresh_messages, &mut new_key, new_dk, join_messages)?;
+
+        // the vss_scheme is the polynomial of the party's own contribution, as after distribute,
+        // a party whose message is not in the round commits to its new share like a joining
+        // party does
+        new_key.vss_scheme = match refresh_messages
+            .iter()
+            .find(|refresh_message| refresh_message.party_index == new_key.i)
+        {
+            Some(refresh_message) => refresh_message.coefficients_committed_vec.clone(),
+            None => {
+                VerifiableSS::share(reshuffle.new_threshold, new_key.n, &new_key.keys_linear.x_i).0
+            }
+        };
+        new_key.t = reshuffle.new_threshold;
+        *local_key = new_key;
+        Ok(())
+    }
+
+    /// Same as [RefreshMessage::collect], additionally returning the homomorphic sum of
+    /// ciphertexts that was decrypted into the new share. The sum is encrypted under the party's
+    /// previous Paillier key, see [RefreshMessage::ciphertext_sum] to recompute it.
+    pub fn collect_with_ciphertext_sum(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<BigInt> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::All,
+            &mut |_| {},
+        )
+    }
+
+    /// Same as [RefreshMessage::collect], decrypting with the precomputed `context`, which must
+    /// have been built for the `paillier_dk` of `local_key`, see [DecryptionContext]. The
+    /// refreshed key is the same as the one [RefreshMessage::collect] produces.
+    pub fn collect_with_context(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        context: &DecryptionContext,
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Context(context),
+            MessageProofs::All,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+
+    /// Same as [RefreshMessage::collect_with_strictness], verifying the share proofs of the
+    /// party's own slot only, for messages reassembled by
+    /// [RefreshBroadcast::assemble](crate::point_to_point::RefreshBroadcast::assemble).
+    pub(crate) fn collect_own_share(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        strictness: Strictness,
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            strictness,
+            ShareDecryption::Key,
+            MessageProofs::OwnShare,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+
+    /// Same as [RefreshMessage::collect], with the amount of verification selected by
+    /// `strictness`. With [Strictness::Structural] no proof is verified, so the messages must have
+    /// been verified by other means.
+    pub fn collect_with_strictness(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        strictness: Strictness,
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            strictness,
+            ShareDecryption::Key,
+            MessageProofs::All,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+
+    /// Same as [RefreshMessage::collect_with_strictness], first requiring the Paillier modulus
+    /// of every joining party to have at least `min_bits` bits, see
+    /// [JoinMessage::enforce_min_modulus].
+    pub fn collect_with_min_modulus(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        strictness: Strictness,
+        min_bits: usize,
+    ) -> FsDkrResult<()> {
+        for join_message in join_messages.iter() {
+            join_message.enforce_min_modulus(min_bits)?;
+        }
+        RefreshMessage::collect_with_strictness(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            strictness,
+        )
+    }
+
+    /// Same as [RefreshMessage::collect], reporting the verification of every message to
+    /// `progress`, see [VerificationEvent].
+    pub fn collect_with_progress(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        progress: &mut dyn FnMut(VerificationEvent),
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::All,
+            progress,
+        )
+        .map(|_| ())
+    }
+
+    /// Public part of the collect of a round in a committee of `n` parties at threshold `t`, for
+    /// a coordinator that only needs to confirm the group key, e.g. to display it, before the
+    /// parties collect. The new public shares are combined from the committed points of the
+    /// quorum exactly as collect does, and the public key they interpolate to is returned. No
+    /// share is decrypted and no proof is verified, so no key material is needed; the public key
+    /// is only confirmed once the parties collect. Fails with
+    /// [FsDkrError::BroadcastedPublicKeyError] if a message claims another public key.
+    pub fn collect_public_only(
+        refresh_messages: &[Self],
+        Threshold(t): Threshold,
+        ShareCount(n): ShareCount,
+    ) -> FsDkrResult<Point<E>> {
+        RefreshMessage::validate_collect(refresh_messages, Threshold(t), ShareCount(n))?;
+        let quorum = RefreshMessage::quorum(refresh_messages, t)?;
+        let parameters = ShamirSecretSharing {
+            threshold: t,
+            share_count: n,
+        };
+        let li_vec = RefreshMessage::quorum_lagrange_coefficients(&quorum, &parameters);
+        let pk_vec = RefreshMessage::public_shares(&quorum, &li_vec, n as usize);
+        let public_key = RefreshMessage::reconstruct_public_key(&quorum, &pk_vec)?;
+        RefreshMessage::validate_aggregate_commitments(&quorum, &li_vec, &public_key)?;
+
+        if refresh_messages
+            .iter()
+            .any(|refresh_message| refresh_message.public_key != public_key)
+        {
+            return Err(FsDkrError::BroadcastedPublicKeyError);
+        }
+        Ok(public_key)
+    }
+
+    /// Completes the new public shares of a round from the ones of the existing slots, computing
+    /// only the public shares of the `joiners`, the indices assigned to the joining parties, and
+    /// of the slots `1..=new_t + 1`. `pk_vec` holds the public shares of the other slots of the
+    /// new committee in ascending order, computed from the same `refresh_messages`, e.g. the
+    /// `pk_vec` an existing party collected. `t` is the threshold of the current committee. The
+    /// result is the `pk_vec` of the whole new committee, the same as a full recomputation.
+    ///
+    /// The recomputed slots `1..=new_t + 1` fix the polynomial of degree `new_t` the round
+    /// shares over, and every other slot of `pk_vec` is checked to lie on it by interpolation in
+    /// the exponent, which fails with [FsDkrError::PublicKeyReconstructionMismatch] if `pk_vec`
+    /// is not from the same round. The result is also checked to interpolate to the public key
+    /// broadcasted by every message.
+    pub fn extend_pk_vec(
+        pk_vec: &[Point<E>],
+        refresh_messages: &[Self],
+        Threshold(t): Threshold,
+        joiners: &[u16],
+    ) -> FsDkrResult<Vec<Point<E>>> {
+        RefreshMessage::validate_structure(refresh_messages, t)?;
+        let new_n = refresh_messages[0].points_committed_vec.len();
+        crate::validate_committee_size(new_n)?;
+        if pk_vec.len() + joiners.len() != new_n {
+            return Err(FsDkrError::PublicSharesLengthMismatch {
+                expected: new_n.saturating_sub(joiners.len()),
+                got: pk_vec.len(),
+            });
+        }
+        let mut seen_indices = HashSet::new();
+        for party_index in joiners.iter() {
+            if *party_index == 0 || *party_index as usize > new_n {
+                return Err(FsDkrError::PartyIndexOutOfRange {
+                    index: *party_index,
+                    n: new_n as u16,
+                });
+            }
+            if !seen_indices.insert(*party_index) {
+                return Err(FsDkrError::DuplicatePartyIndex {
+                    party_index: *party_index,
+                });
+            }
+        }
+
+        let quorum = RefreshMessage::quorum(refresh_messages, t)?;
+        let new_t = match quorum[0].coefficients_committed_vec.commitments.len() {
+            0 => return Err(FsDkrError::PublicKeyReconstructionMismatch),
+            len => len - 1,
+        };
+        let parameters = ShamirSecretSharing {
+            threshold: t,
+            share_count: new_n as u16,
+        };
+        let li_vec = RefreshMessage::quorum_lagrange_coefficients(&quorum, &parameters);
+        let basis: Vec<u16> = (1..=(new_t + 1).min(new_n) as u16).collect();
+        let mut existing = pk_vec.iter();
+        let mut extended: Vec<Point<E>> = Vec::with_capacity(new_n);
+        for party_index in 1..=new_n as u16 {
+            let supplied = if joiners.contains(&party_index) {
+                None
+            } else {
+                // the lengths were checked above, every other slot has an existing share
+                Some(existing.next().cloned().unwrap_or_else(Point::<E>::zero))
+            };
+            let expected = if supplied.is_none() || basis.contains(&party_index) {
+                RefreshMessage::aggregate_committed_point(
+                    &quorum,
+                    &li_vec,
+                    (party_index - 1) as usize,
+                )
+            } else {
+                // the slots of the basis precede this one and are already in `extended`
+                crate::local_key_ext::public_share_at(&extended, &basis, party_index)
+            };
+            if supplied.map_or(false, |supplied| supplied != expected) {
+                return Err(FsDkrError::PublicKeyReconstructionMismatch);
+            }
+            extended.push(expected);
+        }
+
+        let public_key = &refresh_messages[0].public_key;
+        if refresh_messages
+            .iter()
+            .any(|refresh_message| refresh_message.public_key != *public_key)
+        {
+            return Err(FsDkrError::BroadcastedPublicKeyError);
+        }
+        RefreshMessage::validate_public_key_reconstruction(&quorum, &extended, public_key)?;
+        Ok(extended)
+    }
+
+    /// Same as [RefreshMessage::collect], also returning the party's new [SharedKeys], the
+    /// `keys_linear` of the refreshed key, the same type a GG20 keygen produces.
+    pub fn collect_shared_keys(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<SharedKeys<E>> {
+        RefreshMessage::collect(refresh_messages, local_key, new_dk, join_messages)?;
+        Ok(local_key.keys_linear.clone())
+    }
+
+    /// Same as [RefreshMessage::distribute] with the Paillier operations of `B`, see
+    /// [PaillierBackend].
+    pub fn distribute_with_backend<B: PaillierBackend>(
+        old_party_index: u16,
+        local_key: &mut LocalKey<E>,
+        new_n: u16,
+    ) -> FsDkrResult<(RefreshMessage<E, H, M>, DecryptionKey)> {
+        let t = local_key.t;
+        RefreshMessage::distribute_with_threshold::<B>(
+            old_party_index,
+            local_key,
+            t,
+            new_n,
+            0,
+            None,
+        )
+    }
+
+    /// Same as [RefreshMessage::collect] with the Paillier operations of `B`, see
+    /// [PaillierBackend].
+    pub fn collect_with_backend<B: PaillierBackend>(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<B>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::All,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn collect_returning_sum<B: PaillierBackend>(
+        refresh_messages: &[Self],
+        mut local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        strictness: Strictness,
+        decryption: ShareDecryption,
+        message_proofs: MessageProofs,
+        progress: &mut dyn FnMut(VerificationEvent),
+    ) -> FsDkrResult<BigInt> {
+        match decryption {
+            ShareDecryption::Context(context) if !context.matches(&local_key.paillier_dk) => {
+                return Err(FsDkrError::DecryptionVerificationFailed {
+                    party_index: local_key.i,
+                });
+            }
+            ShareDecryption::Plaintext(_) => (),
+            _ => RefreshMessage::validate_own_decryption_key(local_key)?,
+        }
+
+        // a message delivered twice would otherwise be counted as another party of the committee
+        let new_n = refresh_messages.len() + join_messages.len();
+        RefreshMessage::validate_unique_senders(refresh_messages, join_messages, new_n as u16)?;
+        RefreshMessage::validate_collect_with_strictness(
+            refresh_messages,
+            Threshold(local_key.t),
+            ShareCount(new_n as u16),
+            strictness,
+        )?;
+
+        if strictness.verifies_proofs() && message_proofs != MessageProofs::Verified {
+            for refresh_message in refresh_messages.iter() {
+                let party_index = refresh_message.party_index;
+                progress(VerificationEvent::StartedParty(party_index));
+                if message_proofs == MessageProofs::OwnShare {
+                    refresh_message.verify_share_proof(
+                        (local_key.i - 1) as usize,
+                        &local_key.paillier_key_vec,
+                        &local_key.h1_h2_n_tilde_vec,
+                    )?;
+                } else if message_proofs == MessageProofs::OtherShares {
+                    for i in (0..new_n).filter(|i| *i != (local_key.i - 1) as usize) {
+                        refresh_message.verify_share_proof(
+                            i,
+                            &local_key.paillier_key_vec,
+                            &local_key.h1_h2_n_tilde_vec,
+                        )?;
+                    }
+                } else {
+                    refresh_message.verify_share_proofs(
+                        &local_key.paillier_key_vec,
+                        &local_key.h1_h2_n_tilde_vec,
+                        new_n,
+                    )?;
+                }
+                progress(VerificationEvent::ProofVerified(
+                    party_index,
+                    ProofKind::EncryptedShares,
+                ));
+
+                // Verify ring-pedersen parameters
+                refresh_message.verify_ring_pedersen()?;
+                progress(VerificationEvent::ProofVerified(
+                    party_index,
+                    ProofKind::RingPedersen,
+                ));
+
+                refresh_message.verify_paillier_key_with::<B>()?;
+                progress(VerificationEvent::ProofVerified(
+                    party_index,
+                    ProofKind::PaillierKey,
+                ));
+                progress(VerificationEvent::PartyComplete(party_index));
+            }
+
+            for join_message in join_messages.iter() {
+                let party_index = join_message.get_party_index()?;
+                progress(VerificationEvent::StartedParty(party_index));
+                join_message.verify_ring_pedersen()?;
+                progress(VerificationEvent::ProofVerified(
+                    party_index,
+                    ProofKind::RingPedersen,
+                ));
+
+                join_message.verify_paillier_key_correctness_with::<B>()?;
+                join_message.verify_paillier_key_size()?;
+                progress(VerificationEvent::ProofVerified(
+                    party_index,
+                    ProofKind::PaillierKey,
+                ));
+
+                join_message.verify_dlog_proofs()?;
+                progress(VerificationEvent::ProofVerified(
+                    party_index,
+                    ProofKind::DLogStatement,
+                ));
+                progress(VerificationEvent::PartyComplete(party_index));
+            }
+        }
+
+        if strictness.is_paranoid() {
+            for refresh_message in refresh_messages.iter() {
+                refresh_message.validate_reshared_share(&local_key.pk_vec)?;
+            }
+        }
+
+        let quorum = RefreshMessage::quorum(refresh_messages, local_key.t)?;
+        let old_ek = local_key.paillier_key_vec[(local_key.i - 1) as usize].clone();
+        // the quorum holds shares of the committee at its current threshold, which differs from
+        // the threshold of the new vss_scheme after a reshuffle
+        let parameters = ShamirSecretSharing {
+            threshold: local_key.t,
+            share_count: new_n as u16,
+        };
+        let (cipher_text_sum, li_vec, sum_info) =
+            RefreshMessage::get_ciphertext_sum::<B>(&quorum, local_key.i, &parameters, &old_ek);
+        if strictness.verifies_proofs() {
+            RefreshMessage::validate_aggregate_commitments(&quorum, &li_vec, &local_key.y_sum_s)?;
+        }
+
+        // the new paillier public keys are installed only once every check below passed, so that
+        // a failed collect leaves the key as it was and can be retried with other messages
+        let mut paillier_key_vec = local_key.paillier_key_vec.clone();
+        for refresh_message in refresh_messages.iter() {
+            // the proofs were checked above, we add the new paillier public key to the key
+            paillier_key_vec[(refresh_message.party_index - 1) as usize] =
+                refresh_message.ek.clone();
+        }
+
+        for join_message in join_messages {
+            let party_index = join_message.get_party_index()?;
+            paillier_key_vec[(party_index - 1) as usize] = join_message.ek.clone();
+        }
+
+        RefreshMessage::debug_assert_key_positions(
+            &paillier_key_vec,
+            refresh_messages,
+            join_messages,
+        );
+
+        let cipher_text_sum = cipher_text_sum.0.into_owned();
+        let new_share = match decryption {
+            ShareDecryption::Key => B::decrypt(
+                &local_key.paillier_dk,
+                &old_ek,
+                &cipher_text_sum,
+                local_key.i,
+            )?,
+            ShareDecryption::Context(context) => {
+                context.decrypt_verified(&old_ek, &cipher_text_sum, local_key.i)?
+            }
+            ShareDecryption::Plaintext(plaintext) => plaintext.clone(),
+        };
+        RefreshMessage::validate_share_range(&new_share, &sum_info, local_key.i)?;
+
+        let new_share_fe: Scalar<E> =
+            Scalar::<E>::from(&BigInt::modulus(&new_share, &crate::curve_order::<E>()));
+
+        // without the proofs of the own slot, or for a plaintext decrypted elsewhere, the new
+        // share is bound to the committed points only by this check
+        if strictness.is_paranoid()
+            || message_proofs == MessageProofs::OtherShares
+            || matches!(decryption, ShareDecryption::Plaintext(_))
+        {
+            let committed_share = RefreshMessage::aggregate_committed_point(
+                &quorum,
+                &li_vec,
+                (local_key.i - 1) as usize,
+            );
+            if Point::<E>::generator() * &new_share_fe != committed_share {
+                return Err(FsDkrError::PublicShareValidationError);
+            }
+        }
+
+        // update local key list of local public keys (X_i = g^x_i is updated by adding all committed points to that party)
+        let pk_vec = RefreshMessage::public_shares(&quorum, &li_vec, new_n);
+        if strictness.verifies_proofs() {
+            RefreshMessage::validate_public_key_reconstruction(
+                &quorum,
+                &pk_vec,
+                &local_key.y_sum_s,
+            )?;
+        }
 
         // zeroize the old dk key
         local_key.paillier_dk.q.zeroize();
@@ -451,18 +2091,9 @@ impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
         local_key.keys_linear.x_i = new_share_fe.clone();
         local_key.keys_linear.y = Point::<E>::generator() * new_share_fe;
 
-        // update local key list of local public keys (X_i = g^x_i is updated by adding all committed points to that party)
-        for i in 0..refresh_messages.len() + join_messages.len() as usize {
-            local_key.pk_vec.insert(
-                i,
-                refresh_messages[0].points_committed_vec[i].clone() * li_vec[0].clone(),
-            );
-            for j in 1..local_key.t as usize + 1 {
-                local_key.pk_vec[i] = local_key.pk_vec[i].clone()
-                    + refresh_messages[j].points_committed_vec[i].clone() * li_vec[j].clone();
-            }
-        }
+        local_key.pk_vec = pk_vec;
+        local_key.paillier_key_vec = paillier_key_vec;
 
-        Ok(())
+        Ok(cipher_text_sum)
     }
 }
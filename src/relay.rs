@@ -0,0 +1,51 @@
+//! Refresh rounds forwarded by a relay that is not trusted.
+//!
+//! Parties without direct connectivity can broadcast through a relay that routes the messages of
+//! a round to every recipient. The relay can not read the new shares, they are Paillier encrypted
+//! to the keys of the recipients, but without further protection it could alter a message or
+//! forward a message under another sender's index. Every sender signs its message before handing
+//! it to the relay, into a [SignedRefreshMessage] or a [SignedJoinMessage], and a recipient runs
+//! [verify_relay_integrity] on everything the relay delivered before collecting: a message that
+//! was altered, attributed to another party or forwarded twice is rejected.
+//!
+//! The relay still sees who sends how much and when, and it can drop messages: a round missing
+//! too many of them fails in collect like over any other channel.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::refresh_message::RefreshMessage;
+use crate::signed_message::{SignedJoinMessage, SignedRefreshMessage};
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::{Curve, Point};
+use std::collections::{HashMap, HashSet};
+
+/// Authenticates the messages of a round delivered by a relay against the long term key
+/// `registry` holds for the index of their sender, see the [module level](self) documentation.
+/// Returns the refresh and join messages, ready to be collected. Fails with
+/// [FsDkrError::MessageSignatureInvalid] if a message does not carry a valid signature of its
+/// sender, with [FsDkrError::UnknownSigner] if no key is registered for a sender and with
+/// [FsDkrError::DuplicatePartyIndex] if two messages claim the same sender.
+pub fn verify_relay_integrity<E: Curve, H: Digest + Clone, const M: usize>(
+    signed_refresh_messages: &[SignedRefreshMessage<E, H, M>],
+    signed_join_messages: &[SignedJoinMessage<E, H, M>],
+    registry: &HashMap<u16, Point<E>>,
+) -> FsDkrResult<(Vec<RefreshMessage<E, H, M>>, Vec<JoinMessage<E, H, M>>)> {
+    let refresh_messages = SignedRefreshMessage::verify_all(signed_refresh_messages, registry)?;
+    let join_messages = SignedJoinMessage::verify_all(signed_join_messages, registry)?;
+
+    let mut senders = HashSet::new();
+    for party_index in refresh_messages
+        .iter()
+        .map(|refresh_message| refresh_message.party_index)
+        .chain(
+            join_messages
+                .iter()
+                .filter_map(|join_message| join_message.party_index),
+        )
+    {
+        if !senders.insert(party_index) {
+            return Err(FsDkrError::DuplicatePartyIndex { party_index });
+        }
+    }
+    Ok((refresh_messages, join_messages))
+}
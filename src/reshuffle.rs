@@ -0,0 +1,267 @@
+//! Changing the membership and the threshold of a committee in a single refresh.
+//!
+//! A [Reshuffle] describes the parties leaving the committee, the indices assigned to the parties
+//! joining it and the threshold of the new committee. Every remaining party passes the same
+//! reshuffle to [RefreshMessage::reshuffle](crate::refresh_message::RefreshMessage::reshuffle)
+//! and to [RefreshMessage::collect_reshuffle](crate::refresh_message::RefreshMessage::collect_reshuffle),
+//! the joining parties pass it to
+//! [JoinMessage::collect_reshuffle](crate::add_party_message::JoinMessage::collect_reshuffle).
+//! The old shares are re-shared over a polynomial of the new degree, so the new committee holds
+//! shares of the same secret at the new threshold once the round is collected. Distribute does
+//! not modify the key, and collect replaces it with the key of the new committee only if the
+//! whole collect succeeds, so a party whose round fails keeps its current key.
+//!
+//! Before the round, [Reshuffle::plan] computes the committee the reshuffle is expected to
+//! produce as a [RefreshPlan], which an operator can review, and after the round
+//! [RefreshPlan::matches] checks the collected key against it.
+//!
+//! A removal is only effective because the round re-shares over fresh polynomials: the new
+//! sharing agrees with the old one in the secret and nowhere else, so the old shares of the
+//! removed parties are not shares of the new sharing and can not be combined with the new shares
+//! of the remaining parties. [verify_removed_shares_revoked] checks this invariant on the public
+//! shares of the old and the new key.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::local_key_ext::{active_indices, public_share_at};
+use crate::params::{ShareCount, Threshold};
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+/// A change of committee applied atomically by one refresh round.
+///
+/// `remove` holds indices in the current committee, `add` holds indices in the new committee.
+/// The remaining parties keep their relative order and are renumbered into the indices of the new
+/// committee that are not in `add`, see [Reshuffle::old_to_new_map].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reshuffle {
+    /// Indices the joining parties are assigned in the new committee.
+    pub add: Vec<u16>,
+    /// Indices of the current committee that leave it.
+    pub remove: Vec<u16>,
+    /// Threshold of the new committee.
+    pub new_threshold: u16,
+}
+
+impl Reshuffle {
+    /// Checks that the reshuffle can be applied to a `t` out of `n` committee: indices are in
+    /// range and not repeated, no index is both added and removed, enough parties remain to form
+    /// a quorum of the current committee and the new committee can reach a quorum at the new
+    /// threshold, which must keep an honest majority. A removal that would leave the key
+    /// unrecoverable fails with [FsDkrError::QuorumWouldBeUnreachable] before anything is sent.
+    pub fn validate(&self, Threshold(t): Threshold, ShareCount(n): ShareCount) -> FsDkrResult<()> {
+        let invalid = |reason: &str| FsDkrError::InvalidReshuffle {
+            reason: reason.to_string(),
+        };
+
+        let mut removed = HashSet::new();
+        for index in self.remove.iter() {
+            if *index == 0 || *index > n {
+                return Err(FsDkrError::PartyIndexOutOfRange { index: *index, n });
+            }
+            if !removed.insert(*index) {
+                return Err(invalid("an index is removed more than once"));
+            }
+        }
+
+        let remaining = self.active_after_removal(n)?;
+        if remaining <= t {
+            return Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining,
+                threshold: t,
+            });
+        }
+
+        crate::validate_committee_size(remaining as usize + self.add.len())?;
+        let new_n = self.new_n(n)?;
+        let mut added = HashSet::new();
+        for index in self.add.iter() {
+            if removed.contains(index) {
+                return Err(invalid("an index is both added and removed"));
+            }
+            if *index == 0 || *index > new_n {
+                return Err(FsDkrError::PartyIndexOutOfRange {
+                    index: *index,
+                    n: new_n,
+                });
+            }
+            if !added.insert(*index) {
+                return Err(invalid("an index is added more than once"));
+            }
+        }
+
+        if new_n <= self.new_threshold {
+            return Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining: new_n,
+                threshold: self.new_threshold,
+            });
+        }
+        if self.new_threshold == 0 || self.new_threshold > new_n / 2 {
+            return Err(invalid("the new threshold violates the honest majority"));
+        }
+        Ok(())
+    }
+
+    /// Checks the reshuffle like [Reshuffle::validate] and returns the committee it is expected
+    /// to produce from a `t` out of `n` committee, see [RefreshPlan].
+    pub fn plan(&self, t: Threshold, n: ShareCount) -> FsDkrResult<RefreshPlan> {
+        self.validate(t, n)?;
+        let new_n = self.new_n(*n)?;
+        Ok(RefreshPlan {
+            t: self.new_threshold,
+            n: new_n,
+            active_indices: (1..=new_n).collect(),
+        })
+    }
+
+    /// Number of parties of the current `n` party committee that remain after the removal, the
+    /// parties that can contribute to the round. Fails with [FsDkrError::InvalidReshuffle] if
+    /// more parties are removed than the committee has.
+    pub fn active_after_removal(&self, n: u16) -> FsDkrResult<u16> {
+        u16::try_from(self.remove.len())
+            .ok()
+            .and_then(|removed| n.checked_sub(removed))
+            .ok_or_else(|| FsDkrError::InvalidReshuffle {
+                reason: "more parties are removed than the committee has".to_string(),
+            })
+    }
+
+    /// Size of the new committee, given the size `n` of the current one. Fails with
+    /// [FsDkrError::InvalidReshuffle] if it does not fit the index range.
+    pub fn new_n(&self, n: u16) -> FsDkrResult<u16> {
+        let remaining = self.active_after_removal(n)?;
+        u16::try_from(self.add.len())
+            .ok()
+            .and_then(|added| remaining.checked_add(added))
+            .ok_or_else(|| FsDkrError::InvalidReshuffle {
+                reason: "the new committee has too many parties".to_string(),
+            })
+    }
+
+    /// Maps the index of every remaining party of the current `n` party committee to its index in
+    /// the new committee. The remaining parties are assigned, in ascending order, the indices of
+    /// the new committee that are not in `add`. Fails like [Reshuffle::new_n].
+    pub fn old_to_new_map(&self, n: u16) -> FsDkrResult<HashMap<u16, u16>> {
+        let remaining = (1..=n).filter(|index| !self.remove.contains(index));
+        let free = (1..=self.new_n(n)?).filter(|index| !self.add.contains(index));
+        Ok(remaining.zip(free).collect())
+    }
+}
+
+/// The committee a refresh is expected to produce, see [Reshuffle::plan].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshPlan {
+    /// Threshold of the new committee.
+    pub t: u16,
+    /// Size of the new committee.
+    pub n: u16,
+    /// Indices of the new committee holding a key, in ascending order.
+    pub active_indices: Vec<u16>,
+}
+
+impl RefreshPlan {
+    /// Checks that `result`, the key a party collected from the round, belongs to the planned
+    /// committee: same threshold, same size and a key in exactly the planned slots. Fails with
+    /// [FsDkrError::CommitteeMismatch] holding the planned and the actual committee otherwise.
+    pub fn matches<E: Curve>(&self, result: &LocalKey<E>) -> FsDkrResult<()> {
+        let actual = RefreshPlan {
+            t: result.t,
+            n: result.n,
+            active_indices: active_indices(result),
+        };
+        if actual != *self {
+            return Err(FsDkrError::CommitteeMismatch {
+                expected: self.clone(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the shares the parties at the indices `removed` of the committee of `old_key`
+/// held are not shares of the sharing of `new_key`, the key collected after their removal, see
+/// the [module level](self) documentation. The new sharing is interpolated in the exponent from
+/// the public shares of `t + 1` active slots of `new_key` and evaluated at every removed index.
+/// Fails with [FsDkrError::RemovedShareStillValid] if it matches the old public share there,
+/// i.e. if the round did not re-share over a fresh polynomial.
+pub fn verify_removed_shares_revoked<E: Curve>(
+    old_key: &LocalKey<E>,
+    new_key: &LocalKey<E>,
+    removed: &[u16],
+) -> FsDkrResult<()> {
+    let quorum: Vec<u16> = active_indices(new_key)
+        .into_iter()
+        .filter(|index| (*index as usize) <= new_key.pk_vec.len())
+        .take(new_key.t as usize + 1)
+        .collect();
+    if quorum.len() <= new_key.t as usize {
+        return Err(FsDkrError::QuorumWouldBeUnreachable {
+            remaining: quorum.len() as u16,
+            threshold: new_key.t,
+        });
+    }
+
+    for party_index in removed.iter() {
+        let position = party_index.checked_sub(1).map(usize::from);
+        let old_public_share = match position.and_then(|position| old_key.pk_vec.get(position)) {
+            Some(old_public_share) => old_public_share,
+            None => {
+                return Err(FsDkrError::PartyIndexOutOfRange {
+                    index: *party_index,
+                    n: old_key.pk_vec.len() as u16,
+                })
+            }
+        };
+        if public_share_at(&new_key.pk_vec, &quorum, *party_index) == *old_public_share {
+            return Err(FsDkrError::RemovedShareStillValid {
+                party_index: *party_index,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the indices of `1..=n` that are not in `occupied`, in ascending order, i.e. the slots
+/// of an `n` party committee that can be assigned to joining parties. Entries of `occupied`
+/// outside of `1..=n` are ignored.
+pub fn free_slots(occupied: &[u16], n: u16) -> Vec<u16> {
+    let occupied: HashSet<u16> = occupied.iter().copied().collect();
+    (1..=n).filter(|index| !occupied.contains(index)).collect()
+}
+
+/// Number of parties of the committee of `local_key` that must be online and send a refresh
+/// message for a refresh to complete: a quorum of `t + 1` parties of the current committee,
+/// whichever parties are added or removed.
+pub fn required_online_for_refresh<E: Curve>(local_key: &LocalKey<E>) -> usize {
+    local_key.t as usize + 1
+}
+
+/// Checks that `reshuffle` can be applied to the committee of `local_key`, see
+/// [Reshuffle::validate], and that the parties of the current committee expected to be online
+/// form a quorum of it. Removed parties do not send a refresh message and are not counted, nor
+/// are indices outside of the committee. Returns [required_online_for_refresh] on success and
+/// fails with [FsDkrError::QuorumWouldBeUnreachable] if too few parties are expected online, so
+/// that a refresh that can not complete is not scheduled.
+pub fn required_online_for_reshuffle<E: Curve>(
+    local_key: &LocalKey<E>,
+    reshuffle: &Reshuffle,
+    online: &[u16],
+) -> FsDkrResult<usize> {
+    reshuffle.validate(Threshold(local_key.t), ShareCount(local_key.n))?;
+    let online: HashSet<u16> = online
+        .iter()
+        .copied()
+        .filter(|index| *index >= 1 && *index <= local_key.n && !reshuffle.remove.contains(index))
+        .collect();
+    let required = required_online_for_refresh(local_key);
+    if online.len() < required {
+        return Err(FsDkrError::QuorumWouldBeUnreachable {
+            remaining: online.len() as u16,
+            threshold: local_key.t,
+        });
+    }
+    Ok(required)
+}
@@ -18,12 +18,13 @@ use curv::cryptographic_primitives::hashing::DigestExt;
 use curv::elliptic::curves::Curve;
 use curv::BigInt;
 use paillier::EncryptionKey;
-use paillier::{KeyGeneration, Paillier};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
+use crate::entropy::Entropy;
 use crate::error::FsDkrError;
 use crate::error::FsDkrResult;
+use crate::paillier_backend::KzenPaillier;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "E: Curve, H: Digest + Clone")]
@@ -46,16 +47,23 @@ pub struct RingPedersenWitness<E: Curve, H: Digest + Clone> {
 
 impl<E: Curve, H: Digest + Clone> RingPedersenStatement<E, H> {
     pub fn generate() -> (Self, RingPedersenWitness<E, H>) {
+        Self::generate_with(&mut Entropy::Os).expect("the operating system generator does not fail")
+    }
+
+    /// Same as [RingPedersenStatement::generate], drawing the parameters from `entropy`.
+    pub(crate) fn generate_with(
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<(Self, RingPedersenWitness<E, H>)> {
         let (ek_tilde, dk_tilde) =
-            Paillier::keypair_with_modulus_size(crate::PAILLIER_KEY_SIZE).keys();
+            entropy.paillier_keypair::<KzenPaillier>(crate::PAILLIER_KEY_SIZE)?;
         let one = BigInt::one();
         let phi = (&dk_tilde.p - &one) * (&dk_tilde.q - &one);
-        let r = BigInt::sample_below(&ek_tilde.n);
-        let lambda = BigInt::sample_below(&phi);
+        let r = entropy.sample_below(&ek_tilde.n)?;
+        let lambda = entropy.sample_below(&phi)?;
         let t = BigInt::mod_pow(&r, &BigInt::from(2), &ek_tilde.n);
         let s = BigInt::mod_pow(&t, &lambda, &ek_tilde.n);
 
-        (
+        Ok((
             Self {
                 S: s,
                 T: t,
@@ -70,7 +78,7 @@ impl<E: Curve, H: Digest + Clone> RingPedersenStatement<E, H> {
                 lambda,
                 phantom: PhantomData,
             },
-        )
+        ))
     }
 }
 
@@ -85,17 +93,35 @@ pub struct RingPedersenProof<E: Curve, H: Digest + Clone, const M: usize> {
 
 // Link to the UC non-interactive threshold ECDSA paper
 impl<E: Curve, H: Digest + Clone, const M: usize> RingPedersenProof<E, H, M> {
+    /// Returns true if no commitment or response of the proof is negative.
+    pub(crate) fn is_non_negative(&self) -> bool {
+        self.A
+            .iter()
+            .chain(self.Z.iter())
+            .all(|value| *value >= BigInt::zero())
+    }
+
     pub fn prove(
         witness: &RingPedersenWitness<E, H>,
         statement: &RingPedersenStatement<E, H>,
     ) -> RingPedersenProof<E, H, M> {
+        Self::prove_with(witness, statement, &mut Entropy::Os)
+            .expect("the operating system generator does not fail")
+    }
+
+    /// Same as [RingPedersenProof::prove], drawing the nonces from `entropy`.
+    pub(crate) fn prove_with(
+        witness: &RingPedersenWitness<E, H>,
+        statement: &RingPedersenStatement<E, H>,
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<RingPedersenProof<E, H, M>> {
         // 1. Sample alphas from 1 -> m from \phi(N)
         let mut a = [(); M].map(|_| BigInt::zero());
         let mut A = [(); M].map(|_| BigInt::zero());
         let mut hash = H::new();
         for i in 0..M {
             // TODO: Consider ensuring we get a unit element of this subgroup
-            let a_i = BigInt::sample_below(&statement.phi);
+            let a_i = entropy.sample_below(&statement.phi)?;
             a[i] = a_i.clone();
             let A_i = BigInt::mod_pow(&statement.T, &a_i, &statement.N);
             A[i] = A_i.clone();
@@ -116,11 +142,11 @@ impl<E: Curve, H: Digest + Clone, const M: usize> RingPedersenProof<E, H, M> {
             Z[i] = z_i;
         }
 
-        Self {
+        Ok(Self {
             A: A.to_vec(),
             Z: Z.to_vec(),
             phantom: PhantomData,
-        }
+        })
     }
 
     pub fn verify(
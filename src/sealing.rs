@@ -0,0 +1,232 @@
+//! Encryption at rest of a [LocalKey], enabled by the `sealing` feature.
+//!
+//! The serde encoding of a [LocalKey] carries the secret share `keys_linear.x_i` and the Paillier
+//! decryption key `paillier_dk` in the clear. [SealLocalKey::seal] encrypts them with
+//! ChaCha20-Poly1305 under a key derived from a passphrase with PBKDF2-HMAC-SHA256, and leaves
+//! the public fields readable in [SealedLocalKey::public], e.g. to look up the group public key or
+//! the index of a stored key without the passphrase. The public fields are authenticated as
+//! associated data, so they can not be swapped between sealed keys. [unseal] fails with
+//! [FsDkrError::UnsealFailed] on a wrong passphrase or a tampered encoding.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use curv::arithmetic::{Converter, Samplable};
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::{Curve, Point};
+use curv::BigInt;
+use hmac::Hmac;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::SharedKeys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{DecryptionKey, EncryptionKey};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+use zk_paillier::zkproofs::DLogStatement;
+
+/// Version of the [SealedLocalKey] encoding.
+pub const SEALED_KEY_VERSION: u16 = 1;
+
+/// Number of PBKDF2 iterations used for new sealed keys, and the least accepted when unsealing.
+const PBKDF2_ROUNDS: u32 = 600_000;
+/// Most PBKDF2 iterations accepted when unsealing, so that a crafted sealed key can not make the
+/// key derivation arbitrarily slow.
+const MAX_PBKDF2_ROUNDS: u32 = 10 * PBKDF2_ROUNDS;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// The fields of a [LocalKey] that are not secret.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct PublicLocalKey<E: Curve> {
+    pub pk_vec: Vec<Point<E>>,
+    pub paillier_key_vec: Vec<EncryptionKey>,
+    pub y_sum_s: Point<E>,
+    pub h1_h2_n_tilde_vec: Vec<DLogStatement>,
+    pub vss_scheme: VerifiableSS<E, sha2::Sha256>,
+    pub i: u16,
+    pub t: u16,
+    pub n: u16,
+}
+
+/// The fields of a [LocalKey] that are encrypted by [SealLocalKey::seal].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+struct SecretLocalKey<E: Curve> {
+    keys_linear: SharedKeys<E>,
+    paillier_dk: DecryptionKey,
+}
+
+/// A [LocalKey] with its secret fields encrypted, see the [module level](self) documentation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct SealedLocalKey<E: Curve> {
+    pub version: u16,
+    pub public: PublicLocalKey<E>,
+    rounds: u32,
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+/// Sealing of a [LocalKey] for storage.
+pub trait SealLocalKey {
+    /// Encodes the key with its secret fields encrypted under `passphrase`.
+    fn seal(&self, passphrase: &[u8]) -> FsDkrResult<Vec<u8>>;
+}
+
+impl<E: Curve> SealLocalKey for LocalKey<E> {
+    fn seal(&self, passphrase: &[u8]) -> FsDkrResult<Vec<u8>> {
+        SealedLocalKey::seal(self, passphrase)?.to_bytes()
+    }
+}
+
+/// Decodes a key sealed by [SealLocalKey::seal] and decrypts its secret fields.
+pub fn unseal<E: Curve>(bytes: &[u8], passphrase: &[u8]) -> FsDkrResult<LocalKey<E>> {
+    SealedLocalKey::from_bytes(bytes)?.unseal(passphrase)
+}
+
+impl<E: Curve> SealedLocalKey<E> {
+    /// Encrypts the secret fields of `local_key` under `passphrase`, with a fresh salt and nonce.
+    pub fn seal(local_key: &LocalKey<E>, passphrase: &[u8]) -> FsDkrResult<Self> {
+        let public = PublicLocalKey {
+            pk_vec: local_key.pk_vec.clone(),
+            paillier_key_vec: local_key.paillier_key_vec.clone(),
+            y_sum_s: local_key.y_sum_s.clone(),
+            h1_h2_n_tilde_vec: local_key.h1_h2_n_tilde_vec.clone(),
+            vss_scheme: local_key.vss_scheme.clone(),
+            i: local_key.i,
+            t: local_key.t,
+            n: local_key.n,
+        };
+        let mut plaintext = serde_json::to_vec(&SecretLocalKey {
+            keys_linear: local_key.keys_linear.clone(),
+            paillier_dk: local_key.paillier_dk.clone(),
+        })
+        .map_err(|e| FsDkrError::InvalidSealedKey {
+            reason: e.to_string(),
+        })?;
+
+        let salt = random_bytes(SALT_LEN);
+        let nonce = random_bytes(NONCE_LEN);
+        let associated_data = SealedLocalKey::associated_data(&public, PBKDF2_ROUNDS, &salt)?;
+        let cipher = SealedLocalKey::<E>::cipher(passphrase, PBKDF2_ROUNDS, &salt);
+        let ciphertext = cipher.encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &associated_data,
+            },
+        );
+        plaintext.zeroize();
+
+        Ok(SealedLocalKey {
+            version: SEALED_KEY_VERSION,
+            public,
+            rounds: PBKDF2_ROUNDS,
+            salt,
+            nonce,
+            ciphertext: ciphertext.map_err(|_| FsDkrError::InvalidSealedKey {
+                reason: "encryption failed".to_string(),
+            })?,
+        })
+    }
+
+    /// Decrypts the secret fields and reassembles the [LocalKey]. Fails with
+    /// [FsDkrError::UnsealFailed] if `passphrase` is wrong or the sealed key was modified, and
+    /// with [FsDkrError::InvalidSealedKey] if its PBKDF2 iteration count is below the one used
+    /// for new sealed keys or above ten times that.
+    pub fn unseal(&self, passphrase: &[u8]) -> FsDkrResult<LocalKey<E>> {
+        if self.version != SEALED_KEY_VERSION {
+            return Err(FsDkrError::UnsupportedMessageVersion {
+                found: self.version,
+                supported: SEALED_KEY_VERSION,
+            });
+        }
+        if self.nonce.len() != NONCE_LEN {
+            return Err(FsDkrError::InvalidSealedKey {
+                reason: "nonce".to_string(),
+            });
+        }
+        if !(PBKDF2_ROUNDS..=MAX_PBKDF2_ROUNDS).contains(&self.rounds) {
+            return Err(FsDkrError::InvalidSealedKey {
+                reason: "rounds".to_string(),
+            });
+        }
+
+        let associated_data =
+            SealedLocalKey::associated_data(&self.public, self.rounds, &self.salt)?;
+        let cipher = SealedLocalKey::<E>::cipher(passphrase, self.rounds, &self.salt);
+        let mut plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&self.nonce),
+                Payload {
+                    msg: &self.ciphertext,
+                    aad: &associated_data,
+                },
+            )
+            .map_err(|_| FsDkrError::UnsealFailed)?;
+        let secret: Result<SecretLocalKey<E>, _> = serde_json::from_slice(&plaintext);
+        plaintext.zeroize();
+        let secret = secret.map_err(|e| FsDkrError::InvalidSealedKey {
+            reason: e.to_string(),
+        })?;
+
+        let public = self.public.clone();
+        Ok(LocalKey {
+            paillier_dk: secret.paillier_dk,
+            pk_vec: public.pk_vec,
+            keys_linear: secret.keys_linear,
+            paillier_key_vec: public.paillier_key_vec,
+            y_sum_s: public.y_sum_s,
+            h1_h2_n_tilde_vec: public.h1_h2_n_tilde_vec,
+            vss_scheme: public.vss_scheme,
+            i: public.i,
+            t: public.t,
+            n: public.n,
+        })
+    }
+
+    pub fn to_bytes(&self) -> FsDkrResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| FsDkrError::InvalidSealedKey {
+            reason: e.to_string(),
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> FsDkrResult<Self> {
+        serde_json::from_slice(bytes).map_err(|e| FsDkrError::InvalidSealedKey {
+            reason: e.to_string(),
+        })
+    }
+
+    fn cipher(passphrase: &[u8], rounds: u32, salt: &[u8]) -> ChaCha20Poly1305 {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2::<Hmac<sha2::Sha256>>(passphrase, salt, rounds, &mut key);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        key.zeroize();
+        cipher
+    }
+
+    // binds the public fields and the key derivation parameters to the ciphertext
+    fn associated_data(
+        public: &PublicLocalKey<E>,
+        rounds: u32,
+        salt: &[u8],
+    ) -> FsDkrResult<Vec<u8>> {
+        let mut associated_data = SEALED_KEY_VERSION.to_be_bytes().to_vec();
+        associated_data.extend_from_slice(&rounds.to_be_bytes());
+        associated_data.extend_from_slice(salt);
+        associated_data.extend(serde_json::to_vec(public).map_err(|e| {
+            FsDkrError::InvalidSealedKey {
+                reason: e.to_string(),
+            }
+        })?);
+        Ok(associated_data)
+    }
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let bytes = BigInt::sample(len * 8).to_bytes();
+    let mut padded = vec![0u8; len - bytes.len()];
+    padded.extend(bytes);
+    padded
+}
@@ -0,0 +1,194 @@
+//! Authentication of join and refresh messages sent over a channel that is not authenticated.
+//!
+//! The protocol assumes an authenticated broadcast channel. Where the transport does not provide
+//! one, a joining party signs its [JoinMessage] with a long term key known to the committee, and
+//! the collect phase only accepts [SignedJoinMessage]s whose signature verifies against the key
+//! registered for the index they were assigned. The signature is a Schnorr signature over the
+//! [JoinMessage::to_bytes] encoding of the message, so the joining party signs once its index was
+//! assigned and the signature binds it as well.
+//!
+//! Existing parties sign their [RefreshMessage]s the same way into [SignedRefreshMessage]s, over
+//! the JSON encoding of the message, which binds the party index of the sender. The signatures
+//! of join and refresh messages are domain separated, a signature on one can not be passed off
+//! as a signature on the other.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::{Digest, DigestExt};
+use curv::elliptic::curves::{Curve, Point, Scalar};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Domain separation tag of the signature challenge of a join message.
+const SIGNATURE_DOMAIN: &[u8] = b"fs-dkr/signed-join-message";
+
+/// Domain separation tag of the signature challenge of a refresh message.
+const REFRESH_SIGNATURE_DOMAIN: &[u8] = b"fs-dkr/signed-refresh-message";
+
+/// Schnorr signature `(R, s)` with `s * G = R + e * P` for the challenge `e`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "E: Curve")]
+pub struct MessageSignature<E: Curve> {
+    r: Point<E>,
+    s: Scalar<E>,
+}
+
+impl<E: Curve> MessageSignature<E> {
+    fn challenge(domain: &[u8], r: &Point<E>, verifying_key: &Point<E>, bytes: &[u8]) -> Scalar<E> {
+        sha2::Sha256::new()
+            .chain(domain)
+            .chain_point(r)
+            .chain_point(verifying_key)
+            .chain(bytes)
+            .result_scalar()
+    }
+
+    fn sign(domain: &[u8], signing_key: &Scalar<E>, bytes: &[u8]) -> Self {
+        let verifying_key = Point::<E>::generator() * signing_key;
+        let nonce = Scalar::<E>::random();
+        let r = Point::<E>::generator() * &nonce;
+        let challenge = MessageSignature::challenge(domain, &r, &verifying_key, bytes);
+        MessageSignature {
+            s: nonce + challenge * signing_key,
+            r,
+        }
+    }
+
+    fn verify(&self, domain: &[u8], verifying_key: &Point<E>, bytes: &[u8]) -> bool {
+        if verifying_key.is_zero() {
+            return false;
+        }
+        let challenge = MessageSignature::challenge(domain, &self.r, verifying_key, bytes);
+        Point::<E>::generator() * &self.s == &self.r + verifying_key * &challenge
+    }
+}
+
+/// A [JoinMessage] together with the signature of the joining party.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve, H: Digest + Clone")]
+pub struct SignedJoinMessage<E: Curve, H: Digest + Clone, const M: usize> {
+    message: JoinMessage<E, H, M>,
+    signature: MessageSignature<E>,
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
+    /// Signs the message with the long term key of the joining party, see the
+    /// [module level](crate::signed_message) documentation.
+    pub fn sign(&self, signing_key: &Scalar<E>) -> FsDkrResult<SignedJoinMessage<E, H, M>> {
+        Ok(SignedJoinMessage {
+            signature: MessageSignature::sign(SIGNATURE_DOMAIN, signing_key, &self.to_bytes()?),
+            message: self.clone(),
+        })
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> SignedJoinMessage<E, H, M> {
+    /// The signed message, not yet authenticated.
+    pub fn message(&self) -> &JoinMessage<E, H, M> {
+        &self.message
+    }
+
+    /// Checks the signature against `verifying_key` and returns the authenticated message.
+    pub fn verify(&self, verifying_key: &Point<E>) -> FsDkrResult<&JoinMessage<E, H, M>> {
+        if !self
+            .signature
+            .verify(SIGNATURE_DOMAIN, verifying_key, &self.message.to_bytes()?)
+        {
+            return Err(FsDkrError::MessageSignatureInvalid {
+                party_index: self.message.party_index.unwrap_or(0),
+            });
+        }
+        Ok(&self.message)
+    }
+
+    /// Authenticates every message against the key `registry` holds for the index it was
+    /// assigned, failing if a message was not assigned an index, if no key is registered for it
+    /// or if a signature does not verify.
+    pub fn verify_all(
+        signed_join_messages: &[Self],
+        registry: &HashMap<u16, Point<E>>,
+    ) -> FsDkrResult<Vec<JoinMessage<E, H, M>>> {
+        signed_join_messages
+            .iter()
+            .map(|signed_join_message| {
+                let party_index = signed_join_message.message.get_party_index()?;
+                let verifying_key = registry
+                    .get(&party_index)
+                    .ok_or(FsDkrError::UnknownSigner { party_index })?;
+                signed_join_message.verify(verifying_key).cloned()
+            })
+            .collect()
+    }
+}
+
+/// A [RefreshMessage] together with the signature of its sender.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "E: Curve, H: Digest + Clone")]
+pub struct SignedRefreshMessage<E: Curve, H: Digest + Clone, const M: usize> {
+    message: RefreshMessage<E, H, M>,
+    signature: MessageSignature<E>,
+}
+
+/// The encoding of `refresh_message` that is signed.
+fn refresh_message_bytes<E: Curve, H: Digest + Clone, const M: usize>(
+    refresh_message: &RefreshMessage<E, H, M>,
+) -> FsDkrResult<Vec<u8>> {
+    serde_json::to_vec(refresh_message).map_err(|e| FsDkrError::SerializationError {
+        reason: e.to_string(),
+    })
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Signs the message with the long term key of the sender, see the
+    /// [module level](crate::signed_message) documentation.
+    pub fn sign(&self, signing_key: &Scalar<E>) -> FsDkrResult<SignedRefreshMessage<E, H, M>> {
+        Ok(SignedRefreshMessage {
+            signature: MessageSignature::sign(
+                REFRESH_SIGNATURE_DOMAIN,
+                signing_key,
+                &refresh_message_bytes(self)?,
+            ),
+            message: self.clone(),
+        })
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> SignedRefreshMessage<E, H, M> {
+    /// The signed message, not yet authenticated.
+    pub fn message(&self) -> &RefreshMessage<E, H, M> {
+        &self.message
+    }
+
+    /// Checks the signature against `verifying_key` and returns the authenticated message.
+    pub fn verify(&self, verifying_key: &Point<E>) -> FsDkrResult<&RefreshMessage<E, H, M>> {
+        if !self.signature.verify(
+            REFRESH_SIGNATURE_DOMAIN,
+            verifying_key,
+            &refresh_message_bytes(&self.message)?,
+        ) {
+            return Err(FsDkrError::MessageSignatureInvalid {
+                party_index: self.message.party_index,
+            });
+        }
+        Ok(&self.message)
+    }
+
+    /// Authenticates every message against the key `registry` holds for the index of its
+    /// sender, failing if no key is registered for it or if a signature does not verify.
+    pub fn verify_all(
+        signed_refresh_messages: &[Self],
+        registry: &HashMap<u16, Point<E>>,
+    ) -> FsDkrResult<Vec<RefreshMessage<E, H, M>>> {
+        signed_refresh_messages
+            .iter()
+            .map(|signed_refresh_message| {
+                let party_index = signed_refresh_message.message.party_index;
+                let verifying_key = registry
+                    .get(&party_index)
+                    .ok_or(FsDkrError::UnknownSigner { party_index })?;
+                signed_refresh_message.verify(verifying_key).cloned()
+            })
+            .collect()
+    }
+}
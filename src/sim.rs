@@ -0,0 +1,292 @@
+//! In-memory simulation of the broadcast channel between the parties of a committee.
+//!
+//! [Network] collects the [JoinMessage]s and [RefreshMessage]s of all the parties, delivers them
+//! to everyone and drives each party's collect phase, so that an end-to-end test reduces to
+//! `Network::run_refresh(t, n, joiners)`. [sign_with_quorum] checks that the resulting keys can
+//! still sign. It is available with the `sim` feature.
+//!
+//! By default every party receives the messages in the order they were sent.
+//! [Network::refresh_with_delivery] instead delivers them like a real transport might, see
+//! [Delivery]: every party receives the messages in its own order, and possibly some of them
+//! twice. A collect must not depend on the order, and must reject a message delivered twice
+//! rather than count it as another party.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use crate::split::{join_split, split_committee, ChildDistribution, ChildJoin};
+use curv::arithmetic::Converter;
+use curv::elliptic::curves::Secp256k1;
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::{
+    verify, Keys, SignatureRecid,
+};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::{
+    Keygen, LocalKey,
+};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::sign::{
+    OfflineStage, SignManual,
+};
+use round_based::dev::Simulation;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+fn simulation_error<T: Debug>(error: T) -> FsDkrError {
+    FsDkrError::SimulationError {
+        reason: format!("{:?}", error),
+    }
+}
+
+/// How the simulated channel delivers the messages of a round to each party.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// Every party receives the messages in the order they were sent.
+    InOrder,
+    /// Every party receives the messages in an order derived from `seed` and its index.
+    Shuffled { seed: u64 },
+    /// Same as [Delivery::Shuffled], with `copies` of the messages delivered a second time.
+    Duplicated { seed: u64, copies: usize },
+}
+
+impl Delivery {
+    /// The refresh and join messages of a round as received by the party at `party_index`.
+    fn deliver<T: Clone>(&self, messages: &[T], party_index: u16, kind: &[u8]) -> Vec<T> {
+        let mut delivered = messages.to_vec();
+        let (seed, copies) = match *self {
+            Delivery::InOrder => return delivered,
+            Delivery::Shuffled { seed } => (seed, 0),
+            Delivery::Duplicated { seed, copies } => (seed, copies),
+        };
+        let mut tag = Vec::new();
+        tag.extend_from_slice(&seed.to_be_bytes());
+        tag.extend_from_slice(&party_index.to_be_bytes());
+        tag.extend_from_slice(kind);
+        shuffle(&mut delivered, &tag);
+        let repeated: Vec<T> = delivered.iter().take(copies).cloned().collect();
+        delivered.extend(repeated);
+        tag.push(1);
+        shuffle(&mut delivered, &tag);
+        delivered
+    }
+}
+
+/// Fisher-Yates shuffle of `items` driven by SHA-256 of `tag` and a counter, so that a delivery
+/// can be replayed from its seed.
+fn shuffle<T>(items: &mut [T], tag: &[u8]) {
+    for i in (1..items.len()).rev() {
+        let digest = Sha256::new()
+            .chain(tag)
+            .chain((i as u64).to_be_bytes())
+            .finalize();
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&digest[..8]);
+        let j = (u64::from_be_bytes(prefix) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// A committee of parties connected by a simulated broadcast channel.
+pub struct Network<const M: usize> {
+    keys: Vec<LocalKey<Secp256k1>>,
+}
+
+impl<const M: usize> Network<M> {
+    /// Creates a `t` out of `n` committee by simulating keygen.
+    pub fn keygen(t: u16, n: u16) -> FsDkrResult<Self> {
+        let mut simulation = Simulation::new();
+        simulation.enable_benchmarks(false);
+
+        for i in 1..=n {
+            simulation.add_party(Keygen::new(i, t, n).map_err(simulation_error)?);
+        }
+
+        let keys = simulation.run().map_err(simulation_error)?;
+        Ok(Network { keys })
+    }
+
+    /// Creates a network from the keys of an existing committee, sorted by party index.
+    pub fn from_keys(mut keys: Vec<LocalKey<Secp256k1>>) -> Self {
+        keys.sort_by_key(|key| key.i);
+        Network { keys }
+    }
+
+    /// The keys of the committee, sorted by party index.
+    pub fn keys(&self) -> &[LocalKey<Secp256k1>] {
+        &self.keys
+    }
+
+    pub fn into_keys(self) -> Vec<LocalKey<Secp256k1>> {
+        self.keys
+    }
+
+    /// Runs one refresh round in which the parties at the indices in `joiners` are replaced by
+    /// new parties, every other party keeps its index. With no joiners this is a plain refresh.
+    /// The keys are only updated if every party collected successfully.
+    pub fn refresh(&mut self, joiners: &[u16]) -> FsDkrResult<()> {
+        self.refresh_with_delivery(joiners, Delivery::InOrder)
+    }
+
+    /// Same as [Network::refresh], every party receiving the messages of the round as described
+    /// by `delivery`.
+    pub fn refresh_with_delivery(
+        &mut self,
+        joiners: &[u16],
+        delivery: Delivery,
+    ) -> FsDkrResult<()> {
+        let n = self.keys.len() as u16;
+        let t = self.keys[0].t;
+
+        let mut keys: Vec<LocalKey<Secp256k1>> = self
+            .keys
+            .iter()
+            .filter(|key| !joiners.contains(&key.i))
+            .cloned()
+            .collect();
+        let old_to_new_map: HashMap<u16, u16> = keys.iter().map(|key| (key.i, key.i)).collect();
+
+        // the new parties broadcast their join messages, the index they were assigned is public
+        let mut join_messages: Vec<JoinMessage<Secp256k1, Sha256, M>> =
+            Vec::with_capacity(joiners.len());
+        let mut join_keys: Vec<Keys> = Vec::with_capacity(joiners.len());
+        for party_index in joiners.iter() {
+            let (mut join_message, paillier_keys) = JoinMessage::distribute()?;
+            join_message.set_party_index(*party_index);
+            join_messages.push(join_message);
+            join_keys.push(paillier_keys);
+        }
+
+        // the existing parties broadcast their refresh messages
+        let mut refresh_messages = Vec::with_capacity(keys.len());
+        let mut new_dks = Vec::with_capacity(keys.len());
+        for key in keys.iter_mut() {
+            let (refresh_message, new_dk) =
+                RefreshMessage::replace(&join_messages, key, &old_to_new_map, n)?;
+            refresh_messages.push(refresh_message);
+            new_dks.push(new_dk);
+        }
+
+        // every party collects everything that was broadcasted
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(
+                &delivery.deliver(&refresh_messages, key.i, b"refresh"),
+                key,
+                new_dk,
+                &delivery.deliver(&join_messages, key.i, b"join"),
+            )?;
+        }
+        for ((party_index, join_message), paillier_keys) in
+            joiners.iter().zip(join_messages.iter()).zip(join_keys)
+        {
+            keys.push(join_message.collect(
+                &delivery.deliver(&refresh_messages, *party_index, b"refresh"),
+                paillier_keys,
+                &delivery.deliver(&join_messages, *party_index, b"join"),
+                Threshold(t),
+                ShareCount(n),
+            )?);
+        }
+
+        keys.sort_by_key(|key| key.i);
+        self.keys = keys;
+        Ok(())
+    }
+
+    /// Splits the committee into two child committees, the parties at `quorum` distributing to
+    /// both children with [split_committee] and every other party joining both children with
+    /// [join_split]. Every party collects the messages of each child.
+    pub fn split(&self, quorum: &[u16]) -> FsDkrResult<(Self, Self)> {
+        let mut first_joins = Vec::new();
+        let mut second_joins = Vec::new();
+        for key in self.keys.iter().filter(|key| !quorum.contains(&key.i)) {
+            let [first_join, second_join] = join_split::<Secp256k1, Sha256, M>(key)?;
+            first_joins.push(first_join);
+            second_joins.push(second_join);
+        }
+        let join_messages = |joins: &[ChildJoin<Secp256k1, Sha256, M>]| -> Vec<_> {
+            joins
+                .iter()
+                .map(|join| join.join_message().clone())
+                .collect()
+        };
+        let (first_join_messages, second_join_messages) =
+            (join_messages(&first_joins), join_messages(&second_joins));
+
+        let mut first = Vec::with_capacity(quorum.len());
+        let mut second = Vec::with_capacity(quorum.len());
+        for key in self.keys.iter().filter(|key| quorum.contains(&key.i)) {
+            let [first_child, second_child] = split_committee::<Secp256k1, Sha256, M>(
+                key,
+                [&first_join_messages[..], &second_join_messages[..]],
+                0,
+            )?;
+            first.push(first_child);
+            second.push(second_child);
+        }
+
+        let collect_child =
+            |child: Vec<ChildDistribution<Secp256k1, Sha256, M>>,
+             joins: Vec<ChildJoin<Secp256k1, Sha256, M>>,
+             join_messages: &[JoinMessage<Secp256k1, Sha256, M>]| {
+                let refresh_messages: Vec<_> = child
+                    .iter()
+                    .map(|distribution| distribution.refresh_message().clone())
+                    .collect();
+                let mut keys = child
+                    .into_iter()
+                    .map(|distribution| distribution.collect(&refresh_messages, join_messages))
+                    .collect::<FsDkrResult<Vec<_>>>()?;
+                for join in joins {
+                    keys.push(join.collect(&refresh_messages, join_messages)?);
+                }
+                Ok::<_, FsDkrError>(Network::from_keys(keys))
+            };
+        Ok((
+            collect_child(first, first_joins, &first_join_messages)?,
+            collect_child(second, second_joins, &second_join_messages)?,
+        ))
+    }
+
+    /// Simulates keygen of a `t` out of `n` committee followed by a refresh in which the parties
+    /// at `joiners` are replaced, and returns the resulting keys sorted by party index.
+    pub fn run_refresh(t: u16, n: u16, joiners: &[u16]) -> FsDkrResult<Vec<LocalKey<Secp256k1>>> {
+        let mut network = Network::keygen(t, n)?;
+        network.refresh(joiners)?;
+        Ok(network.into_keys())
+    }
+}
+
+/// Runs GG20 signing of the SHA-256 digest of `message` by the parties holding `keys`, which
+/// must be at least `t + 1` keys of the same committee, and returns the signature after checking
+/// it against the group public key `y_sum_s`.
+pub fn sign_with_quorum(
+    keys: &[LocalKey<Secp256k1>],
+    message: &[u8],
+) -> FsDkrResult<SignatureRecid> {
+    let s_l: Vec<u16> = keys.iter().map(|key| key.i).collect();
+
+    let mut simulation = Simulation::new();
+    simulation.enable_benchmarks(false);
+    for (i, key) in (1..).zip(keys) {
+        simulation
+            .add_party(OfflineStage::new(i, s_l.clone(), key.clone()).map_err(simulation_error)?);
+    }
+    let offline = simulation.run().map_err(simulation_error)?;
+
+    let message = BigInt::from_bytes(&Sha256::digest(message));
+    let (mut parties, local_sigs): (Vec<_>, Vec<_>) = offline
+        .into_iter()
+        .map(|completed_offline_stage| SignManual::new(message.clone(), completed_offline_stage))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(simulation_error)?
+        .into_iter()
+        .unzip();
+    let signature = parties
+        .remove(0)
+        .complete(&local_sigs[1..])
+        .map_err(simulation_error)?;
+
+    verify(&signature, &keys[0].y_sum_s, &message).map_err(simulation_error)?;
+    Ok(signature)
+}
@@ -0,0 +1,165 @@
+//! Upper bounds on the encoded size of the messages, for transports that pre-allocate buffers.
+//!
+//! The encoding of a message, see [JoinMessage::to_bytes], is dominated by its big integers,
+//! whose size follows from the size of the Paillier moduli and, for a [RefreshMessage], from the
+//! size of the committee. [JoinMessage::serialized_size_hint] and
+//! [RefreshMessage::serialized_size_hint] add up a bound on every value of the message: a big
+//! integer of at most `b` bits is encoded as a quoted hex string of at most `b / 4` digits, and
+//! the bound on a point or a scalar is twice the size of a full length one. Every field is
+//! charged a generous fixed cost for its name and separators. The bounds are never below the
+//! encoded size, they exceed it by a small factor.
+
+use crate::add_party_message::JoinMessage;
+use crate::freshness::NONCE_BITS;
+use crate::refresh_message::RefreshMessage;
+use curv::arithmetic::One;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::{Curve, Point, Scalar};
+use curv::BigInt;
+
+/// Cost of the name, quotes and separators of a field, or of the separator of an element.
+const FIELD_OVERHEAD: usize = 64;
+
+/// Number of entries of the `sigma_vec` of a `NiCorrectKeyProof`.
+const CORRECT_KEY_PROOF_LEN: usize = 11;
+
+/// Bits the responses of the sigma protocols exceed the modulus by, besides the bits of the
+/// challenge: their masks are sampled below the cube of the curve order times the modulus.
+const RESPONSE_SLACK_BITS: usize = 1024;
+
+/// Bound on the encoding of a non negative big integer of at most `bits` bits, as a field.
+fn bigint(bits: usize) -> usize {
+    FIELD_OVERHEAD + (bits + 3) / 4 + 2
+}
+
+/// Bound on the encoding of a field holding an integer of up to 20 digits.
+fn integer() -> usize {
+    FIELD_OVERHEAD + 20
+}
+
+/// Bound on the encoding of a point of `E`, as a field.
+fn point<E: Curve>() -> usize {
+    let encoded = serde_json::to_vec(&Point::<E>::generator().to_point())
+        .map(|bytes| bytes.len())
+        .unwrap_or(0);
+    FIELD_OVERHEAD + 2 * encoded
+}
+
+/// Bound on the encoding of a scalar of `E`, as a field.
+fn scalar<E: Curve>() -> usize {
+    let largest = Scalar::<E>::from(&(crate::curve_order::<E>() - BigInt::one()));
+    let encoded = serde_json::to_vec(&largest)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0);
+    FIELD_OVERHEAD + 2 * encoded
+}
+
+/// Bound on the encoding of a Paillier encryption key with a modulus of `bits` bits.
+fn encryption_key(bits: usize) -> usize {
+    FIELD_OVERHEAD + bigint(bits) + bigint(2 * bits)
+}
+
+/// Bound on the encoding of the optional freshness stamp.
+fn freshness() -> usize {
+    FIELD_OVERHEAD + integer() + bigint(NONCE_BITS)
+}
+
+/// Bound on the encoding of the ring-Pedersen statement and proof, whose modulus has `bits`
+/// bits.
+fn ring_pedersen<const M: usize>(bits: usize) -> usize {
+    let statement = FIELD_OVERHEAD + 4 * bigint(bits) + encryption_key(bits);
+    let proof = FIELD_OVERHEAD + 2 * (FIELD_OVERHEAD + M * bigint(bits));
+    statement + proof
+}
+
+/// Bound on the encoding of the proof of a Paillier key with a modulus of `bits` bits.
+fn correct_key_proof(bits: usize) -> usize {
+    FIELD_OVERHEAD + FIELD_OVERHEAD + CORRECT_KEY_PROOF_LEN * bigint(bits)
+}
+
+/// Bound on the encoding of a dlog statement with a modulus of `bits` bits.
+fn dlog_statement(bits: usize) -> usize {
+    FIELD_OVERHEAD + 3 * bigint(bits)
+}
+
+/// The modulus size the bounds are computed for: the ring-Pedersen and dlog moduli always have
+/// [PAILLIER_KEY_SIZE](crate::PAILLIER_KEY_SIZE) bits.
+fn modulus_bits_bound(modulus_bits: usize) -> usize {
+    modulus_bits.max(crate::PAILLIER_KEY_SIZE)
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> JoinMessage<E, H, M> {
+    /// Upper bound on the length of [JoinMessage::to_bytes] for a message whose Paillier modulus
+    /// has `modulus_bits` bits, see the [module level](self) documentation.
+    pub fn serialized_size_hint(modulus_bits: usize) -> usize {
+        let bits = modulus_bits_bound(modulus_bits);
+        let response_bits = bits + RESPONSE_SLACK_BITS + 8 * H::output_size();
+        let composite_dlog_proof = FIELD_OVERHEAD + bigint(bits) + bigint(response_bits);
+
+        2 + integer()
+            + encryption_key(bits)
+            + correct_key_proof(bits)
+            + integer()
+            + dlog_statement(bits)
+            + 2 * composite_dlog_proof
+            + ring_pedersen::<M>(bits)
+            + freshness()
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Upper bound on the length of the encoding of a refresh message to a committee of `n`
+    /// parties whose Paillier moduli have `modulus_bits` bits, see the [module level](self)
+    /// documentation.
+    pub fn serialized_size_hint(modulus_bits: usize, n: u16) -> usize {
+        let n = n as usize;
+        let bits = modulus_bits_bound(modulus_bits);
+        let hash_bits = 8 * H::output_size();
+        let response_bits = bits + RESPONSE_SLACK_BITS + hash_bits;
+
+        let pdl_proof = FIELD_OVERHEAD
+            + bigint(bits)
+            + point::<E>()
+            + bigint(2 * bits)
+            + bigint(bits)
+            + bigint(response_bits)
+            + bigint(bits)
+            + bigint(response_bits);
+        let range_proof = FIELD_OVERHEAD
+            + bigint(bits)
+            + bigint(hash_bits)
+            + bigint(bits)
+            + 2 * bigint(response_bits);
+        // the polynomial has at most n coefficients, its commitments are followed by a dlog
+        // proof of two points and a scalar
+        let coefficients_committed = FIELD_OVERHEAD
+            + 3 * integer()
+            + FIELD_OVERHEAD
+            + n * point::<E>()
+            + FIELD_OVERHEAD
+            + 2 * point::<E>()
+            + scalar::<E>()
+            + integer();
+
+        2 + 2 * integer()
+            + FIELD_OVERHEAD
+            + n * pdl_proof
+            + FIELD_OVERHEAD
+            + n * range_proof
+            + coefficients_committed
+            + FIELD_OVERHEAD
+            + n * point::<E>()
+            + FIELD_OVERHEAD
+            + n * bigint(2 * bits)
+            + correct_key_proof(bits)
+            + dlog_statement(bits)
+            + encryption_key(bits)
+            + FIELD_OVERHEAD
+            + n * integer()
+            + point::<E>()
+            + ring_pedersen::<M>(bits)
+            + integer()
+            + freshness()
+            + integer()
+    }
+}
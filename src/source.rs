@@ -0,0 +1,167 @@
+//! Collect pulling the refresh messages from a callback instead of a slice.
+//!
+//! An event driven party does not have to buffer the messages of a round before it collects:
+//! [RefreshMessage::collect_with_source] pulls them one at a time from a [MessageSource] and
+//! verifies every message as it arrives, so that an invalid message fails the collect before the
+//! rest of the round was received. A message delivered again by the source, byte for byte, is
+//! ignored, while another message from a party that was already heard from fails the collect
+//! with [FsDkrError::DuplicatePartyIndex]. A source may deliver every message of the round at most
+//! [MAX_REDELIVERIES] more times in total, beyond that the collect fails with
+//! [FsDkrError::MessageSourceFlooded] instead of pulling duplicates forever.
+//!
+//! The collect installs the new Paillier key of every sender into the key, a quorum of `t + 1`
+//! messages is enough for the new share but not for the keys of the other parties. The source is
+//! therefore pulled until it delivered a message from every existing party of the new committee,
+//! i.e. one per slot of the committee that is not taken by a joining party, and no further.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::paillier_backend::KzenPaillier;
+use crate::refresh_message::{MessageProofs, RefreshMessage, ShareDecryption};
+use crate::strictness::Strictness;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use std::collections::HashSet;
+
+/// How many times, on average over the messages of a round, a [MessageSource] may deliver a
+/// message again before [RefreshMessage::collect_with_source] gives up on it.
+pub const MAX_REDELIVERIES: usize = 2;
+
+/// Supplies the refresh messages of a round on demand, see the [module level](self)
+/// documentation. Every iterator over refresh messages is a source.
+pub trait MessageSource<E: Curve, H: Digest + Clone, const M: usize> {
+    /// The next refresh message of the round, `None` once no further message will arrive.
+    fn next_message(&mut self) -> Option<RefreshMessage<E, H, M>>;
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize, I> MessageSource<E, H, M> for I
+where
+    I: Iterator<Item = RefreshMessage<E, H, M>>,
+{
+    fn next_message(&mut self) -> Option<RefreshMessage<E, H, M>> {
+        self.next()
+    }
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Same as [RefreshMessage::collect], pulling the refresh messages from `source` and
+    /// verifying each of them as it arrives, see the [module level](self) documentation.
+    /// `local_key` is the key after [RefreshMessage::distribute] or [RefreshMessage::replace],
+    /// its committee keys determine the size of the new committee. Fails with
+    /// [FsDkrError::MessageSourceExhausted] if the source runs dry before every existing party
+    /// was heard from, and with [FsDkrError::MessageSourceFlooded] if it delivers too many
+    /// duplicates.
+    pub fn collect_with_source(
+        source: &mut dyn MessageSource<E, H, M>,
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<()> {
+        let new_n = local_key.paillier_key_vec.len();
+        crate::validate_committee_size(new_n)?;
+        let expected = new_n.saturating_sub(join_messages.len());
+
+        let mut senders = HashSet::new();
+        for join_message in join_messages.iter() {
+            let party_index = join_message.get_party_index_in_committee(new_n as u16)?;
+            if !senders.insert(party_index) {
+                return Err(FsDkrError::DuplicatePartyIndex { party_index });
+            }
+            join_message.verify_ring_pedersen()?;
+            join_message.verify_paillier_key_correctness()?;
+            join_message.verify_paillier_key_size()?;
+            join_message.verify_dlog_proofs()?;
+        }
+
+        let duplicate_limit = expected * MAX_REDELIVERIES;
+        let mut duplicates = 0;
+        let mut refresh_messages: Vec<Self> = Vec::with_capacity(expected);
+        while refresh_messages.len() < expected {
+            let refresh_message = match source.next_message() {
+                Some(refresh_message) => refresh_message,
+                None => {
+                    return Err(FsDkrError::MessageSourceExhausted {
+                        received: refresh_messages.len(),
+                        expected,
+                    })
+                }
+            };
+            if let Some(received) = refresh_messages
+                .iter()
+                .find(|received| received.party_index == refresh_message.party_index)
+            {
+                if encode(received)? == encode(&refresh_message)? {
+                    duplicates += 1;
+                    if duplicates > duplicate_limit {
+                        return Err(FsDkrError::MessageSourceFlooded {
+                            limit: duplicate_limit,
+                        });
+                    }
+                    continue;
+                }
+                return Err(FsDkrError::DuplicatePartyIndex {
+                    party_index: refresh_message.party_index,
+                });
+            }
+            if !senders.insert(refresh_message.party_index) {
+                return Err(FsDkrError::DuplicatePartyIndex {
+                    party_index: refresh_message.party_index,
+                });
+            }
+            refresh_message.verify_arriving(refresh_messages.len(), local_key, new_n)?;
+            refresh_messages.push(refresh_message);
+        }
+
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            &refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Key,
+            MessageProofs::Verified,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+
+    /// The checks of a collect on a single message, run as the message arrives:
+    /// `refresh_message_index` is its position in the round, `new_n` the size of the new
+    /// committee.
+    fn verify_arriving(
+        &self,
+        refresh_message_index: usize,
+        local_key: &LocalKey<E>,
+        new_n: usize,
+    ) -> FsDkrResult<()> {
+        if self.party_index == 0 || self.party_index as usize > new_n {
+            return Err(FsDkrError::PartyIndexOutOfRange {
+                index: self.party_index,
+                n: new_n as u16,
+            });
+        }
+        self.validate_lengths(refresh_message_index, new_n)?;
+        if self.public_key != local_key.y_sum_s {
+            return Err(FsDkrError::BroadcastedPublicKeyError);
+        }
+        self.validate_curve_points()?;
+        self.validate_public_shares(new_n as u16)?;
+        self.verify_share_proofs(
+            &local_key.paillier_key_vec,
+            &local_key.h1_h2_n_tilde_vec,
+            new_n,
+        )?;
+        self.verify_ring_pedersen()?;
+        self.verify_paillier_key()
+    }
+}
+
+fn encode<E: Curve, H: Digest + Clone, const M: usize>(
+    refresh_message: &RefreshMessage<E, H, M>,
+) -> FsDkrResult<Vec<u8>> {
+    serde_json::to_vec(refresh_message).map_err(|e| FsDkrError::SerializationError {
+        reason: e.to_string(),
+    })
+}
@@ -0,0 +1,218 @@
+//! Splitting a committee into two child committees holding independent sharings of its key.
+//!
+//! A split runs two independent refreshes of the committee of a [LocalKey], one per child
+//! committee, each of them a `t` out of `n` committee reconstructing to the parent's `y_sum_s`.
+//! The re-sharing polynomials of the two rounds are independent, so the shares of one child can
+//! not be combined with the shares of the other, and every party of a child holds a Paillier key
+//! of its own, drawn for that child only.
+//!
+//! Any quorum of at least `t + 1` parties of the parent can run the split. Every party outside
+//! the quorum first broadcasts one join message per child with [join_split], taking a fresh
+//! Paillier key in each child at its own index. The parties of the quorum then distribute to both
+//! children with [split_committee], re-sharing their shares to the children and encrypting them
+//! to the keys of the join messages of each child. Each [ChildDistribution] and [ChildJoin] is
+//! collected with the messages of its child only. The refresh messages of the two children must
+//! not be mixed: each message carries the child committee it was produced for, `0` or `1`, and a
+//! message of the other child is rejected with [FsDkrError::SplitChildMismatch].
+//!
+//! Inside a child the parties keep the indices `1..=n` of the parent, as a [LocalKey] indexes
+//! its committee from 1. Across the split the two children occupy disjoint ranges of split
+//! indices, see [child_indices]: the party at index `i` of child `c` has split index `c * n + i`,
+//! so that every party of both children can be addressed on its own.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::RefreshMessage;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::DecryptionKey;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+/// The split indices of the `n` parties of child committee `child`, see the
+/// [module level](self) documentation. The ranges of the two children are disjoint.
+pub fn child_indices(child: u8, n: u16) -> RangeInclusive<u16> {
+    let offset = child as u16 * n;
+    offset + 1..=offset + n
+}
+
+/// The state of one party of the quorum in one child committee of a split, between distribute
+/// and collect. It holds the new Paillier decryption key and must be kept secret.
+pub struct ChildDistribution<E: Curve, H: Digest + Clone, const M: usize> {
+    child: u8,
+    local_key: LocalKey<E>,
+    new_dk: DecryptionKey,
+    refresh_message: RefreshMessage<E, H, M>,
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> ChildDistribution<E, H, M> {
+    /// The message to broadcast to the child committee.
+    pub fn refresh_message(&self) -> &RefreshMessage<E, H, M> {
+        &self.refresh_message
+    }
+
+    /// The child committee of the distribution, `0` or `1`.
+    pub fn child(&self) -> u8 {
+        self.child
+    }
+
+    /// The split index of the party in its child committee, see [child_indices].
+    pub fn split_index(&self) -> u16 {
+        self.child as u16 * self.local_key.n + self.local_key.i
+    }
+
+    /// Collects the refresh messages of the child committee with [RefreshMessage::collect] and
+    /// returns the party's key in the child committee. `join_messages` are the join messages of
+    /// the child the distribution was made with. Fails with [FsDkrError::SplitChildMismatch] if
+    /// a refresh message was not produced for this child.
+    pub fn collect(
+        mut self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<LocalKey<E>> {
+        validate_child(self.child, refresh_messages)?;
+        RefreshMessage::collect(
+            refresh_messages,
+            &mut self.local_key,
+            self.new_dk,
+            join_messages,
+        )?;
+        Ok(self.local_key)
+    }
+}
+
+/// The state of one party outside the quorum in one child committee of a split, between its
+/// join message and collect. It holds the Paillier keys of the party in the child and must be
+/// kept secret.
+pub struct ChildJoin<E: Curve, H: Digest + Clone, const M: usize> {
+    child: u8,
+    party_index: u16,
+    t: u16,
+    n: u16,
+    join_message: JoinMessage<E, H, M>,
+    keys: Keys,
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> ChildJoin<E, H, M> {
+    /// The message to broadcast to the quorum and the child committee.
+    pub fn join_message(&self) -> &JoinMessage<E, H, M> {
+        &self.join_message
+    }
+
+    /// The child committee of the join message, `0` or `1`.
+    pub fn child(&self) -> u8 {
+        self.child
+    }
+
+    /// The split index of the party in its child committee, see [child_indices].
+    pub fn split_index(&self) -> u16 {
+        self.child as u16 * self.n + self.party_index
+    }
+
+    /// Collects the refresh messages of the child committee with [JoinMessage::collect] and
+    /// returns the party's key in the child committee. `join_messages` are all the join messages
+    /// of the child, this one included. Fails with [FsDkrError::SplitChildMismatch] if a refresh
+    /// message was not produced for this child.
+    pub fn collect(
+        self,
+        refresh_messages: &[RefreshMessage<E, H, M>],
+        join_messages: &[JoinMessage<E, H, M>],
+    ) -> FsDkrResult<LocalKey<E>> {
+        validate_child(self.child, refresh_messages)?;
+        self.join_message.collect(
+            refresh_messages,
+            self.keys,
+            join_messages,
+            Threshold(self.t),
+            ShareCount(self.n),
+        )
+    }
+}
+
+/// Join phase of a split for a party of the committee of `local_key` that is not part of the
+/// quorum, see the [module level](self) documentation. Returns one join message per child
+/// committee, at the party's index, each with a fresh Paillier key.
+pub fn join_split<E: Curve, H: Digest + Clone, const M: usize>(
+    local_key: &LocalKey<E>,
+) -> FsDkrResult<[ChildJoin<E, H, M>; 2]> {
+    let join = |child: u8| -> FsDkrResult<ChildJoin<E, H, M>> {
+        let (mut join_message, keys) = JoinMessage::distribute()?;
+        join_message.set_party_index(local_key.i);
+        Ok(ChildJoin {
+            child,
+            party_index: local_key.i,
+            t: local_key.t,
+            n: local_key.n,
+            join_message,
+            keys,
+        })
+    };
+    Ok([join(0)?, join(1)?])
+}
+
+/// Distribute phase of a split of the committee of `local_key` at `epoch` for a party of the
+/// quorum, see the [module level](self) documentation. `join_messages` holds, for each child,
+/// the join messages of the parties outside the quorum, the parties at the other indices form
+/// the quorum. Returns the distributions for the two child committees. Fails with
+/// [FsDkrError::DuplicatePartyIndex] if a join message was made at the party's own index.
+pub fn split_committee<E: Curve, H: Digest + Clone, const M: usize>(
+    local_key: &LocalKey<E>,
+    join_messages: [&[JoinMessage<E, H, M>]; 2],
+    epoch: u64,
+) -> FsDkrResult<[ChildDistribution<E, H, M>; 2]> {
+    let distribute = |child: u8| -> FsDkrResult<ChildDistribution<E, H, M>> {
+        let join_messages = join_messages[child as usize];
+        let mut joining = Vec::with_capacity(join_messages.len());
+        for join_message in join_messages.iter() {
+            joining.push(join_message.get_party_index()?);
+        }
+        if joining.contains(&local_key.i) {
+            return Err(FsDkrError::DuplicatePartyIndex {
+                party_index: local_key.i,
+            });
+        }
+        // the parties of the quorum keep their index in the child
+        let old_to_new_map: HashMap<u16, u16> = (1..=local_key.n)
+            .filter(|party_index| !joining.contains(party_index))
+            .map(|party_index| (party_index, party_index))
+            .collect();
+
+        let mut local_key = local_key.clone();
+        let n = local_key.n;
+        let (mut refresh_message, new_dk) = RefreshMessage::replace_with_epoch(
+            join_messages,
+            &mut local_key,
+            &old_to_new_map,
+            n,
+            epoch,
+        )?;
+        refresh_message.split_child = Some(child);
+        Ok(ChildDistribution {
+            child,
+            local_key,
+            new_dk,
+            refresh_message,
+        })
+    };
+    Ok([distribute(0)?, distribute(1)?])
+}
+
+// checks that every refresh message was produced for `child`
+fn validate_child<E: Curve, H: Digest + Clone, const M: usize>(
+    child: u8,
+    refresh_messages: &[RefreshMessage<E, H, M>],
+) -> FsDkrResult<()> {
+    for refresh_message in refresh_messages.iter() {
+        if refresh_message.split_child != Some(child) {
+            return Err(FsDkrError::SplitChildMismatch {
+                party_index: refresh_message.party_index,
+                expected: child,
+                found: refresh_message.split_child,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! Verification strictness levels used by the collect phase.
+
+use serde::{Deserialize, Serialize};
+
+/// Selects how much of the received material is verified during collect. The variants are
+/// ordered from the cheapest to the most thorough, every level includes the checks of the
+/// previous one.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Strictness {
+    /// Only checks the structure of the messages: the number of messages against the threshold
+    /// and consistent vector lengths. No proof is verified.
+    Structural,
+    /// Verifies all the proofs, and that the committed points lie on the committed polynomials.
+    #[default]
+    Standard,
+    /// Additionally checks the degree of every committed polynomial, that every existing party
+    /// re-shared the share it is known to hold, and that the decrypted share matches its public
+    /// commitment.
+    Paranoid,
+}
+
+impl Strictness {
+    pub(crate) fn verifies_proofs(self) -> bool {
+        self >= Strictness::Standard
+    }
+
+    pub(crate) fn is_paranoid(self) -> bool {
+        self == Strictness::Paranoid
+    }
+}
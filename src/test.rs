@@ -1,12 +1,14 @@
 #[cfg(test)]
 mod tests {
-    use crate::refresh_message::RefreshMessage;
-    use curv::arithmetic::Converter;
+    use crate::refresh_message::{
+        lagrange_coefficients, MessageProofs, RefreshMessage, ShareDecryption,
+    };
+    use curv::arithmetic::{Converter, Modulo, Samplable};
     use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
         ShamirSecretSharing, VerifiableSS,
     };
     use curv::elliptic::curves::secp256_k1::Secp256k1Point;
-    use curv::elliptic::curves::Secp256k1;
+    use curv::elliptic::curves::{Point, Secp256k1};
     use curv::BigInt;
     use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::verify;
     use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
@@ -18,16 +20,62 @@ mod tests {
     };
     use sha2::Sha256;
 
-    use crate::add_party_message::JoinMessage;
-    use crate::error::FsDkrResult;
+    use crate::add_party_message::{
+        validate_committee_statements, verify_paillier_key, JoinMessage, KeyPolicy,
+        JOIN_MESSAGE_VERSION,
+    };
+    use crate::attestation::ShareAttestation;
+    use crate::audit::audit_transcript;
+    use crate::ceremony::{CeremonyPhase, CeremonyState};
+    use crate::ceremony_log::{CeremonyLog, CEREMONY_LOG_VERSION};
+    use crate::chain::{verify_refresh_chain, RefreshTranscript};
+    use crate::compact::{from_compact_bytes, CompressLocalKey};
+    use crate::decryption::DecryptionContext;
+    use crate::delta::{apply_delta, DiffLocalKey, LocalKeyDelta};
+    use crate::diagnose::{diagnose_local_key, Diagnostic};
+    use crate::dlog_pool::DLogStatementPool;
+    use crate::error::{FsDkrError, FsDkrResult};
+    use crate::estimate::{estimate_work, WorkEstimate};
+    use crate::events::RefreshEvent;
+    use crate::external::{ProofItem, ProofVerifier};
+    use crate::freshness::{Freshness, FreshnessGuard};
+    use crate::local_key_ext::{
+        refresh_changed_material, verify_signature, FsDkrLocalKeyExt, VerificationBundle,
+        ViabilityReport,
+    };
+    use crate::monitor::HealthMonitor;
+    use crate::paillier_backend::{KzenPaillier, PaillierBackend};
+    use crate::params::{ShareCount, Threshold};
+    use crate::party_index::{derive_party_index, derive_party_indices};
+    use crate::point_to_point::EncryptedShare;
+    use crate::progress::{ProofKind, VerificationEvent};
+    use crate::proxy::ContributionCache;
+    use crate::relay::verify_relay_integrity;
+    use crate::reshuffle::{
+        free_slots, required_online_for_refresh, required_online_for_reshuffle,
+        verify_removed_shares_revoked, RefreshPlan, Reshuffle,
+    };
+    use crate::signed_message::SignedJoinMessage;
+    use crate::sim::{sign_with_quorum, Delivery, Network};
+    use crate::split::{child_indices, join_split, split_committee};
+    use crate::strictness::Strictness;
+    use crate::threshold_decryption::{
+        combine_partial_decryptions, share_decryption_key, PartialDecryption,
+    };
     use curv::{
         elliptic::curves::Scalar,
         cryptographic_primitives::{
             hashing::Digest, proofs::sigma_dlog::DLogProof}
     };
-    use paillier::DecryptionKey;
+    use paillier::{
+        Decrypt, DecryptionKey, Encrypt, EncryptWithChosenRandomness, EncryptionKey, KeyGeneration,
+        Paillier, Randomness, RawCiphertext, RawPlaintext,
+    };
+    use rand_core::{CryptoRng, RngCore};
     use round_based::dev::Simulation;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
+    use std::num::NonZeroU32;
+    use zk_paillier::zkproofs::NiCorrectKeyProof;
 
     type GE = Secp256k1Point;
 
@@ -66,6 +114,4038 @@ mod tests {
         assert_ne!(old_linear_secret_key, new_linear_secret_key);
     }
 
+    // Conformance vectors for the refresh of the FS-DKR paper, worked out by hand on a 1 out of 3
+    // committee sharing the secret 2 over f(x) = 2 + 3x, i.e. with the shares 5, 8 and 11. Parties
+    // 1 and 2 form the quorum and re-share their shares over g_1(x) = 5 + 7x and g_2(x) = 8 + x.
+    // With the lagrange coefficients 2 and -1 of the quorum, the new shares lie on
+    // 2 * g_1(x) - g_2(x) = 2 + 13x, i.e. they are 15, 28 and 41.
+    fn conformance_vss(t: u16, n: u16) -> VerifiableSS<Secp256k1, sha2::Sha256> {
+        VerifiableSS::<Secp256k1, sha2::Sha256> {
+            parameters: ShamirSecretSharing {
+                threshold: t,
+                share_count: n,
+            },
+            commitments: Vec::new(),
+            proof: DLogProof::<Secp256k1, sha2::Sha256>::prove(&Scalar::random()),
+        }
+    }
+
+    fn scalar(value: u64) -> Scalar<Secp256k1> {
+        Scalar::from_bigint(&BigInt::from(value))
+    }
+
+    fn point_from_hex(compressed: &str) -> BigInt {
+        BigInt::from_hex(compressed).unwrap()
+    }
+
+    // The Paillier key every slot of the vectors is encrypted to, the product of the two largest
+    // primes below 2^1024: 2^1024 - 105 and 2^1024 - 179.
+    fn conformance_paillier_key() -> (EncryptionKey, DecryptionKey) {
+        let base = BigInt::from(1) << 1024;
+        let dk = DecryptionKey {
+            p: &base - BigInt::from(105),
+            q: &base - BigInt::from(179),
+        };
+        let n = &dk.p * &dk.q;
+        let ek = EncryptionKey { nn: &n * &n, n };
+        (ek, dk)
+    }
+
+    fn conformance_encrypt(ek: &EncryptionKey, plaintext: u64, randomness: u64) -> BigInt {
+        Paillier::encrypt_with_chosen_randomness(
+            ek,
+            RawPlaintext::from(BigInt::from(plaintext)),
+            &Randomness::from(BigInt::from(randomness)),
+        )
+        .0
+        .into_owned()
+    }
+
+    // Refresh messages of a 1 out of 3 committee carrying the vectors above in place of their
+    // random polynomials: the first two parties commit to g_1 and g_2 and encrypt g_k(j) to the
+    // pinned key for every slot j, with the fixed randomness 5 and 7. The message of the third
+    // party is outside of the quorum and left as distributed. Every key holds the pinned key in
+    // every slot, so that each party decrypts its own slot.
+    fn conformance_refresh() -> (
+        Vec<LocalKey<Secp256k1>>,
+        Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+        Vec<DecryptionKey>,
+    ) {
+        let (ek, dk) = conformance_paillier_key();
+        let mut keys = simulate_keygen(1, 3);
+        let mut messages = Vec::new();
+        let mut new_dks = Vec::new();
+        for key in keys.iter_mut() {
+            let (message, new_dk) = RefreshMessage::distribute(key.i, key, 3).unwrap();
+            messages.push(message);
+            new_dks.push(new_dk);
+        }
+
+        let polynomials = [([5u64, 7], 5u64), ([8, 1], 7)];
+        for (message, (coefficients, randomness)) in messages.iter_mut().zip(polynomials.iter()) {
+            message.coefficients_committed_vec.commitments = coefficients
+                .iter()
+                .map(|coefficient| Point::generator() * scalar(*coefficient))
+                .collect();
+            message.points_committed_vec = (1..=3)
+                .map(|x| Point::generator() * scalar(coefficients[0] + coefficients[1] * x))
+                .collect();
+            message.points_encrypted_vec = (1..=3)
+                .map(|x| {
+                    conformance_encrypt(&ek, coefficients[0] + coefficients[1] * x, *randomness)
+                })
+                .collect();
+        }
+
+        for key in keys.iter_mut() {
+            key.paillier_dk = dk.clone();
+            key.paillier_key_vec = vec![ek.clone(); 3];
+        }
+        (keys, messages, new_dks)
+    }
+
+    // The messages only carry the vectors, the proofs are those of the random polynomials, so
+    // the vectors are collected without verifying the proofs.
+    #[test]
+    fn test_conformance_fixed_polynomial_vectors() {
+        assert_eq!(
+            lagrange_coefficients::<Secp256k1>(&[0, 1]),
+            vec![scalar(2), scalar(0) - scalar(1)]
+        );
+
+        let (mut keys, messages, new_dks) = conformance_refresh();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks.into_iter()) {
+            RefreshMessage::collect_with_strictness(
+                &messages,
+                key,
+                new_dk,
+                &[],
+                Strictness::Structural,
+            )
+            .unwrap();
+        }
+        let new_shares: Vec<_> = keys.iter().map(|key| key.keys_linear.x_i.clone()).collect();
+        assert_eq!(new_shares, vec![scalar(15), scalar(28), scalar(41)]);
+
+        let vss = conformance_vss(1, 3);
+        assert_eq!(vss.reconstruct(&[0, 1], &new_shares[..2]), scalar(2));
+        assert_eq!(vss.reconstruct(&[1, 2], &new_shares[1..]), scalar(2));
+
+        let expected_public_shares = [
+            "02d7924d4f7d43ea965a465ae3095ff41131e5946f3c85f79e44adbcf8e27e080e",
+            "0255eb67d7b7238a70a7fa6f64d5dc3c826b31536da6eb344dc39a66f904f97968",
+            "037a9375ad6167ad54aa74c6348cc54d344cc5dc9487d847049d5eabb0fa03c8fb",
+        ];
+        for key in keys.iter() {
+            assert_eq!(key.pk_vec.len(), expected_public_shares.len());
+            for (public_share, expected) in key.pk_vec.iter().zip(expected_public_shares.iter()) {
+                assert_eq!(
+                    BigInt::from_bytes(&public_share.to_bytes(true)),
+                    point_from_hex(expected)
+                );
+            }
+        }
+        assert_eq!(
+            BigInt::from_bytes(&(Point::<Secp256k1>::generator() * scalar(2)).to_bytes(true)),
+            point_from_hex("02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5")
+        );
+    }
+
+    #[test]
+    fn test_lagrange_coefficients_large_committee() {
+        let (t, n) = (24u16, 50u16);
+        let q = Scalar::<Secp256k1>::group_order();
+        // the upper half of the committee, where the products of the indices are largest
+        let indices: Vec<u16> = (n - t - 1..n).collect();
+        let li_vec = lagrange_coefficients::<Secp256k1>(&indices);
+
+        // the same coefficients computed over the integers, reduced once at the end
+        for (index, li) in indices.iter().zip(li_vec.iter()) {
+            let xi = BigInt::from(*index as u64 + 1);
+            let (num, den) = indices.iter().filter(|other| *other != index).fold(
+                (BigInt::from(1), BigInt::from(1)),
+                |(num, den), other| {
+                    let xj = BigInt::from(*other as u64 + 1);
+                    (num * &xj, den * (xj - &xi))
+                },
+            );
+            let expected = BigInt::mod_mul(
+                &BigInt::modulus(&num, q),
+                &BigInt::mod_inv(&BigInt::modulus(&den, q), q).unwrap(),
+                q,
+            );
+            assert_eq!(li.to_bigint(), expected);
+        }
+
+        let parameters = ShamirSecretSharing {
+            threshold: t,
+            share_count: n,
+        };
+        for (index, li) in indices.iter().zip(li_vec.iter()) {
+            assert_eq!(
+                *li,
+                VerifiableSS::<Secp256k1, sha2::Sha256>::map_share_to_new_params(
+                    &parameters,
+                    *index,
+                    &indices
+                )
+            );
+        }
+        assert_eq!(
+            li_vec
+                .iter()
+                .fold(Scalar::<Secp256k1>::zero(), |acc, li| acc + li),
+            scalar(1)
+        );
+
+        let secret = Scalar::<Secp256k1>::random();
+        let (_, shares) = VerifiableSS::<Secp256k1, sha2::Sha256>::share(t, n, &secret);
+        let reconstructed = indices
+            .iter()
+            .zip(li_vec.iter())
+            .fold(Scalar::<Secp256k1>::zero(), |acc, (index, li)| {
+                acc + &shares[*index as usize] * li
+            });
+        assert_eq!(reconstructed, secret);
+
+        // a quorum whose indices exceed the size of the new committee, as after a reshuffle
+        // removing the first party of three
+        assert_eq!(
+            lagrange_coefficients::<Secp256k1>(&[1, 2]),
+            vec![scalar(3), scalar(0) - scalar(2)]
+        );
+    }
+
+    // The decryption the second party of the vectors above performs. The ciphertexts and their
+    // homomorphic sum are pinned by their SHA-256 digests. The lagrange coefficient -1 enters the
+    // sum as q - 1, so that it decrypts to 2 * 19 + (q - 1) * 10 = 10q + 28, which is the new
+    // share 28 modulo q.
+    #[test]
+    fn test_conformance_paillier_vectors() {
+        let (ek, dk) = conformance_paillier_key();
+        crate::validate_generated_modulus(&ek.n, crate::PAILLIER_KEY_SIZE).unwrap();
+        let digest = |value: &BigInt| BigInt::from_bytes(&Sha256::digest(&value.to_bytes()));
+
+        let (mut keys, messages, new_dks) = conformance_refresh();
+        assert_eq!(
+            digest(&messages[0].points_encrypted_vec[1]),
+            BigInt::from_hex("5b962be5634faa85a8a7b7095d930ab1f051c6476ed93e52f8245a1577fc60fa")
+                .unwrap()
+        );
+        assert_eq!(
+            digest(&messages[1].points_encrypted_vec[1]),
+            BigInt::from_hex("9692673cd17227437469aa1eacfdcaba8f4cabfedc1dec3da288cc14fefae5bd")
+                .unwrap()
+        );
+
+        let quorum = RefreshMessage::quorum(&messages, 1).unwrap();
+        let parameters = ShamirSecretSharing {
+            threshold: 1,
+            share_count: 3,
+        };
+        let (ciphertext_sum, li_vec, _) =
+            RefreshMessage::get_ciphertext_sum::<KzenPaillier>(&quorum, 2, &parameters, &ek);
+        let ciphertext_sum = ciphertext_sum.0.into_owned();
+        assert_eq!(li_vec, vec![scalar(2), scalar(0) - scalar(1)]);
+        assert_eq!(
+            digest(&ciphertext_sum),
+            BigInt::from_hex("94915b036551dba60554072c8f0efa8502b340be10dc6c108569890e6ea1d62c")
+                .unwrap()
+        );
+        assert_eq!(
+            RefreshMessage::ciphertext_sum(&messages, 2, 1, &ek).unwrap(),
+            ciphertext_sum
+        );
+
+        let context = DecryptionContext::new(&dk).unwrap();
+        assert_eq!(
+            context.decrypt_verified(&ek, &ciphertext_sum, 2).unwrap(),
+            BigInt::from(10) * Scalar::<Secp256k1>::group_order() + BigInt::from(28)
+        );
+
+        let collected_sum = RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            &messages,
+            &mut keys[1],
+            new_dks[1].clone(),
+            &[],
+            Strictness::Structural,
+            ShareDecryption::Key,
+            MessageProofs::All,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(collected_sum, ciphertext_sum);
+        assert_eq!(keys[1].keys_linear.x_i, scalar(28));
+    }
+
+    // Completes the vectors above for randomly generated keys: the shares collected by a refresh
+    // reconstruct the secret of the public key.
+    #[test]
+    fn test_conformance_refresh_reconstructs_public_key() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+        let vss = conformance_vss(t, n);
+        let shares: Vec<_> = keys.iter().map(|key| key.keys_linear.x_i.clone()).collect();
+        for quorum in [[0u16, 1], [0, 2], [1, 2]].iter() {
+            let quorum_shares: Vec<_> = quorum
+                .iter()
+                .map(|index| shares[*index as usize].clone())
+                .collect();
+            let secret = vss.reconstruct(quorum, &quorum_shares);
+            assert_eq!(Point::<Secp256k1>::generator() * secret, keys[0].y_sum_s);
+        }
+    }
+
+    #[test]
+    fn test_refresh_keeps_group() {
+        let mut keys = simulate_keygen(2, 5);
+        let old_keys = keys.clone();
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+        assert!(keys
+            .iter()
+            .zip(old_keys.iter())
+            .all(|(key, old_key)| key.same_group_as(old_key)));
+
+        let other_group = simulate_keygen(2, 5);
+        assert!(!keys[0].same_group_as(&other_group[0]));
+    }
+
+    #[test]
+    fn test_local_key_introspection() {
+        let mut keys = simulate_keygen(2, 5);
+        let old_keys = keys.clone();
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+        for (key, old_key) in keys.iter().zip(old_keys.iter()) {
+            assert_eq!(key.public_key(), old_key.public_key());
+            assert_ne!(key.share_scalar(), old_key.share_scalar());
+            assert_eq!(
+                Point::generator() * key.share_scalar(),
+                key.pk_vec[(key.i - 1) as usize]
+            );
+            assert_eq!(key.committee_size(), 5);
+            assert_eq!(key.threshold(), 2);
+            assert_eq!(key.shamir_params().threshold, 2);
+            assert_eq!(key.shamir_params().share_count, 5);
+        }
+
+        let mut vss = conformance_vss(0, 0);
+        vss.parameters = keys[0].shamir_params();
+        let indices: Vec<u16> = (0..3).collect();
+        let shares: Vec<_> = keys[..3]
+            .iter()
+            .map(|key| key.share_scalar().clone())
+            .collect();
+        assert_eq!(
+            Point::generator() * vss.reconstruct(&indices, &shares),
+            keys[0].y_sum_s
+        );
+    }
+
+    #[test]
+    fn test_aggregate_modulus_product() {
+        let mut key = Network::<{ crate::M_SECURITY }>::run_refresh(1, 3, &[])
+            .unwrap()
+            .remove(0);
+        let product = key
+            .paillier_key_vec
+            .iter()
+            .fold(BigInt::from(1), |product, ek| product * &ek.n);
+        assert_eq!(key.aggregate_modulus_product(), product);
+
+        let skipped = key.paillier_key_vec[1].n.clone();
+        key.paillier_key_vec[1] = EncryptionKey {
+            n: BigInt::from(0),
+            nn: BigInt::from(0),
+        };
+        assert_eq!(key.aggregate_modulus_product() * skipped, product);
+    }
+
+    #[test]
+    fn test_signing_key_indices() {
+        let mut key = Network::<{ crate::M_SECURITY }>::run_refresh(1, 3, &[])
+            .unwrap()
+            .remove(0);
+        assert_eq!(key.signing_key_indices(&[3, 1]).unwrap(), vec![2, 0]);
+        assert!(matches!(
+            key.signing_key_indices(&[1, 4]),
+            Err(FsDkrError::PartyIndexOutOfRange { index: 4, n: 3 })
+        ));
+        assert!(matches!(
+            key.signing_key_indices(&[2, 2]),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 2 })
+        ));
+
+        key.paillier_key_vec[1] = EncryptionKey {
+            n: BigInt::from(0),
+            nn: BigInt::from(0),
+        };
+        assert!(matches!(
+            key.signing_key_indices(&[1, 2]),
+            Err(FsDkrError::PlaceholderSlot { party_index: 2 })
+        ));
+        assert_eq!(key.signing_key_indices(&[1, 3]).unwrap(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_signing_viability() {
+        let mut key = Network::<{ crate::M_SECURITY }>::run_refresh(1, 3, &[])
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            key.signing_viability(),
+            ViabilityReport {
+                active_slots: 3,
+                threshold: 1,
+                my_slot_active: true,
+            }
+        );
+        assert!(key.is_signing_viable());
+
+        let placeholder = EncryptionKey {
+            n: BigInt::from(0),
+            nn: BigInt::from(0),
+        };
+        let mut shrunk = key.clone();
+        shrunk.paillier_key_vec[1] = placeholder.clone();
+        shrunk.paillier_key_vec[2] = placeholder.clone();
+        assert_eq!(shrunk.signing_viability().active_slots, 1);
+        assert!(!shrunk.is_signing_viable());
+
+        key.paillier_key_vec[0] = placeholder;
+        assert_eq!(
+            key.signing_viability(),
+            ViabilityReport {
+                active_slots: 2,
+                threshold: 1,
+                my_slot_active: false,
+            }
+        );
+        assert!(!key.is_signing_viable());
+    }
+
+    #[test]
+    fn test_diagnose_local_key() {
+        let keys = Network::<{ crate::M_SECURITY }>::run_refresh(2, 5, &[2]).unwrap();
+        for key in keys.iter() {
+            assert_eq!(diagnose_local_key(key), Vec::new());
+        }
+
+        let mut inconsistent = keys[0].clone();
+        inconsistent.keys_linear.x_i = Scalar::random();
+        inconsistent.pk_vec[3] = inconsistent.pk_vec[4].clone();
+        inconsistent.vss_scheme.parameters.threshold = 3;
+        assert_eq!(
+            diagnose_local_key(&inconsistent),
+            vec![
+                Diagnostic::ShareScalarMismatch,
+                Diagnostic::PublicShareOffPolynomial { party_index: 4 },
+                Diagnostic::VssParametersMismatch {
+                    threshold: 3,
+                    share_count: 5
+                },
+            ]
+        );
+
+        let placeholder = EncryptionKey {
+            n: BigInt::from(0),
+            nn: BigInt::from(0),
+        };
+        let mut placeholders = keys[0].clone();
+        placeholders.paillier_key_vec[1] = placeholder.clone();
+        placeholders.paillier_key_vec[2] = placeholder.clone();
+        placeholders.paillier_key_vec[4] = placeholder;
+        placeholders.pk_vec[0] = placeholders.pk_vec[1].clone();
+        let diagnostics = diagnose_local_key(&placeholders);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::OwnPublicShareMismatch,
+                Diagnostic::PlaceholderSlot { party_index: 2 },
+                Diagnostic::PlaceholderSlot { party_index: 3 },
+                Diagnostic::PlaceholderSlot { party_index: 5 },
+                Diagnostic::QuorumUnavailable {
+                    active_slots: 2,
+                    threshold: 2
+                },
+            ]
+        );
+        assert_eq!(
+            diagnostics[4].to_string(),
+            "only 2 slots hold a key, a quorum needs 3"
+        );
+    }
+
+    #[test]
+    fn test_verification_bundle() {
+        let mut keys = simulate_keygen(2, 5);
+        let bundle = keys[0].verification_bundle();
+        assert_eq!(bundle.public_key, keys[0].public_key_compressed());
+        assert_eq!((bundle.t, bundle.n), (2, 5));
+        assert_eq!(bundle.vss_commitments_hash.len(), 32);
+        assert_eq!(keys[0].verification_bundle(), bundle);
+
+        let encoded = serde_json::to_string(&bundle).unwrap();
+        let decoded: VerificationBundle = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded, bundle);
+        let secret = serde_json::to_string(&keys[0].keys_linear.x_i).unwrap();
+        assert!(!encoded.contains(&secret));
+        let paillier_p = serde_json::to_string(&keys[0].paillier_dk.p).unwrap();
+        assert!(!encoded.contains(&paillier_p));
+
+        // a refresh keeps what the verifier relies on and re-shares over a new polynomial
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+        let refreshed = keys[0].verification_bundle();
+        assert_eq!(refreshed.public_key, bundle.public_key);
+        assert_eq!((refreshed.t, refreshed.n), (bundle.t, bundle.n));
+        assert_ne!(refreshed.vss_commitments_hash, bundle.vss_commitments_hash);
+    }
+
+    #[test]
+    fn test_committee_commitment() {
+        let mut keys = simulate_keygen(2, 5);
+        let commitment = keys[0].committee_commitment();
+        assert!(keys
+            .iter()
+            .all(|key| key.committee_commitment() == commitment));
+        assert_ne!(simulate_keygen(2, 5)[0].committee_commitment(), commitment);
+
+        // the public shares are rotated by a refresh, the parties still agree
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+        let refreshed = keys[0].committee_commitment();
+        assert_ne!(refreshed, commitment);
+        assert!(keys
+            .iter()
+            .all(|key| key.committee_commitment() == refreshed));
+
+        // a party that ended up with another public share disagrees
+        let mut diverged = keys[1].clone();
+        diverged.pk_vec[3] = diverged.pk_vec[4].clone();
+        assert_ne!(diverged.committee_commitment(), refreshed);
+        let mut diverged = keys[1].clone();
+        diverged.t = 3;
+        assert_ne!(diverged.committee_commitment(), refreshed);
+    }
+
+    #[test]
+    fn test_refresh_changed_material() {
+        let mut keys = simulate_keygen(1, 3);
+        let old_keys = keys.clone();
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+        for (old_key, key) in old_keys.iter().zip(keys.iter()) {
+            assert!(refresh_changed_material(old_key, key));
+            assert!(!refresh_changed_material(old_key, old_key));
+
+            let mut same_polynomial = key.clone();
+            same_polynomial.vss_scheme = old_key.vss_scheme.clone();
+            assert!(!refresh_changed_material(old_key, &same_polynomial));
+
+            let mut other_group = key.clone();
+            other_group.y_sum_s = &key.y_sum_s + Point::<Secp256k1>::generator().to_point();
+            assert!(!refresh_changed_material(old_key, &other_group));
+        }
+    }
+
+    #[test]
+    fn test_reencrypt_own_share_to_rotated_key() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // party 2 rotates its Paillier key after the refresh messages were distributed
+        let (mut join_message, paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(2);
+
+        let mut other_party_key = keys[1].clone();
+        other_party_key.paillier_dk = keys[0].paillier_dk.clone();
+        let mut reencrypted = refresh_messages.clone();
+        assert!(matches!(
+            reencrypted[0].reencrypt_own_share(&other_party_key, &join_message.ek),
+            Err(FsDkrError::KeyPartyMismatch { party_index: 2 })
+        ));
+        for refresh_message in reencrypted.iter_mut() {
+            refresh_message
+                .reencrypt_own_share(&keys[1], &join_message.ek)
+                .unwrap();
+        }
+
+        let mut rotated_key = keys[1].clone();
+        rotated_key
+            .update_own_encryption_key(&join_message, paillier_keys)
+            .unwrap();
+        assert!(RefreshMessage::collect(
+            &refresh_messages,
+            &mut rotated_key.clone(),
+            new_dks[1].clone(),
+            &[]
+        )
+        .is_err());
+
+        // a share of the slot that was not re-encrypted from the message is caught after decryption
+        let mut substituted = reencrypted.clone();
+        substituted[0].points_encrypted_vec[1] =
+            Paillier::encrypt(&join_message.ek, RawPlaintext::from(BigInt::from(7)))
+                .0
+                .into_owned();
+        assert!(matches!(
+            RefreshMessage::collect_reencrypted(
+                &substituted,
+                &mut rotated_key.clone(),
+                new_dks[1].clone(),
+                &[],
+            ),
+            Err(FsDkrError::PublicShareValidationError)
+        ));
+
+        RefreshMessage::collect_reencrypted(
+            &reencrypted,
+            &mut rotated_key,
+            new_dks[1].clone(),
+            &[],
+        )
+        .unwrap();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+        }
+        assert_eq!(rotated_key.keys_linear.x_i, keys[1].keys_linear.x_i);
+        assert_eq!(rotated_key.pk_vec, keys[1].pk_vec);
+        keys[1] = rotated_key;
+        sign_with_quorum(&keys, b"ZenGo").unwrap();
+    }
+
+    #[test]
+    fn test_update_party_encryption_key() {
+        let mut keys = simulate_keygen(1, 3);
+        let (mut join_message, paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(2);
+
+        assert!(matches!(
+            keys[1].update_party_encryption_key(&join_message),
+            Err(FsDkrError::OwnEncryptionKeyUpdate { party_index: 2 })
+        ));
+        keys[1]
+            .update_own_encryption_key(&join_message, paillier_keys)
+            .unwrap();
+        for i in [0, 2].iter() {
+            keys[*i].update_party_encryption_key(&join_message).unwrap();
+        }
+        for key in keys.iter() {
+            assert_eq!(key.paillier_key_vec[1], join_message.ek);
+            assert_eq!(key.h1_h2_n_tilde_vec[1].N, join_message.dlog_statement.N);
+        }
+
+        let mut forged = join_message.clone();
+        forged.dlog_statement.ni = forged.dlog_statement.g.clone();
+        assert!(keys[0].update_party_encryption_key(&forged).is_err());
+
+        // the committee refreshes and signs with the rotated key
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+        sign_with_quorum(&keys[1..], b"ZenGo").unwrap();
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let mut keys = simulate_keygen(1, 3);
+        let signature = sign_with_quorum(&keys[..2], b"ZenGo").unwrap();
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+        let refreshed_signature = sign_with_quorum(&keys[1..], b"ZenGo").unwrap();
+        for key in keys.iter() {
+            assert!(verify_signature(key, b"ZenGo", &refreshed_signature).unwrap());
+            assert!(verify_signature(key, b"ZenGo", &signature).unwrap());
+            assert!(!verify_signature(key, b"other message", &refreshed_signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_public_eq() {
+        let mut keys = simulate_keygen(1, 3);
+        let old_keys = keys.clone();
+        let key = &keys[0];
+
+        let mut other_secrets = key.clone();
+        other_secrets.keys_linear.x_i = Scalar::random();
+        other_secrets.paillier_dk = keys[1].paillier_dk.clone();
+        assert!(key.public_eq(&other_secrets));
+        assert!(!key.public_eq(&keys[1]));
+
+        let mut other_share = key.clone();
+        other_share.pk_vec[2] = Point::generator().to_point();
+        assert!(!key.public_eq(&other_share));
+
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+        assert!(!keys[0].public_eq(&old_keys[0]));
+    }
+
+    #[test]
+    fn test_compact_local_key_round_trip() {
+        let mut key = simulate_keygen(1, 3).remove(0);
+        // parties 1 and 2 share the modulus of their dlog statements
+        key.h1_h2_n_tilde_vec[1].N = key.h1_h2_n_tilde_vec[0].N.clone();
+        let encoded = serde_json::to_vec(&key).unwrap();
+
+        let compact = key.to_compact_bytes().unwrap();
+        assert!(compact.len() < encoded.len());
+        let restored: LocalKey<Secp256k1> = from_compact_bytes(&compact).unwrap();
+        assert_eq!(serde_json::to_vec(&restored).unwrap(), encoded);
+
+        let mut inconsistent = key.clone();
+        inconsistent.paillier_key_vec[0].nn = BigInt::from(1);
+        assert!(matches!(
+            inconsistent.to_compact_bytes(),
+            Err(FsDkrError::SerializationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_local_key_delta_round_trip() {
+        let mut keys = simulate_keygen(1, 3);
+        let old_keys = keys.clone();
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+        let delta = keys[0].delta_from(&old_keys[0]).unwrap();
+        assert!(!delta.is_empty());
+        let delta = LocalKeyDelta::from_bytes(&delta.to_bytes().unwrap()).unwrap();
+        let restored = apply_delta(&old_keys[0], &delta).unwrap();
+        assert_eq!(
+            serde_json::to_vec(&restored).unwrap(),
+            serde_json::to_vec(&keys[0]).unwrap()
+        );
+        assert!(matches!(
+            apply_delta(&old_keys[1], &delta),
+            Err(FsDkrError::DeltaBaseMismatch)
+        ));
+
+        // only the rotated Paillier key of party 2 is stored
+        let mut rotated = keys[0].clone();
+        rotated.paillier_key_vec[1] = old_keys[0].paillier_key_vec[1].clone();
+        let delta = rotated.delta_from(&keys[0]).unwrap();
+        let encoded = delta.to_bytes().unwrap();
+        assert!(encoded.len() < serde_json::to_vec(&rotated).unwrap().len() / 3);
+        assert_eq!(
+            serde_json::to_vec(&apply_delta(&keys[0], &delta).unwrap()).unwrap(),
+            serde_json::to_vec(&rotated).unwrap()
+        );
+        assert!(keys[0].delta_from(&keys[0]).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "sealing")]
+    #[test]
+    fn test_seal_unseal_round_trip() {
+        use crate::sealing::{unseal, SealLocalKey, SealedLocalKey};
+
+        let key = simulate_keygen(1, 3).remove(0);
+        let sealed = key.seal(b"correct horse battery staple").unwrap();
+
+        let public = SealedLocalKey::<Secp256k1>::from_bytes(&sealed)
+            .unwrap()
+            .public;
+        assert_eq!(public.y_sum_s, key.y_sum_s);
+        assert_eq!(public.i, key.i);
+        let x_i = BigInt::from_bytes(&key.keys_linear.x_i.to_bytes()).to_hex();
+        assert!(!String::from_utf8_lossy(&sealed).contains(&x_i));
+
+        let unsealed: LocalKey<Secp256k1> =
+            unseal(&sealed, b"correct horse battery staple").unwrap();
+        assert_eq!(unsealed.keys_linear.x_i, key.keys_linear.x_i);
+        assert_eq!(unsealed.paillier_dk.p, key.paillier_dk.p);
+        assert_eq!(unsealed.paillier_dk.q, key.paillier_dk.q);
+        assert_eq!(unsealed.pk_vec, key.pk_vec);
+        assert_eq!((unsealed.i, unsealed.t, unsealed.n), (key.i, key.t, key.n));
+
+        assert!(matches!(
+            unseal::<Secp256k1>(&sealed, b"wrong passphrase"),
+            Err(FsDkrError::UnsealFailed)
+        ));
+
+        // the iteration count read from the sealed key is bounded on both sides
+        for rounds in [1u32, u32::MAX].iter() {
+            let mut encoding: serde_json::Value = serde_json::from_slice(&sealed).unwrap();
+            encoding["rounds"] = serde_json::json!(rounds);
+            let tampered = serde_json::to_vec(&encoding).unwrap();
+            assert!(matches!(
+                unseal::<Secp256k1>(&tampered, b"correct horse battery staple"),
+                Err(FsDkrError::InvalidSealedKey { reason }) if reason == "rounds"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_public_key_encodings() {
+        let mut keys = simulate_keygen(2, 5);
+        let compressed = keys[0].public_key_compressed();
+        let uncompressed = keys[0].public_key_uncompressed();
+        assert_eq!(compressed.len(), 33);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(uncompressed[0], 0x04);
+        assert_eq!(compressed[1..], uncompressed[1..33]);
+
+        simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+        assert!(keys
+            .iter()
+            .all(|key| key.public_key_compressed() == compressed
+                && key.public_key_uncompressed() == uncompressed));
+    }
+
+    #[test]
+    fn test_audit_transcript() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let paillier_key_vec = keys[0].paillier_key_vec.clone();
+        let h1_h2_n_tilde_vec = keys[0].h1_h2_n_tilde_vec.clone();
+        let public_key = keys[0].y_sum_s.clone();
+
+        let mut refresh_messages: Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>> =
+            keys.iter_mut()
+                .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap().0)
+                .collect();
+
+        let report = audit_transcript(
+            &refresh_messages,
+            &[],
+            &paillier_key_vec,
+            &h1_h2_n_tilde_vec,
+            &public_key,
+            Threshold(t),
+            ShareCount(n),
+        )
+        .unwrap();
+        assert!(report.is_valid());
+
+        // a party that broadcasts a different public key is flagged, the others still pass
+        refresh_messages[1].public_key = Point::<Secp256k1>::generator().to_point();
+        let report = audit_transcript(
+            &refresh_messages,
+            &[],
+            &paillier_key_vec,
+            &h1_h2_n_tilde_vec,
+            &public_key,
+            Threshold(t),
+            ShareCount(n),
+        )
+        .unwrap();
+        let failures = report.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].party_index,
+            Some(refresh_messages[1].party_index)
+        );
+        assert!(report.quorum_failure.is_none());
+
+        // the first message is not trusted as the reference, a bad one is flagged alone
+        refresh_messages[1].public_key = public_key.clone();
+        refresh_messages[0].public_key = Point::<Secp256k1>::generator().to_point();
+        let report = audit_transcript(
+            &refresh_messages,
+            &[],
+            &paillier_key_vec,
+            &h1_h2_n_tilde_vec,
+            &public_key,
+            Threshold(t),
+            ShareCount(n),
+        )
+        .unwrap();
+        let failures = report.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].party_index,
+            Some(refresh_messages[0].party_index)
+        );
+        assert!(report.quorum_failure.is_none());
+
+        // a polynomial of the wrong degree is flagged
+        refresh_messages[0].public_key = public_key.clone();
+        refresh_messages[2]
+            .coefficients_committed_vec
+            .commitments
+            .pop();
+        let report = audit_transcript(
+            &refresh_messages,
+            &[],
+            &paillier_key_vec,
+            &h1_h2_n_tilde_vec,
+            &public_key,
+            Threshold(t),
+            ShareCount(n),
+        )
+        .unwrap();
+        let failures = report.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].party_index,
+            Some(refresh_messages[2].party_index)
+        );
+
+        // with too few valid messages left the quorum fails
+        let report = audit_transcript(
+            &refresh_messages[..3],
+            &[],
+            &paillier_key_vec,
+            &h1_h2_n_tilde_vec,
+            &public_key,
+            Threshold(t),
+            ShareCount(n),
+        )
+        .unwrap();
+        assert!(matches!(
+            report.quorum_failure,
+            Some(FsDkrError::PartiesThresholdViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_refresh_chain() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let genesis_pk = keys[0].y_sum_s.clone();
+
+        let mut transcripts: Vec<RefreshTranscript<Secp256k1, Sha256, { crate::M_SECURITY }>> =
+            Vec::new();
+        for _ in 0..2 {
+            let paillier_key_vec = keys[0].paillier_key_vec.clone();
+            let h1_h2_n_tilde_vec = keys[0].h1_h2_n_tilde_vec.clone();
+            let (refresh_messages, new_dks): (Vec<_>, Vec<DecryptionKey>) = keys
+                .iter_mut()
+                .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+                .unzip();
+            for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+                RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+            }
+            transcripts.push(RefreshTranscript {
+                t,
+                paillier_key_vec,
+                h1_h2_n_tilde_vec,
+                refresh_messages,
+                join_messages: Vec::new(),
+            });
+        }
+
+        let report = verify_refresh_chain(&genesis_pk, &transcripts).unwrap();
+        assert_eq!((report.rounds, report.t, report.n), (2, t, n));
+        assert_eq!(report.pk_vec, keys[0].pk_vec);
+        assert_eq!(report.paillier_key_vec, keys[0].paillier_key_vec);
+
+        // the second round does not encrypt to the keys installed by the first round
+        let reordered = vec![transcripts[1].clone(), transcripts[0].clone()];
+        assert!(matches!(
+            verify_refresh_chain(&genesis_pk, &reordered),
+            Err(FsDkrError::RefreshChainBroken { round: 1, .. })
+        ));
+        let other_pk = Point::<Secp256k1>::generator().to_point();
+        assert!(matches!(
+            verify_refresh_chain(&other_pk, &transcripts),
+            Err(FsDkrError::RefreshChainBroken { round: 0, .. })
+        ));
+
+        // a message without coefficient commitments breaks the round instead of underflowing
+        let mut empty_commitments = transcripts.clone();
+        for refresh_message in empty_commitments[0].refresh_messages.iter_mut() {
+            refresh_message
+                .coefficients_committed_vec
+                .commitments
+                .clear();
+        }
+        assert!(matches!(
+            verify_refresh_chain(&genesis_pk, &empty_commitments),
+            Err(FsDkrError::RefreshChainBroken { round: 0, .. })
+        ));
+        assert!(matches!(
+            verify_refresh_chain::<Secp256k1, Sha256, { crate::M_SECURITY }>(&genesis_pk, &[]),
+            Err(FsDkrError::RefreshChainBroken { round: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_collect_with_paranoid_strictness() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect_with_strictness(
+                &refresh_messages,
+                key,
+                new_dk,
+                &[],
+                Strictness::Paranoid,
+            )
+            .unwrap();
+        }
+
+        let offline_sign = simulate_offline_stage(keys, &[1, 2, 3]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    #[test]
+    fn test_collect_with_surplus_messages_in_any_order() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // every party receives all n > t + 1 messages, each in a different order
+        for (k, (key, new_dk)) in keys.iter_mut().zip(new_dks).enumerate() {
+            let mut received = refresh_messages.clone();
+            received.rotate_left(k);
+            RefreshMessage::collect(&received, key, new_dk, &[]).unwrap();
+        }
+
+        for key in keys.iter() {
+            assert_eq!(key.pk_vec.len(), n as usize);
+            assert_eq!(key.pk_vec, keys[0].pk_vec);
+            assert_eq!(key.pk_vec[(key.i - 1) as usize], key.keys_linear.y);
+        }
+
+        let offline_sign = simulate_offline_stage(keys, &[2, 4, 5]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    #[test]
+    fn test_collect_verified() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // a transcript with a tampered public key is rejected and the key is left untouched
+        let mut tampered = refresh_messages.clone();
+        tampered[1].public_key = Point::generator() * Scalar::random();
+        let old_key = keys[0].clone();
+        assert!(matches!(
+            RefreshMessage::collect_verified(&tampered, &keys[0], new_dks[0].clone(), &[]),
+            Err(FsDkrError::BroadcastedPublicKeyError)
+        ));
+        assert_eq!(keys[0].keys_linear.x_i, old_key.keys_linear.x_i);
+
+        let new_keys: Vec<_> = keys
+            .iter()
+            .zip(new_dks)
+            .map(|(key, new_dk)| {
+                RefreshMessage::collect_verified(&refresh_messages, key, new_dk, &[]).unwrap()
+            })
+            .collect();
+
+        let offline_sign = simulate_offline_stage(new_keys, &[1, 3, 5]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    #[test]
+    fn test_collect_rejects_duplicate_sender() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // the messages of two relays are merged, party 1 shows up twice
+        let mut merged = vec![refresh_messages[0].clone()];
+        merged.extend(refresh_messages[..4].iter().cloned());
+        assert!(matches!(
+            RefreshMessage::collect_with_strictness(
+                &merged,
+                &mut keys[0],
+                new_dks[0].clone(),
+                &[],
+                Strictness::Structural
+            ),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_collect_rejects_truncated_message() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        // every message only carries shares for n - 1 slots
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n - 1).unwrap())
+            .unzip();
+
+        assert!(matches!(
+            RefreshMessage::collect(&refresh_messages, &mut keys[0], new_dks[0].clone(), &[]),
+            Err(FsDkrError::MalformedRefreshMessage { party_index: 1, ref field })
+                if field == "points_committed_vec"
+        ));
+    }
+
+    #[test]
+    fn test_validate_collect_report() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (mut refresh_messages, _new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let report =
+            RefreshMessage::validate_collect_report(&refresh_messages, Threshold(t), ShareCount(n))
+                .unwrap();
+        assert_eq!(report.validated, vec![1, 2, 3]);
+
+        refresh_messages[0].points_committed_vec.pop();
+        refresh_messages[2].points_committed_vec[0] = Point::generator().to_point();
+        match RefreshMessage::validate_collect_report(
+            &refresh_messages,
+            Threshold(t),
+            ShareCount(n),
+        ) {
+            Err(FsDkrError::ValidationFailed { failures }) => {
+                assert_eq!(failures.len(), 2);
+                assert!(matches!(
+                    failures[0],
+                    (0, FsDkrError::SizeMismatchError { .. })
+                ));
+                assert!(matches!(
+                    failures[1],
+                    (2, FsDkrError::PublicShareValidationError)
+                ));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_aggregate_commitments() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let public_key = keys[0].y_sum_s.clone();
+        let (mut refresh_messages, _new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let parameters = ShamirSecretSharing {
+            threshold: t,
+            share_count: n,
+        };
+        let validate =
+            |refresh_messages: &[RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>]| {
+                let quorum = RefreshMessage::quorum(refresh_messages, t).unwrap();
+                let li_vec = RefreshMessage::quorum_lagrange_coefficients(&quorum, &parameters);
+                RefreshMessage::validate_aggregate_commitments(&quorum, &li_vec, &public_key)
+            };
+        validate(&refresh_messages).unwrap();
+
+        let mut shifted = refresh_messages.clone();
+        let constant_term = &mut shifted[0].coefficients_committed_vec.commitments[0];
+        *constant_term = &*constant_term + Point::generator().to_point();
+        assert!(matches!(
+            validate(&shifted),
+            Err(FsDkrError::CoefficientCommitmentMismatch { party_index: 0 })
+        ));
+
+        refresh_messages[1]
+            .coefficients_committed_vec
+            .commitments
+            .pop();
+        assert!(matches!(
+            validate(&refresh_messages),
+            Err(FsDkrError::CoefficientCommitmentMismatch { party_index: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_collect_with_progress() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let mut events = Vec::new();
+        RefreshMessage::collect_with_progress(
+            &refresh_messages,
+            &mut keys[0],
+            new_dks[0].clone(),
+            &[],
+            &mut |event| events.push(event),
+        )
+        .unwrap();
+
+        let expected: Vec<VerificationEvent> = (1..=n)
+            .flat_map(|i| {
+                vec![
+                    VerificationEvent::StartedParty(i),
+                    VerificationEvent::ProofVerified(i, ProofKind::EncryptedShares),
+                    VerificationEvent::ProofVerified(i, ProofKind::RingPedersen),
+                    VerificationEvent::ProofVerified(i, ProofKind::PaillierKey),
+                    VerificationEvent::PartyComplete(i),
+                ]
+            })
+            .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn test_validate_public_key_reconstruction() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let public_key = keys[0].y_sum_s.clone();
+        let (refresh_messages, _new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let parameters = ShamirSecretSharing {
+            threshold: t,
+            share_count: n,
+        };
+        let quorum = RefreshMessage::quorum(&refresh_messages, t).unwrap();
+        let li_vec = RefreshMessage::quorum_lagrange_coefficients(&quorum, &parameters);
+        let mut pk_vec: Vec<_> = (0..n as usize)
+            .map(|i| RefreshMessage::aggregate_committed_point(&quorum, &li_vec, i))
+            .collect();
+
+        RefreshMessage::validate_public_key_reconstruction(&quorum, &pk_vec, &public_key).unwrap();
+        let wrong_key = &public_key + Point::generator().to_point();
+        assert!(matches!(
+            RefreshMessage::validate_public_key_reconstruction(&quorum, &pk_vec, &wrong_key),
+            Err(FsDkrError::PublicKeyReconstructionMismatch)
+        ));
+        pk_vec[0] = &pk_vec[0] + Point::generator().to_point();
+        assert!(matches!(
+            RefreshMessage::validate_public_key_reconstruction(&quorum, &pk_vec, &public_key),
+            Err(FsDkrError::PublicKeyReconstructionMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_collect_with_participation() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (mut refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        refresh_messages.reverse();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let participation =
+                RefreshMessage::collect_with_participation(&refresh_messages, key, new_dk, &[])
+                    .unwrap();
+            assert_eq!(participation.quorum, vec![1, 2, 3]);
+            assert!(participation.joiners.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_collect_with_ciphertext_sum() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let old_ek = key.paillier_key_vec[(key.i - 1) as usize].clone();
+            let old_dk = key.paillier_dk.clone();
+            let ciphertext_sum =
+                RefreshMessage::collect_with_ciphertext_sum(&refresh_messages, key, new_dk, &[])
+                    .unwrap();
+
+            assert_eq!(
+                RefreshMessage::ciphertext_sum(&refresh_messages, key.i, t, &old_ek).unwrap(),
+                ciphertext_sum
+            );
+            let decrypted = Paillier::decrypt(&old_dk, RawCiphertext::from(ciphertext_sum))
+                .0
+                .into_owned();
+            assert_eq!(
+                Scalar::<Secp256k1>::from(&BigInt::modulus(
+                    &decrypted,
+                    &crate::curve_order::<Secp256k1>()
+                )),
+                key.keys_linear.x_i
+            );
+        }
+    }
+
+    #[test]
+    fn test_combine_partial_decryptions() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let old_ek = keys[0].paillier_key_vec[0].clone();
+        // three custodians hold the decryption key of party 1, any two of them decrypt
+        let dk_shares =
+            share_decryption_key(&keys[0].paillier_dk, Threshold(1), ShareCount(3)).unwrap();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let ciphertext_sum =
+            RefreshMessage::ciphertext_sum(&refresh_messages, 1, t, &old_ek).unwrap();
+        let partials: Vec<PartialDecryption> = dk_shares
+            .iter()
+            .map(|dk_share| dk_share.partial_decrypt(&ciphertext_sum))
+            .collect();
+
+        let plaintext = combine_partial_decryptions(
+            &[partials[2].clone(), partials[0].clone()],
+            &old_ek,
+            Threshold(1),
+            ShareCount(3),
+        )
+        .unwrap();
+        assert_eq!(
+            combine_partial_decryptions(&partials[..2], &old_ek, Threshold(1), ShareCount(3))
+                .unwrap(),
+            plaintext
+        );
+
+        // party 1 collects with the combined plaintext, without the decryption key of its slot
+        let mut custodial_key = keys[0].clone();
+        custodial_key.paillier_dk = keys[1].paillier_dk.clone();
+        assert!(matches!(
+            RefreshMessage::collect_with_plaintext_share(
+                &refresh_messages,
+                &mut custodial_key.clone(),
+                new_dks[0].clone(),
+                &[],
+                &(&plaintext + BigInt::from(1)),
+            ),
+            Err(FsDkrError::PublicShareValidationError)
+        ));
+        RefreshMessage::collect_with_plaintext_share(
+            &refresh_messages,
+            &mut custodial_key,
+            new_dks[0].clone(),
+            &[],
+            &plaintext,
+        )
+        .unwrap();
+
+        RefreshMessage::collect(&refresh_messages, &mut keys[0], new_dks[0].clone(), &[]).unwrap();
+        assert_eq!(
+            Scalar::<Secp256k1>::from(&BigInt::modulus(
+                &plaintext,
+                &crate::curve_order::<Secp256k1>()
+            )),
+            keys[0].keys_linear.x_i
+        );
+        assert_eq!(custodial_key.keys_linear.x_i, keys[0].keys_linear.x_i);
+        assert_eq!(custodial_key.pk_vec, keys[0].pk_vec);
+
+        assert!(matches!(
+            combine_partial_decryptions(&partials[1..2], &old_ek, Threshold(1), ShareCount(3)),
+            Err(FsDkrError::PartialDecryptionsBelowThreshold {
+                threshold: 1,
+                received: 1
+            })
+        ));
+        assert!(matches!(
+            combine_partial_decryptions(
+                &[partials[1].clone(), partials[1].clone()],
+                &old_ek,
+                Threshold(1),
+                ShareCount(3)
+            ),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 2 })
+        ));
+        assert!(matches!(
+            share_decryption_key(&keys[1].paillier_dk, Threshold(3), ShareCount(3)),
+            Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining: 3,
+                threshold: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_decrypt_verified_detects_key_mismatch() {
+        let (ek, dk) = Paillier::keypair().keys();
+        let (_other_ek, other_dk) = Paillier::keypair().keys();
+        let ciphertext = Paillier::encrypt(&ek, RawPlaintext::from(BigInt::from(42)))
+            .0
+            .into_owned();
+
+        let context = DecryptionContext::new(&dk).unwrap();
+        assert_eq!(
+            context.decrypt_verified(&ek, &ciphertext, 1).unwrap(),
+            BigInt::from(42)
+        );
+        assert!(matches!(
+            DecryptionContext::new(&other_dk)
+                .unwrap()
+                .decrypt_verified(&ek, &ciphertext, 1),
+            Err(FsDkrError::DecryptionVerificationFailed { party_index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_collect_with_context() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let ciphertext = Paillier::encrypt(
+                &key.paillier_key_vec[(key.i - 1) as usize],
+                RawPlaintext::from(BigInt::from(7)),
+            )
+            .0
+            .into_owned();
+            let context = DecryptionContext::new(&key.paillier_dk).unwrap();
+            assert_eq!(
+                context.decrypt(&ciphertext),
+                Paillier::decrypt(&key.paillier_dk, RawCiphertext::from(ciphertext.clone()))
+                    .0
+                    .into_owned()
+            );
+
+            let mut stateless_key = key.clone();
+            RefreshMessage::collect(&refresh_messages, &mut stateless_key, new_dk.clone(), &[])
+                .unwrap();
+            RefreshMessage::collect_with_context(&refresh_messages, key, new_dk, &[], &context)
+                .unwrap();
+            assert_eq!(key.keys_linear.x_i, stateless_key.keys_linear.x_i);
+            assert_eq!(key.pk_vec, stateless_key.pk_vec);
+
+            // the key was rotated, the context no longer matches it
+            assert!(!context.matches(&key.paillier_dk));
+        }
+    }
+
+    #[test]
+    fn test_collect_expecting() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let expected_pk = keys[0].y_sum_s.clone();
+        let other_pk = simulate_keygen(t, n)[0].y_sum_s.clone();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let mut key = keys[0].clone();
+        let result = RefreshMessage::collect_expecting(
+            &other_pk,
+            &refresh_messages,
+            &mut key,
+            new_dks[0].clone(),
+            &[],
+        );
+        assert!(matches!(
+            result,
+            Err(FsDkrError::UnexpectedPublicKey { .. })
+        ));
+        assert_eq!(key.keys_linear.x_i, keys[0].keys_linear.x_i);
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect_expecting(&expected_pk, &refresh_messages, key, new_dk, &[])
+                .unwrap();
+            assert_eq!(key.y_sum_s, expected_pk);
+        }
+    }
+
+    #[test]
+    fn test_collect_public_only() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let public_key = keys[0].y_sum_s.clone();
+        let (mut refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        assert_eq!(
+            RefreshMessage::collect_public_only(&refresh_messages, Threshold(t), ShareCount(n))
+                .unwrap(),
+            public_key
+        );
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+            assert_eq!(key.y_sum_s, public_key);
+        }
+
+        refresh_messages[2].public_key = Point::generator().to_point();
+        assert!(matches!(
+            RefreshMessage::collect_public_only(&refresh_messages, Threshold(t), ShareCount(n)),
+            Err(FsDkrError::BroadcastedPublicKeyError)
+        ));
+    }
+
+    #[test]
+    fn test_verify_decrypted_share() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_message, _new_dk): (
+            RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = RefreshMessage::distribute(keys[0].i, &mut keys[0], n).unwrap();
+
+        let key = &keys[1];
+        let slot = (key.i - 1) as usize;
+        let decrypted = Paillier::decrypt(
+            &key.paillier_dk,
+            RawCiphertext::from(refresh_message.points_encrypted_vec[slot].clone()),
+        )
+        .0
+        .into_owned();
+        let share = Scalar::<Secp256k1>::from(&decrypted);
+        refresh_message
+            .verify_decrypted_share(slot, &share)
+            .unwrap();
+
+        assert!(matches!(
+            refresh_message.verify_decrypted_share(slot, &(share + scalar(1))),
+            Err(FsDkrError::DecryptedShareMismatch {
+                party_index: 1,
+                slot: 1
+            })
+        ));
+        assert!(matches!(
+            refresh_message.verify_decrypted_share(n as usize, &scalar(1)),
+            Err(FsDkrError::MalformedRefreshMessage { party_index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_collect_with_source() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        assert!(matches!(
+            RefreshMessage::collect_with_source(
+                &mut refresh_messages[..2].to_vec().into_iter(),
+                &mut keys[0].clone(),
+                new_dks[0].clone(),
+                &[]
+            ),
+            Err(FsDkrError::MessageSourceExhausted {
+                received: 2,
+                expected: 3
+            })
+        ));
+
+        // an invalid message fails the collect before the rest of the round is pulled
+        let mut tampered = refresh_messages.clone();
+        tampered[0].public_key = Point::generator().to_point();
+        let mut pulled = 0;
+        assert!(matches!(
+            RefreshMessage::collect_with_source(
+                &mut tampered.into_iter().inspect(|_| pulled += 1),
+                &mut keys[0].clone(),
+                new_dks[0].clone(),
+                &[]
+            ),
+            Err(FsDkrError::BroadcastedPublicKeyError)
+        ));
+        assert_eq!(pulled, 1);
+
+        // a different message from a party that was already heard from is not ignored
+        let mut conflicting = refresh_messages[1].clone();
+        conflicting.epoch = 1;
+        assert!(matches!(
+            RefreshMessage::collect_with_source(
+                &mut vec![refresh_messages[1].clone(), conflicting].into_iter(),
+                &mut keys[0].clone(),
+                new_dks[0].clone(),
+                &[]
+            ),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 2 })
+        ));
+
+        // a source repeating a message forever fails the collect
+        let mut pulled = 0;
+        assert!(matches!(
+            RefreshMessage::collect_with_source(
+                &mut std::iter::repeat(refresh_messages[1].clone()).inspect(|_| pulled += 1),
+                &mut keys[0].clone(),
+                new_dks[0].clone(),
+                &[]
+            ),
+            Err(FsDkrError::MessageSourceFlooded { limit: 6 })
+        ));
+        assert_eq!(pulled, 8);
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let mut buffered = key.clone();
+            RefreshMessage::collect(&refresh_messages, &mut buffered, new_dk.clone(), &[]).unwrap();
+
+            // messages delivered again are ignored, nothing is pulled after the round is complete
+            let mut source = refresh_messages[..1]
+                .iter()
+                .chain(refresh_messages.iter())
+                .cloned()
+                .chain(std::iter::once(refresh_messages[0].clone()));
+            RefreshMessage::collect_with_source(&mut source, key, new_dk, &[]).unwrap();
+            assert!(source.next().is_some());
+            assert_eq!(key.keys_linear.x_i, buffered.keys_linear.x_i);
+            assert_eq!(key.pk_vec, buffered.pk_vec);
+            assert_eq!(key.paillier_key_vec, buffered.paillier_key_vec);
+        }
+    }
+
+    #[test]
+    fn test_collect_point_to_point() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let (broadcasts, shares): (Vec<_>, Vec<_>) = refresh_messages
+            .iter()
+            .map(|refresh_message| refresh_message.split())
+            .unzip();
+        let shares: Vec<EncryptedShare> = shares.into_iter().flatten().collect();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let own_shares: Vec<EncryptedShare> = shares
+                .iter()
+                .filter(|share| share.recipient == key.i)
+                .cloned()
+                .collect();
+            let mut broadcast_key = key.clone();
+            RefreshMessage::collect_point_to_point(
+                &broadcasts,
+                &own_shares,
+                &mut broadcast_key,
+                new_dk.clone(),
+                &[],
+            )
+            .unwrap();
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+            assert_eq!(broadcast_key.keys_linear.x_i, key.keys_linear.x_i);
+            assert_eq!(broadcast_key.pk_vec, key.pk_vec);
+        }
+
+        let key = keys[0].clone();
+        let missing = RefreshMessage::collect_point_to_point(
+            &broadcasts,
+            &shares[1..],
+            &mut key.clone(),
+            key.paillier_dk.clone(),
+            &[],
+        );
+        assert!(matches!(
+            missing,
+            Err(FsDkrError::MissingContribution { party_index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_ceremony_state_resume() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let old_keys = keys.clone();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let mut new_keys = Vec::new();
+        for ((key, refresh_message), new_dk) in
+            keys.into_iter().zip(refresh_messages.iter()).zip(new_dks)
+        {
+            let mut state = CeremonyState::existing(key, refresh_message.clone(), new_dk);
+            for received in refresh_messages[..3].iter() {
+                state.receive_refresh_message(received.clone()).unwrap();
+            }
+
+            // the party restarts before all messages arrived
+            let mut state = CeremonyState::<Secp256k1, Sha256, { crate::M_SECURITY }>::load(
+                &state.save().unwrap(),
+            )
+            .unwrap();
+            assert_eq!(state.phase(), CeremonyPhase::Collecting);
+            for received in refresh_messages.iter() {
+                state.receive_refresh_message(received.clone()).unwrap();
+            }
+            // a different message from a party that was already heard from is rejected
+            let mut equivocation = refresh_messages[1].clone();
+            equivocation.epoch += 1;
+            assert!(matches!(
+                state.receive_refresh_message(equivocation),
+                Err(FsDkrError::DuplicatePartyIndex { party_index: 2 })
+            ));
+
+            new_keys.push(state.collect().unwrap().clone());
+            assert_eq!(state.phase(), CeremonyPhase::Completed);
+        }
+
+        assert!(new_keys
+            .iter()
+            .zip(old_keys.iter())
+            .all(|(key, old_key)| key.same_group_as(old_key)
+                && key.keys_linear.x_i != old_key.keys_linear.x_i));
+        let offline_sign = simulate_offline_stage(new_keys, &[1, 2, 3]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    #[test]
+    fn test_health_monitor() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let refresh_messages: Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>> = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap().0)
+            .collect();
+
+        let mut monitor = HealthMonitor::new();
+        for refresh_message in refresh_messages.iter() {
+            monitor
+                .ingest_refresh_message(refresh_message, &keys[0])
+                .unwrap();
+        }
+        assert_eq!(monitor.healthy_indices(), vec![1, 2, 3, 4, 5]);
+
+        // party 3 later broadcasts a Paillier key its correctness proof is not for
+        let mut tampered = refresh_messages[2].clone();
+        tampered.ek = refresh_messages[0].ek.clone();
+        assert!(monitor.ingest_refresh_message(&tampered, &keys[0]).is_err());
+
+        assert_eq!(monitor.healthy_indices(), vec![1, 2, 4, 5]);
+        assert_eq!(monitor.flagged_indices(), vec![3]);
+        let health = monitor.health(3).unwrap();
+        assert_eq!((health.verified, health.failed), (1, 1));
+        assert_eq!(health.last_seen_epoch, Some(0));
+        assert!(matches!(
+            health.last_failure,
+            Some(FsDkrError::PaillierVerificationError { party_index: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_replay_cached_contribution() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // party 1, a member of the quorum {1, 2, 3}, contributed to an earlier attempt of the
+        // round and then went offline
+        let mut cache = ContributionCache::new();
+        cache.insert(refresh_messages[0].clone());
+
+        let mut round_messages = refresh_messages[1..].to_vec();
+        round_messages.extend(cache.replay(&[1], &keys[1], n as usize, 0).unwrap());
+        let quorum = RefreshMessage::quorum(&round_messages, t).unwrap();
+        assert!(quorum
+            .iter()
+            .any(|refresh_message| refresh_message.party_index == 1));
+
+        // a contribution produced for another epoch is not replayed into this one
+        let (later_message, _) =
+            RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_epoch(
+                keys[1].i,
+                &mut keys[1].clone(),
+                n,
+                1,
+            )
+            .unwrap();
+        let mut later_cache = ContributionCache::new();
+        later_cache.insert(later_message);
+        assert!(matches!(
+            later_cache.replay(&[2], &keys[0], n as usize, 0),
+            Err(FsDkrError::EpochMismatch {
+                party_index: 2,
+                expected: 0,
+                found: 1,
+            })
+        ));
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&round_messages, key, new_dk, &[]).unwrap();
+        }
+
+        let offline_sign = simulate_offline_stage(keys.clone(), &[1, 4, 5]);
+        simulate_signing(offline_sign, b"ZenGo");
+
+        // the shares changed, so the cached contribution can not be replayed anymore
+        assert!(matches!(
+            cache.replay(&[1], &keys[1], n as usize, 0),
+            Err(FsDkrError::StaleContribution { party_index: 1 })
+        ));
+        assert!(matches!(
+            cache.replay(&[4], &keys[1], n as usize, 0),
+            Err(FsDkrError::MissingContribution { party_index: 4 })
+        ));
+    }
+
+    #[test]
+    fn test_collect_with_epoch() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute_with_epoch(key.i, key, n, 3).unwrap())
+            .unzip();
+
+        // mixing contributions from different epochs is rejected
+        let mut mixed_messages = refresh_messages.clone();
+        mixed_messages[1].epoch = 2;
+        let result = RefreshMessage::collect(
+            &mixed_messages,
+            &mut keys[0].clone(),
+            new_dks[0].clone(),
+            &[],
+        );
+        assert!(matches!(
+            result,
+            Err(FsDkrError::EpochMismatch {
+                expected: 3,
+                found: 2,
+                ..
+            })
+        ));
+
+        // a key at another epoch can not collect the round
+        let result = RefreshMessage::collect_with_epoch(
+            &refresh_messages,
+            &mut keys[0].clone(),
+            new_dks[0].clone(),
+            &[],
+            4,
+        );
+        assert!(matches!(result, Err(FsDkrError::EpochMismatch { .. })));
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let epoch =
+                RefreshMessage::collect_with_epoch(&refresh_messages, key, new_dk, &[], 3).unwrap();
+            assert_eq!(epoch, 4);
+        }
+    }
+
+    #[test]
+    fn test_decode_message_without_epoch() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // a message encoded before the epoch field existed decodes at epoch 0
+        let decoded: Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>> =
+            refresh_messages
+                .iter()
+                .map(|refresh_message| {
+                    let mut value = serde_json::to_value(refresh_message).unwrap();
+                    assert!(value.as_object_mut().unwrap().remove("epoch").is_some());
+                    serde_json::from_value(value).unwrap()
+                })
+                .collect();
+        assert!(decoded
+            .iter()
+            .all(|refresh_message| refresh_message.epoch() == 0));
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&decoded, key, new_dk, &[]).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_failed_collect_leaves_key_unchanged() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        // party 2, a member of the quorum, sends party 1 a share with a random plaintext added,
+        // which is only caught after decryption since structural collect verifies no proof
+        let ek = &keys[0].paillier_key_vec[0];
+        let shift = BigInt::from(1) + BigInt::sample_below(&ek.n) * &ek.n;
+        let mut tampered_messages = refresh_messages.clone();
+        tampered_messages[1].points_encrypted_vec[0] =
+            BigInt::mod_mul(&refresh_messages[1].points_encrypted_vec[0], &shift, &ek.nn);
+
+        let encoded = serde_json::to_vec(&keys[0]).unwrap();
+        let result = RefreshMessage::collect_with_strictness(
+            &tampered_messages,
+            &mut keys[0],
+            new_dks[0].clone(),
+            &[],
+            Strictness::Structural,
+        );
+        assert!(matches!(
+            result,
+            Err(FsDkrError::ShareOutOfRange { party_index: 1 })
+        ));
+        assert_eq!(serde_json::to_vec(&keys[0]).unwrap(), encoded);
+
+        // the key is retried with the messages as they were sent
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+        }
+        let offline_sign = simulate_offline_stage(keys, &[1, 2]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    #[test]
+    fn test_paillier_key_vec_is_ordered_by_party_index() {
+        let keys = Network::<{ crate::M_SECURITY }>::run_refresh(2, 5, &[2, 5]).unwrap();
+        for key in keys.iter() {
+            assert_eq!(key.paillier_key_vec.len(), keys.len());
+            for owner in keys.iter() {
+                assert_eq!(
+                    key.paillier_key_vec[(owner.i - 1) as usize].n,
+                    &owner.paillier_dk.p * &owner.paillier_dk.q
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_work() {
+        assert_eq!(
+            estimate_work(Threshold(1), ShareCount(3), 0),
+            WorkEstimate {
+                paillier_decryptions: 1,
+                pdl_with_slack_verifications: 9,
+                range_proof_verifications: 9,
+                ring_pedersen_verifications: 3,
+                correct_key_verifications: 3,
+                composite_dlog_verifications: 0,
+                curve_scalar_multiplications: 18 + 2 + 6 + 2 + 1,
+            }
+        );
+
+        let with_joiners = estimate_work(Threshold(2), ShareCount(5), 2);
+        assert_eq!(with_joiners.pdl_with_slack_verifications, 35);
+        assert_eq!(with_joiners.correct_key_verifications, 7);
+        assert_eq!(with_joiners.composite_dlog_verifications, 4);
+    }
+
+    #[test]
+    fn test_curve_order() {
+        let q =
+            BigInt::from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+                .unwrap();
+        assert_eq!(crate::curve_order::<Secp256k1>(), q);
+    }
+
+    #[test]
+    fn test_committee_size_is_bounded() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let too_large = crate::MAX_COMMITTEE_SIZE as u16 + 1;
+
+        let result = RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute(
+            keys[0].i,
+            &mut keys[0],
+            too_large,
+        );
+        assert!(matches!(
+            result,
+            Err(FsDkrError::CommitteeTooLarge { n, .. }) if n == too_large as usize
+        ));
+
+        let (refresh_messages, _new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        assert!(matches!(
+            RefreshMessage::validate_collect(
+                &refresh_messages,
+                Threshold(t),
+                ShareCount(too_large)
+            ),
+            Err(FsDkrError::CommitteeTooLarge { .. })
+        ));
+
+        let reshuffle = Reshuffle {
+            add: (n + 1..=too_large + n).collect(),
+            remove: Vec::new(),
+            new_threshold: t,
+        };
+        assert!(matches!(
+            reshuffle.validate(Threshold(t), ShareCount(n)),
+            Err(FsDkrError::CommitteeTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_network_run_refresh() {
+        let keys = Network::<{ crate::M_SECURITY }>::run_refresh(2, 5, &[2, 5]).unwrap();
+        assert_eq!(keys.len(), 5);
+        let offline_sign = simulate_offline_stage(keys, &[1, 2, 5]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    #[test]
+    fn test_refresh_with_joiners_signs_with_every_party() {
+        let (t, n) = (2, 7);
+        let keys = Network::<{ crate::M_SECURITY }>::run_refresh(t, n, &[2, 4, 7]).unwrap();
+        assert_eq!(keys.len(), n as usize);
+
+        // every party, existing or joining, inserted its own keys at its own position and ended
+        // up with the same view of the committee as everyone else
+        for key in keys.iter() {
+            assert_eq!(key.y_sum_s, keys[0].y_sum_s);
+            assert_eq!(key.pk_vec, keys[0].pk_vec);
+            assert_eq!(key.paillier_key_vec, keys[0].paillier_key_vec);
+            for (statement, expected) in key
+                .h1_h2_n_tilde_vec
+                .iter()
+                .zip(keys[0].h1_h2_n_tilde_vec.iter())
+            {
+                assert_eq!(
+                    (&statement.N, &statement.g, &statement.ni),
+                    (&expected.N, &expected.g, &expected.ni)
+                );
+            }
+            assert_eq!(key.keys_linear.y, key.pk_vec[(key.i - 1) as usize]);
+            assert_eq!(
+                key.paillier_key_vec[(key.i - 1) as usize].n,
+                &key.paillier_dk.p * &key.paillier_dk.q
+            );
+        }
+
+        // quorums covering every party, each mixing existing and joining parties
+        for start in (0..n as usize).step_by(t as usize) {
+            let mut quorum: Vec<_> = (0..=t as usize)
+                .map(|k| keys[(start + k) % n as usize].clone())
+                .collect();
+            quorum.sort_by_key(|key| key.i);
+            sign_with_quorum(&quorum, b"ZenGo").unwrap();
+        }
+        sign_with_quorum(&keys, b"ZenGo").unwrap();
+    }
+
+    #[test]
+    fn test_share_attestation() {
+        let keys = Network::<{ crate::M_SECURITY }>::run_refresh(1, 3, &[3]).unwrap();
+        let attestation = ShareAttestation::new(&keys[2]).unwrap();
+        for key in keys.iter() {
+            attestation.verify(key).unwrap();
+        }
+
+        // an attestation of another share does not attest the new party
+        let mut relabeled = ShareAttestation::new(&keys[0]).unwrap();
+        relabeled.party_index = 3;
+        assert!(matches!(
+            relabeled.verify(&keys[1]),
+            Err(FsDkrError::ShareAttestationInvalid { party_index: 3 })
+        ));
+        relabeled.party_index = 4;
+        assert!(matches!(
+            relabeled.verify(&keys[1]),
+            Err(FsDkrError::PartyIndexOutOfRange { index: 4, n: 3 })
+        ));
+
+        let mut inconsistent = keys[2].clone();
+        inconsistent.keys_linear.x_i = Scalar::random();
+        assert!(matches!(
+            ShareAttestation::new(&inconsistent),
+            Err(FsDkrError::PublicShareValidationError)
+        ));
+    }
+
+    #[test]
+    fn test_sign_with_quorum() {
+        let keys = Network::<{ crate::M_SECURITY }>::run_refresh(2, 5, &[]).unwrap();
+        sign_with_quorum(&keys[1..4], b"ZenGo").unwrap();
+        sign_with_quorum(&keys, b"ZenGo").unwrap();
+    }
+
+    #[test]
+    fn test_maximum_threshold() {
+        // t = n - 1, the quorum is the whole committee
+        let mut keys = simulate_keygen(1, 2);
+        let old_keys = keys.clone();
+        let (refresh_messages, _) = simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+        let quorum = RefreshMessage::quorum(&refresh_messages, 1).unwrap();
+        assert_eq!(quorum.len(), 2);
+        for (old_key, key) in old_keys.iter().zip(keys.iter()) {
+            assert!(refresh_changed_material(old_key, key));
+            assert_eq!(
+                Point::generator() * &key.keys_linear.x_i,
+                key.pk_vec[(key.i - 1) as usize]
+            );
+        }
+        sign_with_quorum(&keys, b"ZenGo").unwrap();
+
+        // any larger t = n - 1 committee violates the honest majority the refresh assumes
+        for n in 3..=4 {
+            let mut keys = simulate_keygen(n - 1, n);
+            let result: FsDkrResult<(
+                RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+                DecryptionKey,
+            )> = RefreshMessage::distribute(1, &mut keys[0], n);
+            assert!(matches!(
+                result,
+                Err(FsDkrError::HonestMajorityViolation { t, n: new_n }) if t == n - 1 && new_n == n
+            ));
+        }
+    }
+
+    #[test]
+    fn test_refresh_with_reordered_and_duplicated_delivery() {
+        let mut network = Network::<{ crate::M_SECURITY }>::keygen(2, 5).unwrap();
+        let y_sum_s = network.keys()[0].y_sum_s.clone();
+
+        // every party receives the refresh and join messages in its own order
+        network
+            .refresh_with_delivery(&[2, 5], Delivery::Shuffled { seed: 7 })
+            .unwrap();
+        for key in network.keys().iter() {
+            assert_eq!(key.y_sum_s, y_sum_s);
+            assert_eq!(key.pk_vec, network.keys()[0].pk_vec);
+            assert_eq!(key.paillier_key_vec, network.keys()[0].paillier_key_vec);
+        }
+        sign_with_quorum(&network.keys()[1..4], b"ZenGo").unwrap();
+
+        // a message delivered twice is rejected instead of being counted as another party, and
+        // the keys are left as they were
+        let shares: Vec<_> = network
+            .keys()
+            .iter()
+            .map(|key| key.keys_linear.x_i.clone())
+            .collect();
+        for copies in [1, 3] {
+            assert!(matches!(
+                network.refresh_with_delivery(&[3], Delivery::Duplicated { seed: 7, copies }),
+                Err(FsDkrError::DuplicatePartyIndex { .. })
+            ));
+        }
+        for (key, share) in network.keys().iter().zip(shares) {
+            assert_eq!(key.keys_linear.x_i, share);
+        }
+    }
+
+    #[test]
+    fn test_split_committee() {
+        let network = Network::<{ crate::M_SECURITY }>::keygen(1, 3).unwrap();
+        let y_sum_s = network.keys()[0].y_sum_s.clone();
+
+        // the whole committee or any quorum of t + 1 parties can run the split
+        for quorum in [vec![1, 2, 3], vec![1, 3], vec![2, 3]].iter() {
+            let (first, second) = network.split(quorum).unwrap();
+
+            for child in [&first, &second].iter() {
+                assert!(child.keys().iter().all(|key| key.y_sum_s == y_sum_s));
+                // every child signs on its own, checked against y_sum_s
+                sign_with_quorum(&child.keys()[..2], b"ZenGo").unwrap();
+                let other_quorum = [child.keys()[0].clone(), child.keys()[2].clone()];
+                sign_with_quorum(&other_quorum, b"ZenGo").unwrap();
+            }
+
+            // the children hold independent sharings under Paillier keys of their own
+            let own_modulus =
+                |key: &LocalKey<Secp256k1>| key.paillier_key_vec[(key.i - 1) as usize].n.clone();
+            let moduli: Vec<BigInt> = first
+                .keys()
+                .iter()
+                .chain(second.keys())
+                .map(own_modulus)
+                .collect();
+            for (i, modulus) in moduli.iter().enumerate() {
+                assert!(moduli[i + 1..].iter().all(|other| other != modulus));
+            }
+            for (first_key, second_key) in first.keys().iter().zip(second.keys()) {
+                assert_ne!(first_key.keys_linear.x_i, second_key.keys_linear.x_i);
+            }
+        }
+
+        // the two children occupy disjoint ranges of split indices
+        let keys = simulate_keygen(1, 3);
+        let joins = join_split::<Secp256k1, Sha256, { crate::M_SECURITY }>(&keys[2]).unwrap();
+        let join_messages = [
+            vec![joins[0].join_message().clone()],
+            vec![joins[1].join_message().clone()],
+        ];
+        let mut distributions: Vec<_> = keys[..2]
+            .iter()
+            .map(|key| {
+                split_committee(key, [&join_messages[0][..], &join_messages[1][..]], 0).unwrap()
+            })
+            .collect();
+        let split_indices = |child: usize| -> HashSet<u16> {
+            distributions
+                .iter()
+                .map(|distribution| distribution[child].split_index())
+                .chain(std::iter::once(joins[child].split_index()))
+                .collect()
+        };
+        assert_eq!(split_indices(0), child_indices(0, 3).collect());
+        assert_eq!(split_indices(1), child_indices(1, 3).collect());
+        assert!(split_indices(0).is_disjoint(&split_indices(1)));
+
+        // a quorum party can not also join
+        assert!(matches!(
+            split_committee(&keys[2], [&join_messages[0][..], &join_messages[1][..]], 0),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 3 })
+        ));
+
+        // the messages of the two children can not be mixed
+        let mixed_messages = vec![
+            distributions[0][0].refresh_message().clone(),
+            distributions[1][1].refresh_message().clone(),
+        ];
+        let [first_child, _] = distributions.remove(0);
+        assert_eq!(first_child.child(), 0);
+        assert!(matches!(
+            first_child.collect(&mixed_messages, &join_messages[0]),
+            Err(FsDkrError::SplitChildMismatch {
+                party_index: 2,
+                expected: 0,
+                found: Some(1),
+            })
+        ));
+    }
+
+    #[test]
+    fn test_small_committees() {
+        for (t, n) in [(1u16, 2u16), (1, 3)].iter().copied() {
+            let mut keys = simulate_keygen(t, n);
+            let old_keys = keys.clone();
+            simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+            simulate_dkr::<{ crate::M_SECURITY }>(&mut keys);
+
+            let mut vss = conformance_vss(t, n);
+            vss.parameters = keys[0].shamir_params();
+            let indices: Vec<u16> = (0..=t).collect();
+            let secret = |keys: &[LocalKey<Secp256k1>]| {
+                let shares: Vec<_> = keys[..=t as usize]
+                    .iter()
+                    .map(|key| key.keys_linear.x_i.clone())
+                    .collect();
+                vss.reconstruct(&indices, &shares)
+            };
+            assert_eq!(secret(&keys), secret(&old_keys));
+
+            for key in keys.iter() {
+                assert_eq!(key.y_sum_s, old_keys[0].y_sum_s);
+                assert_eq!(key.pk_vec.len(), n as usize);
+                assert_eq!(
+                    Point::generator() * &key.keys_linear.x_i,
+                    key.pk_vec[(key.i - 1) as usize]
+                );
+            }
+
+            for i in 0..n as usize {
+                for j in i + 1..n as usize {
+                    sign_with_quorum(&[keys[i].clone(), keys[j].clone()], b"ZenGo").unwrap();
+                }
+            }
+        }
+
+        let removal = Reshuffle {
+            add: vec![],
+            remove: vec![2],
+            new_threshold: 1,
+        };
+        assert!(matches!(
+            removal.validate(Threshold(1), ShareCount(2)),
+            Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining: 1,
+                threshold: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_join_message_rejects_inconsistent_dlog_statements() {
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        join_message.set_party_index(6);
+        join_message.verify_dlog_proofs().unwrap();
+
+        // the same residue, but not reduced modulo N
+        join_message.dlog_statement.ni =
+            &join_message.dlog_statement.ni + &join_message.dlog_statement.N;
+        assert!(matches!(
+            join_message.verify_dlog_proofs(),
+            Err(FsDkrError::InconsistentDLogStatements { party_index: 6 })
+        ));
+    }
+
+    #[test]
+    fn test_distribute_with_key() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+
+        // the key material is generated outside of the join flow, as an HSM would
+        let paillier_keys = Keys::create(0);
+        let dk_correctness_proof = NiCorrectKeyProof::proof(&paillier_keys.dk, None);
+        let dlog_statement_proofs = crate::add_party_message::generate_dlog_statement_proofs(
+            crate::add_party_message::KEYGEN_RETRIES,
+        )
+        .unwrap();
+        let (ring_pedersen_statement, ring_pedersen_witness) =
+            crate::ring_pedersen_proof::RingPedersenStatement::generate();
+        let ring_pedersen_proof = crate::ring_pedersen_proof::RingPedersenProof::prove(
+            &ring_pedersen_witness,
+            &ring_pedersen_statement,
+        );
+
+        // the proof does not belong to another key
+        let other_keys = Keys::create(0);
+        assert!(matches!(
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_key(
+                other_keys.ek.clone(),
+                dk_correctness_proof.clone(),
+                dlog_statement_proofs.clone(),
+                ring_pedersen_statement.clone(),
+                ring_pedersen_proof.clone(),
+            ),
+            Err(FsDkrError::PaillierVerificationError { party_index: 0 })
+        ));
+
+        let mut join_message: JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }> =
+            JoinMessage::distribute_with_key(
+                paillier_keys.ek.clone(),
+                dk_correctness_proof,
+                dlog_statement_proofs,
+                ring_pedersen_statement,
+                ring_pedersen_proof,
+            )
+            .unwrap();
+        assert_eq!(join_message.party_index, None);
+        join_message.set_party_index(3);
+        let join_messages = vec![join_message.clone()];
+
+        let old_to_new_map: HashMap<u16, u16> = vec![(1, 1), (2, 2)].into_iter().collect();
+        let mut keys: Vec<_> = keys.drain(..).filter(|key| key.i != 3).collect();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::replace(&join_messages, key, &old_to_new_map, n).unwrap())
+            .unzip();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &join_messages).unwrap();
+        }
+
+        // the new share is decrypted where the key is held
+        let ciphertext_sum =
+            RefreshMessage::ciphertext_sum(&refresh_messages, 3, t, &paillier_keys.ek).unwrap();
+        let plaintext = Paillier::decrypt(&paillier_keys.dk, RawCiphertext::from(ciphertext_sum))
+            .0
+            .into_owned();
+        assert!(matches!(
+            join_message.collect_with_plaintext_share(
+                &refresh_messages,
+                paillier_keys.dk.clone(),
+                &(&plaintext + BigInt::from(1)),
+                &join_messages,
+                Threshold(t),
+                ShareCount(n),
+            ),
+            Err(FsDkrError::PublicShareValidationError)
+        ));
+        let external_key = join_message
+            .collect_with_plaintext_share(
+                &refresh_messages,
+                paillier_keys.dk.clone(),
+                &plaintext,
+                &join_messages,
+                Threshold(t),
+                ShareCount(n),
+            )
+            .unwrap();
+
+        let new_key = join_message
+            .collect(
+                &refresh_messages,
+                paillier_keys,
+                &join_messages,
+                Threshold(t),
+                ShareCount(n),
+            )
+            .unwrap();
+        assert_eq!(new_key.y_sum_s, keys[0].y_sum_s);
+        assert_eq!(diagnose_local_key(&new_key), Vec::new());
+        assert_eq!(external_key.keys_linear.x_i, new_key.keys_linear.x_i);
+        assert_eq!(external_key.pk_vec, new_key.pk_vec);
+    }
+
+    // An entropy source that stopped delivering.
+    struct FailingRng;
+
+    impl RngCore for FailingRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.try_fill_bytes(dest).expect("the source failed")
+        }
+
+        fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            Err(rand_core::Error::from(
+                NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap(),
+            ))
+        }
+    }
+
+    impl CryptoRng for FailingRng {}
+
+    // Expands a seed with SHA-256 in counter mode, a reproducible stand-in for a hardware source.
+    struct SeededRng {
+        seed: u8,
+        counter: u64,
+    }
+
+    impl SeededRng {
+        fn new(seed: u8) -> Self {
+            SeededRng { seed, counter: 0 }
+        }
+    }
+
+    impl RngCore for SeededRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(32) {
+                let block = Sha256::new()
+                    .chain([self.seed])
+                    .chain(self.counter.to_be_bytes())
+                    .finalize();
+                self.counter += 1;
+                chunk.copy_from_slice(&block[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for SeededRng {}
+
+    #[test]
+    fn test_distribute_with_rng() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+
+        // a failing source fails the distribute instead of falling back to another source
+        let old_key = keys[0].clone();
+        assert!(matches!(
+            RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_rng(
+                1,
+                &mut keys[0],
+                n,
+                0,
+                &mut FailingRng,
+            ),
+            Err(FsDkrError::EntropySourceUnavailable { .. })
+        ));
+        assert_eq!(
+            serde_json::to_vec(&keys[0]).unwrap(),
+            serde_json::to_vec(&old_key).unwrap()
+        );
+        assert!(matches!(
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_rng(
+                &mut FailingRng
+            ),
+            Err(FsDkrError::EntropySourceUnavailable { .. })
+        ));
+
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| {
+                let mut rng = SeededRng::new(key.i as u8);
+                RefreshMessage::distribute_with_rng(key.i, key, n, 0, &mut rng).unwrap()
+            })
+            .unzip();
+        // every random value, the nonces of the proofs included, is drawn from the source
+        let (repeated, _) =
+            RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_rng(
+                1,
+                &mut keys[0].clone(),
+                n,
+                0,
+                &mut SeededRng::new(1),
+            )
+            .unwrap();
+        assert_eq!(
+            serde_json::to_vec(&repeated).unwrap(),
+            serde_json::to_vec(&refresh_messages[0]).unwrap()
+        );
+        assert_ne!(refresh_messages[0].ek, refresh_messages[1].ek);
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+        }
+
+        // a joining party replacing the third one
+        let (mut join_message, paillier_keys) =
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_rng(
+                &mut SeededRng::new(4),
+            )
+            .unwrap();
+        let (repeated, _) =
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_rng(
+                &mut SeededRng::new(4),
+            )
+            .unwrap();
+        assert_eq!(
+            serde_json::to_vec(&repeated).unwrap(),
+            serde_json::to_vec(&join_message).unwrap()
+        );
+        join_message.set_party_index(3);
+        let join_messages = vec![join_message.clone()];
+        let old_to_new_map: HashMap<u16, u16> = vec![(1, 1), (2, 2)].into_iter().collect();
+        let mut keys: Vec<_> = keys.drain(..).filter(|key| key.i != 3).collect();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::replace(&join_messages, key, &old_to_new_map, n).unwrap())
+            .unzip();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &join_messages).unwrap();
+        }
+        let new_key = join_message
+            .collect(
+                &refresh_messages,
+                paillier_keys,
+                &join_messages,
+                Threshold(t),
+                ShareCount(n),
+            )
+            .unwrap();
+        assert_eq!(diagnose_local_key(&new_key), Vec::new());
+        keys.push(new_key);
+
+        let offline_sign = simulate_offline_stage(keys, &[1, 3]);
+        simulate_signing(offline_sign, b"ZenGo");
+    }
+
+    static BACKEND_DECRYPTIONS: std::sync::atomic::AtomicUsize =
+        std::sync::atomic::AtomicUsize::new(0);
+
+    // the default backend, counting the decryptions
+    struct CountingPaillier;
+
+    impl PaillierBackend for CountingPaillier {
+        fn keypair(modulus_bits: usize) -> (EncryptionKey, DecryptionKey) {
+            KzenPaillier::keypair(modulus_bits)
+        }
+
+        fn encrypt(ek: &EncryptionKey, plaintext: &BigInt, randomness: &BigInt) -> BigInt {
+            KzenPaillier::encrypt(ek, plaintext, randomness)
+        }
+
+        fn add(ek: &EncryptionKey, c1: &BigInt, c2: &BigInt) -> BigInt {
+            KzenPaillier::add(ek, c1, c2)
+        }
+
+        fn mul(ek: &EncryptionKey, c: &BigInt, k: &BigInt) -> BigInt {
+            KzenPaillier::mul(ek, c, k)
+        }
+
+        fn decrypt(
+            dk: &DecryptionKey,
+            ek: &EncryptionKey,
+            c: &BigInt,
+            party_index: u16,
+        ) -> FsDkrResult<BigInt> {
+            BACKEND_DECRYPTIONS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            KzenPaillier::decrypt(dk, ek, c, party_index)
+        }
+
+        fn prove_correct_key(dk: &DecryptionKey) -> NiCorrectKeyProof {
+            KzenPaillier::prove_correct_key(dk)
+        }
+
+        fn verify_correct_key(ek: &EncryptionKey, proof: &NiCorrectKeyProof) -> bool {
+            KzenPaillier::verify_correct_key(ek, proof)
+        }
+    }
+
+    #[test]
+    fn test_paillier_backend() {
+        let n = 3;
+        let mut keys = simulate_keygen(1, n);
+        let old_keys = keys.clone();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| {
+                RefreshMessage::distribute_with_backend::<CountingPaillier>(key.i, key, n).unwrap()
+            })
+            .unzip();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            // the messages of a backend are collected by any other
+            let mut default_key = key.clone();
+            RefreshMessage::collect(&refresh_messages, &mut default_key, new_dk.clone(), &[])
+                .unwrap();
+            RefreshMessage::collect_with_backend::<CountingPaillier>(
+                &refresh_messages,
+                key,
+                new_dk,
+                &[],
+            )
+            .unwrap();
+            assert!(key.public_eq(&default_key));
+            assert_eq!(key.keys_linear.x_i, default_key.keys_linear.x_i);
+        }
+        assert_eq!(
+            BACKEND_DECRYPTIONS.load(std::sync::atomic::Ordering::SeqCst),
+            n as usize
+        );
+        for (old_key, key) in old_keys.iter().zip(keys.iter()) {
+            assert!(refresh_changed_material(old_key, key));
+        }
+        sign_with_quorum(&keys[..2], b"ZenGo").unwrap();
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_collect_parallel() {
+        let n = 3;
+        let mut keys = simulate_keygen(1, n);
+        let (mut refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        for (key, new_dk) in keys.iter().zip(new_dks.iter()) {
+            let mut serial_key = key.clone();
+            RefreshMessage::collect(&refresh_messages, &mut serial_key, new_dk.clone(), &[])
+                .unwrap();
+            let mut parallel_key = key.clone();
+            RefreshMessage::collect_parallel(
+                &refresh_messages,
+                &mut parallel_key,
+                new_dk.clone(),
+                &[],
+            )
+            .unwrap();
+            assert!(parallel_key.public_eq(&serial_key));
+            assert_eq!(parallel_key.keys_linear.x_i, serial_key.keys_linear.x_i);
+        }
+
+        // the key correctness proof of party 2 does not hold for the key of party 1
+        refresh_messages[1].ek = refresh_messages[0].ek.clone();
+        let mut key = keys[0].clone();
+        assert!(matches!(
+            RefreshMessage::collect_parallel(&refresh_messages, &mut key, new_dks[0].clone(), &[]),
+            Err(FsDkrError::PaillierVerificationError { party_index: 2 })
+        ));
+        assert!(key.public_eq(&keys[0]));
+    }
+
+    #[test]
+    fn test_collect_shared_keys() {
+        let n = 3;
+        let mut keys = simulate_keygen(1, n);
+        let old_keys = keys.clone();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let shared_keys =
+                RefreshMessage::collect_shared_keys(&refresh_messages, key, new_dk, &[]).unwrap();
+            assert_eq!(shared_keys.x_i, key.keys_linear.x_i);
+            assert_eq!(shared_keys.y, Point::generator() * &shared_keys.x_i);
+            assert_eq!(shared_keys.y, key.pk_vec[(key.i - 1) as usize]);
+        }
+        for (old_key, key) in old_keys.iter().zip(keys.iter()) {
+            assert_ne!(old_key.keys_linear.x_i, key.keys_linear.x_i);
+        }
+    }
+
+    #[test]
+    fn test_collect_fresh() {
+        let n = 3;
+        let mut keys = simulate_keygen(1, n);
+        let old_keys = keys.clone();
+        let (mut refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let mut guard = FreshnessGuard::new(std::time::Duration::from_secs(60));
+        let mut key = keys[0].clone();
+        assert!(matches!(
+            RefreshMessage::collect_fresh(
+                &refresh_messages,
+                &mut key,
+                new_dks[0].clone(),
+                &[],
+                &mut guard
+            ),
+            Err(FsDkrError::StaleMessage { party_index: 1 })
+        ));
+
+        for refresh_message in refresh_messages.iter_mut() {
+            refresh_message.stamp();
+        }
+        let mut stale = refresh_messages.clone();
+        stale[2].freshness = Some(Freshness {
+            created_at: 0,
+            ..Freshness::now()
+        });
+        assert!(matches!(
+            RefreshMessage::collect_fresh(&stale, &mut key, new_dks[0].clone(), &[], &mut guard),
+            Err(FsDkrError::StaleMessage { party_index: 3 })
+        ));
+
+        RefreshMessage::collect_fresh(
+            &refresh_messages,
+            &mut key,
+            new_dks[0].clone(),
+            &[],
+            &mut guard,
+        )
+        .unwrap();
+        assert!(refresh_changed_material(&old_keys[0], &key));
+
+        // the recorded round can not be replayed
+        let mut replayed = keys[0].clone();
+        assert!(matches!(
+            RefreshMessage::collect_fresh(
+                &refresh_messages,
+                &mut replayed,
+                new_dks[0].clone(),
+                &[],
+                &mut guard
+            ),
+            Err(FsDkrError::ReplayedMessage { party_index: 1 })
+        ));
+        assert!(replayed.public_eq(&keys[0]));
+    }
+
+    #[test]
+    fn test_distribute_with_beacon() {
+        let n = 3;
+        let mut keys = simulate_keygen(1, n);
+        let old_keys = keys.clone();
+        let beacon = b"drand round 1234";
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute_with_beacon(key.i, key, n, 0, beacon).unwrap())
+            .unzip();
+
+        for (refresh_message, old_key) in refresh_messages.iter().zip(old_keys.iter()) {
+            let secret = &old_key.keys_linear.x_i;
+            refresh_message
+                .verify_beacon_coefficients(secret, beacon)
+                .unwrap();
+            assert!(matches!(
+                refresh_message.verify_beacon_coefficients(secret, b"drand round 1235"),
+                Err(FsDkrError::CoefficientCommitmentMismatch { .. })
+            ));
+        }
+        // the coefficients differ per party and per epoch
+        let secret = &old_keys[0].keys_linear.x_i;
+        assert_ne!(
+            RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::beacon_coefficients(
+                secret, 1, 0, 1, beacon
+            ),
+            RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::beacon_coefficients(
+                secret, 1, 1, 1, beacon
+            )
+        );
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &[]).unwrap();
+        }
+        for (old_key, key) in old_keys.iter().zip(keys.iter()) {
+            assert!(refresh_changed_material(old_key, key));
+        }
+        sign_with_quorum(&keys[1..], b"ZenGo").unwrap();
+    }
+
+    #[test]
+    fn test_collect_with_events() {
+        let n = 3;
+        let mut keys = simulate_keygen(1, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let events =
+                RefreshMessage::collect_with_events(&refresh_messages, key, new_dk, &[]).unwrap();
+            assert_eq!(
+                events,
+                (1..=n)
+                    .map(|index| RefreshEvent::PaillierRotated { index })
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_collect_with_log() {
+        let t = 1;
+        let n = 3;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let logs: Vec<CeremonyLog<Secp256k1>> = keys
+            .iter_mut()
+            .zip(new_dks)
+            .map(|(key, new_dk)| {
+                RefreshMessage::collect_with_log(&refresh_messages, key, new_dk, &[]).unwrap()
+            })
+            .collect();
+
+        let log = &logs[0];
+        assert_eq!(log.version, CEREMONY_LOG_VERSION);
+        assert_eq!(log.party_index, 1);
+        assert_eq!(log.quorum, vec![1, 2]);
+        assert!(log.joiners.is_empty());
+        assert!(log.removed.is_empty());
+        assert_eq!((log.t, log.n), (t, n));
+        assert_eq!(log.y_sum_s, keys[0].y_sum_s);
+        assert_eq!(
+            log.refresh_messages
+                .iter()
+                .map(|fingerprint| fingerprint.party_index)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        // the parties record the same ceremony
+        let mut other = logs[1].clone();
+        other.party_index = log.party_index;
+        other.recorded_at = log.recorded_at;
+        assert_eq!(other.digest().unwrap(), log.digest().unwrap());
+
+        let encoded = log.to_bytes().unwrap();
+        assert_eq!(
+            &CeremonyLog::<Secp256k1>::from_bytes(&encoded).unwrap(),
+            log
+        );
+        let secret = serde_json::to_string(&keys[0].keys_linear.x_i).unwrap();
+        assert!(!String::from_utf8(encoded).unwrap().contains(&secret));
+    }
+
+    #[test]
+    fn test_validate_committee_statements() {
+        let keys = simulate_keygen(2, 5);
+        let key = &keys[0];
+        validate_committee_statements(&key.paillier_key_vec, &key.h1_h2_n_tilde_vec, 5).unwrap();
+
+        assert!(matches!(
+            validate_committee_statements(&key.paillier_key_vec, &key.h1_h2_n_tilde_vec[..4], 5),
+            Err(FsDkrError::CommitteeKeysLengthMismatch {
+                expected: 5,
+                paillier_keys: 5,
+                dlog_statements: 4
+            })
+        ));
+
+        let mut statements = key.h1_h2_n_tilde_vec.clone();
+        statements[2].ni = statements[2].N.clone();
+        assert!(matches!(
+            validate_committee_statements(&key.paillier_key_vec, &statements, 5),
+            Err(FsDkrError::DegenerateDLogStatement { party_index: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_collect_ignores_message_order() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+
+        let mut shuffled = refresh_messages.clone();
+        shuffled.reverse();
+        shuffled.rotate_left(2);
+        assert_ne!(
+            shuffled
+                .iter()
+                .map(|refresh_message| refresh_message.party_index)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+
+        for (key, new_dk) in keys.iter().zip(new_dks) {
+            let mut in_order = key.clone();
+            RefreshMessage::collect(&refresh_messages, &mut in_order, new_dk.clone(), &[]).unwrap();
+            let mut out_of_order = key.clone();
+            RefreshMessage::collect(&shuffled, &mut out_of_order, new_dk, &[]).unwrap();
+            assert_eq!(
+                serde_json::to_string(&in_order).unwrap(),
+                serde_json::to_string(&out_of_order).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_for_committee() {
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        let policy = KeyPolicy::default();
+        assert!(matches!(
+            join_message.validate_for_committee(&[1, 2, 3], 4, &policy),
+            Err(FsDkrError::NewPartyUnassignedIndexError)
+        ));
+
+        join_message.set_party_index(4);
+        join_message
+            .validate_for_committee(&[1, 2, 3], 4, &policy)
+            .unwrap();
+        assert!(matches!(
+            join_message.validate_for_committee(&[1, 2, 3], 3, &policy),
+            Err(FsDkrError::PartyIndexOutOfRange { index: 4, n: 3 })
+        ));
+        assert!(matches!(
+            join_message.validate_for_committee(&[1, 2, 4], 4, &policy),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 4 })
+        ));
+
+        let strict = KeyPolicy {
+            min_modulus_bits: crate::PAILLIER_KEY_SIZE + 1,
+            max_modulus_bits: 2 * crate::PAILLIER_KEY_SIZE,
+        };
+        assert!(matches!(
+            join_message.validate_for_committee(&[1, 2, 3], 4, &strict),
+            Err(FsDkrError::ModuliTooSmall { party_index: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_enforce_min_modulus() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (mut join_message, paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(5);
+        let modulus_bits = join_message.modulus_bits();
+        assert!(
+            modulus_bits == crate::PAILLIER_KEY_SIZE - 1
+                || modulus_bits == crate::PAILLIER_KEY_SIZE
+        );
+        join_message
+            .enforce_min_modulus(crate::PAILLIER_KEY_SIZE - 1)
+            .unwrap();
+        assert!(matches!(
+            join_message.enforce_min_modulus(3072),
+            Err(FsDkrError::ModulusBelowMinimum {
+                party_index: 5,
+                min_bits: 3072,
+                ..
+            })
+        ));
+
+        let join_messages = vec![join_message.clone()];
+        let old_to_new_map: HashMap<u16, u16> = (1..5).map(|i| (i, i)).collect();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys[..4]
+            .iter_mut()
+            .map(|key| RefreshMessage::replace(&join_messages, key, &old_to_new_map, n).unwrap())
+            .unzip();
+        assert!(matches!(
+            RefreshMessage::collect_with_min_modulus(
+                &refresh_messages,
+                &mut keys[0].clone(),
+                new_dks[0].clone(),
+                &join_messages,
+                Strictness::Standard,
+                3072,
+            ),
+            Err(FsDkrError::ModulusBelowMinimum { party_index: 5, .. })
+        ));
+        assert!(matches!(
+            join_message.collect_with_min_modulus(
+                &refresh_messages,
+                paillier_keys.clone(),
+                &join_messages,
+                Threshold(t),
+                ShareCount(n),
+                Strictness::Standard,
+                3072,
+            ),
+            Err(FsDkrError::ModulusBelowMinimum { party_index: 5, .. })
+        ));
+
+        for (key, new_dk) in keys[..4].iter_mut().zip(new_dks) {
+            RefreshMessage::collect_with_min_modulus(
+                &refresh_messages,
+                key,
+                new_dk,
+                &join_messages,
+                Strictness::Standard,
+                crate::PAILLIER_KEY_SIZE - 1,
+            )
+            .unwrap();
+        }
+        let new_key = join_message
+            .collect_with_min_modulus(
+                &refresh_messages,
+                paillier_keys,
+                &join_messages,
+                Threshold(t),
+                ShareCount(n),
+                Strictness::Standard,
+                crate::PAILLIER_KEY_SIZE - 1,
+            )
+            .unwrap();
+        assert_eq!(new_key.y_sum_s, keys[0].y_sum_s);
+    }
+
+    #[test]
+    fn test_validate_generated_modulus() {
+        let (join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        crate::validate_generated_modulus(&join_message.ek.n, crate::PAILLIER_KEY_SIZE).unwrap();
+        crate::validate_generated_modulus(&(BigInt::from(1) << 2046), crate::PAILLIER_KEY_SIZE)
+            .unwrap();
+        crate::validate_generated_modulus(&(BigInt::from(1) << 3071), crate::PAILLIER_KEY_SIZE)
+            .unwrap();
+
+        // a modulus with small factors, as produced from a zeroed seed
+        assert!(matches!(
+            crate::validate_generated_modulus(&BigInt::from(15), crate::PAILLIER_KEY_SIZE),
+            Err(FsDkrError::WeakModulusGenerated {
+                bits: 4,
+                expected: 2048
+            })
+        ));
+        assert!(matches!(
+            crate::validate_generated_modulus(&(BigInt::from(1) << 2045), crate::PAILLIER_KEY_SIZE),
+            Err(FsDkrError::WeakModulusGenerated { bits: 2046, .. })
+        ));
+    }
+
+    #[test]
+    fn test_distribute_with_retries() {
+        assert!(matches!(
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::distribute_with_retries(0),
+            Err(FsDkrError::KeyGenerationFailed { attempts: 0 })
+        ));
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute_with_retries(crate::add_party_message::KEYGEN_RETRIES).unwrap();
+        join_message.set_party_index(4);
+        join_message.verify_dlog_proofs().unwrap();
+    }
+
+    #[test]
+    fn test_distribute_with_pool() {
+        let pool = DLogStatementPool::new(1);
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute_with_pool(&pool).unwrap();
+        join_message.set_party_index(6);
+        join_message.verify_dlog_proofs().unwrap();
+
+        // every statement is handed out once
+        let (dlog_statement, _, _) = pool.pop().unwrap();
+        assert_ne!(dlog_statement.N, join_message.dlog_statement.N);
+    }
+
+    #[test]
+    fn test_join_message_index_out_of_range() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (mut join_message, paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(n + 1);
+
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys[..4]
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let join_messages = vec![join_message.clone()];
+
+        assert!(matches!(
+            RefreshMessage::collect(
+                &refresh_messages,
+                &mut keys[0],
+                new_dks[0].clone(),
+                &join_messages
+            ),
+            Err(FsDkrError::PartyIndexOutOfRange { index: 6, n: 5 })
+        ));
+        assert!(matches!(
+            join_message.collect(
+                &refresh_messages,
+                paillier_keys,
+                &join_messages,
+                Threshold(t),
+                ShareCount(n)
+            ),
+            Err(FsDkrError::PartyIndexOutOfRange { index: 6, n: 5 })
+        ));
+    }
+
+    #[test]
+    fn test_collect_rejects_key_of_other_party() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (mut join_message, _paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(5);
+        let (_, other_paillier_keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys[..4]
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let join_messages = vec![join_message.clone()];
+
+        let mut mixed_key = keys[0].clone();
+        mixed_key.paillier_dk = keys[1].paillier_dk.clone();
+        assert!(matches!(
+            RefreshMessage::collect(
+                &refresh_messages,
+                &mut mixed_key,
+                new_dks[0].clone(),
+                &join_messages
+            ),
+            Err(FsDkrError::KeyPartyMismatch { party_index: 1 })
+        ));
+        assert!(matches!(
+            join_message.collect(
+                &refresh_messages,
+                other_paillier_keys,
+                &join_messages,
+                Threshold(t),
+                ShareCount(n)
+            ),
+            Err(FsDkrError::KeyPartyMismatch { party_index: 5 })
+        ));
+    }
+
+    #[test]
+    fn test_extend_pk_vec() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let old_pk_vec = keys[0].pk_vec.clone();
+        let (mut join_message, _paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(3);
+        let join_messages = vec![join_message];
+
+        let old_to_new_map: HashMap<u16, u16> =
+            vec![(1, 1), (2, 2), (4, 4), (5, 5)].into_iter().collect();
+        let mut keys: Vec<_> = keys.drain(..).filter(|key| key.i != 3).collect();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::replace(&join_messages, key, &old_to_new_map, n).unwrap())
+            .unzip();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&refresh_messages, key, new_dk, &join_messages).unwrap();
+        }
+
+        let existing: Vec<Point<Secp256k1>> = keys[0]
+            .pk_vec
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| *position != 2)
+            .map(|(_, public_share)| public_share.clone())
+            .collect();
+        assert_eq!(
+            RefreshMessage::extend_pk_vec(&existing, &refresh_messages, Threshold(t), &[3])
+                .unwrap(),
+            keys[0].pk_vec
+        );
+
+        // a public share of the previous round does not interpolate to the public key with the
+        // ones of this round
+        let mut stale = existing.clone();
+        stale[0] = old_pk_vec[0].clone();
+        assert!(matches!(
+            RefreshMessage::extend_pk_vec(&stale, &refresh_messages, Threshold(t), &[3]),
+            Err(FsDkrError::PublicKeyReconstructionMismatch)
+        ));
+        // a slot above the threshold does not take part in the reconstruction of the public key,
+        // it is checked against the polynomial of the round
+        let mut tampered = existing.clone();
+        tampered[3] = old_pk_vec[4].clone();
+        assert!(matches!(
+            RefreshMessage::extend_pk_vec(&tampered, &refresh_messages, Threshold(t), &[3]),
+            Err(FsDkrError::PublicKeyReconstructionMismatch)
+        ));
+        assert!(matches!(
+            RefreshMessage::extend_pk_vec(&existing[1..], &refresh_messages, Threshold(t), &[3]),
+            Err(FsDkrError::PublicSharesLengthMismatch {
+                expected: 4,
+                got: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_join_collect_with_seed_is_reproducible() {
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (mut join_message, paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(5);
+        let join_messages = vec![join_message.clone()];
+
+        let old_to_new_map: HashMap<u16, u16> = (1..5).map(|i| (i, i)).collect();
+        let refresh_messages: Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>> = keys
+            [..4]
+            .iter_mut()
+            .map(|key| {
+                RefreshMessage::replace(&join_messages, key, &old_to_new_map, n)
+                    .unwrap()
+                    .0
+            })
+            .collect();
+
+        let seed = [7u8; 32];
+        let collect_seeded = |seed: &[u8; 32]| {
+            join_message
+                .collect_with_seed(
+                    &refresh_messages,
+                    paillier_keys.clone(),
+                    &join_messages,
+                    Threshold(t),
+                    ShareCount(n),
+                    seed,
+                )
+                .unwrap()
+        };
+        let first = collect_seeded(&seed);
+        let second = collect_seeded(&seed);
+        assert_eq!(
+            serde_json::to_vec(&first).unwrap(),
+            serde_json::to_vec(&second).unwrap()
+        );
+
+        let other = collect_seeded(&[8u8; 32]);
+        assert_eq!(other.keys_linear.x_i, first.keys_linear.x_i);
+        assert_ne!(other.vss_scheme.commitments, first.vss_scheme.commitments);
+    }
+
+    #[test]
+    fn test_collect_with_external_verifier() {
+        struct InProcess;
+        impl ProofVerifier for InProcess {
+            fn verify(&self, _party_index: u16, item: &ProofItem) -> bool {
+                item.verify()
+            }
+        }
+        struct RejectDLog(u16);
+        impl ProofVerifier for RejectDLog {
+            fn verify(&self, party_index: u16, item: &ProofItem) -> bool {
+                party_index != self.0 || !matches!(item, ProofItem::DLog(..))
+            }
+        }
+
+        let t = 2;
+        let n = 5;
+        let mut keys = simulate_keygen(t, n);
+        let (mut join_message, _paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(5);
+        let join_messages = vec![join_message.clone()];
+
+        let old_to_new_map: HashMap<u16, u16> = (1..5).map(|i| (i, i)).collect();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys[..4]
+            .iter_mut()
+            .map(|key| RefreshMessage::replace(&join_messages, key, &old_to_new_map, n).unwrap())
+            .unzip();
+
+        assert_eq!(join_message.proofs().len(), 3);
+        assert!(join_message.proofs().iter().all(ProofItem::verify));
+        for refresh_message in refresh_messages.iter() {
+            assert_eq!(refresh_message.proofs().len(), 1);
+            assert!(refresh_message.proofs().iter().all(ProofItem::verify));
+        }
+        let encoded = serde_json::to_string(&join_message.proofs()).unwrap();
+        let decoded: Vec<ProofItem> = serde_json::from_str(&encoded).unwrap();
+        assert!(decoded.iter().all(ProofItem::verify));
+
+        let mut rejected_key = keys[0].clone();
+        assert!(matches!(
+            RefreshMessage::collect_with_verifier(
+                &refresh_messages,
+                &mut rejected_key,
+                new_dks[0].clone(),
+                &join_messages,
+                &RejectDLog(5),
+            ),
+            Err(FsDkrError::DLogProofValidation { party_index: 5 })
+        ));
+
+        let mut in_process_key = keys[0].clone();
+        RefreshMessage::collect(
+            &refresh_messages,
+            &mut in_process_key,
+            new_dks[0].clone(),
+            &join_messages,
+        )
+        .unwrap();
+        RefreshMessage::collect_with_verifier(
+            &refresh_messages,
+            &mut keys[0],
+            new_dks[0].clone(),
+            &join_messages,
+            &InProcess,
+        )
+        .unwrap();
+        assert_eq!(keys[0].keys_linear.x_i, in_process_key.keys_linear.x_i);
+        assert_eq!(keys[0].pk_vec, in_process_key.pk_vec);
+    }
+
+    #[test]
+    fn test_reshuffle_changes_membership_and_threshold() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let reshuffle = Reshuffle {
+            add: vec![4, 5, 6, 7],
+            remove: Vec::new(),
+            new_threshold: 3,
+        };
+
+        let mut join_messages: Vec<JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>> =
+            Vec::new();
+        let mut paillier_keys = Vec::new();
+        for index in reshuffle.add.iter() {
+            let (mut join_message, paillier_key) = JoinMessage::distribute().unwrap();
+            join_message.set_party_index(*index);
+            join_messages.push(join_message);
+            paillier_keys.push(paillier_key);
+        }
+
+        let (refresh_messages, new_dks): (Vec<_>, Vec<_>) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::reshuffle(&join_messages, key, &reshuffle).unwrap())
+            .unzip();
+        let mut expected_events: Vec<RefreshEvent> = join_messages
+            .iter()
+            .map(|join_message| RefreshEvent::PartyAdded {
+                index: join_message.get_party_index().unwrap(),
+                ek: join_message.ek.clone(),
+            })
+            .collect();
+        expected_events.push(RefreshEvent::ThresholdChanged { from: 1, to: 3 });
+        expected_events.extend((1..=3).map(|index| RefreshEvent::PaillierRotated { index }));
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            let events = RefreshMessage::collect_reshuffle_with_events(
+                &refresh_messages,
+                key,
+                new_dk,
+                &join_messages,
+                &reshuffle,
+            )
+            .unwrap();
+            assert_eq!(events, expected_events);
+        }
+        for (join_message, paillier_key) in join_messages.iter().zip(paillier_keys) {
+            keys.push(
+                join_message
+                    .collect_reshuffle(
+                        &refresh_messages,
+                        paillier_key,
+                        &join_messages,
+                        &reshuffle,
+                        Threshold(t),
+                        ShareCount(n),
+                    )
+                    .unwrap(),
+            );
+        }
+
+        keys.sort_by_key(|key| key.i);
+        assert!(keys.iter().all(|key| key.t == 3 && key.n == 7));
+        let plan = reshuffle.plan(Threshold(t), ShareCount(n)).unwrap();
+        assert_eq!(
+            plan,
+            RefreshPlan {
+                t: 3,
+                n: 7,
+                active_indices: (1..=7).collect(),
+            }
+        );
+        for key in keys.iter() {
+            plan.matches(key).unwrap();
+        }
+        let lower = RefreshPlan {
+            t: 2,
+            ..plan.clone()
+        };
+        match lower.matches(&keys[0]) {
+            Err(FsDkrError::CommitteeMismatch { expected, actual }) => {
+                assert_eq!(expected, lower);
+                assert_eq!(actual, plan);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        sign_with_quorum(&keys[..4], b"ZenGo").unwrap();
+        let quorum = vec![
+            keys[0].clone(),
+            keys[3].clone(),
+            keys[5].clone(),
+            keys[6].clone(),
+        ];
+        sign_with_quorum(&quorum, b"ZenGo").unwrap();
+
+        let overlapping = Reshuffle {
+            add: vec![2],
+            remove: vec![2],
+            new_threshold: 1,
+        };
+        assert!(matches!(
+            overlapping.validate(Threshold(t), ShareCount(n)),
+            Err(FsDkrError::InvalidReshuffle { .. })
+        ));
+        let no_quorum = Reshuffle {
+            add: Vec::new(),
+            remove: vec![2, 3],
+            new_threshold: 1,
+        };
+        assert!(no_quorum.validate(Threshold(t), ShareCount(n)).is_err());
+    }
+
+    #[test]
+    fn test_removed_shares_are_revoked() {
+        let (t, n) = (2, 5);
+        let old_keys = simulate_keygen(t, n);
+        let removal = Reshuffle {
+            add: Vec::new(),
+            remove: vec![2],
+            new_threshold: 2,
+        };
+
+        let mut keys: Vec<_> = old_keys.iter().filter(|key| key.i != 2).cloned().collect();
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::reshuffle(&[], key, &removal).unwrap())
+            .unzip();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect_reshuffle(&refresh_messages, key, new_dk, &[], &removal)
+                .unwrap();
+        }
+
+        // the old share of the removed party is not a share of the new sharing, and neither is
+        // any other old share: the old and the new polynomial only agree in the secret
+        for key in keys.iter() {
+            verify_removed_shares_revoked(&old_keys[0], key, &[2]).unwrap();
+            verify_removed_shares_revoked(&old_keys[0], key, &[1, 2, 3, 4, 5]).unwrap();
+            assert_eq!(key.y_sum_s, old_keys[0].y_sum_s);
+        }
+        sign_with_quorum(&keys[..3], b"ZenGo").unwrap();
+
+        // without a re-sharing the old shares stay valid
+        assert!(matches!(
+            verify_removed_shares_revoked(&old_keys[0], &old_keys[2], &[2]),
+            Err(FsDkrError::RemovedShareStillValid { party_index: 2 })
+        ));
+        assert!(matches!(
+            verify_removed_shares_revoked(&old_keys[0], &keys[0], &[6]),
+            Err(FsDkrError::PartyIndexOutOfRange { index: 6, n: 5 })
+        ));
+    }
+
+    #[test]
+    fn test_failed_reshuffle_leaves_key_unchanged() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let removal = Reshuffle {
+            add: Vec::new(),
+            remove: vec![3],
+            new_threshold: 1,
+        };
+        keys.truncate(2);
+        let encoded: Vec<Vec<u8>> = keys
+            .iter()
+            .map(|key| serde_json::to_vec(key).unwrap())
+            .collect();
+
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter()
+            .map(|key| RefreshMessage::reshuffle(&[], key, &removal).unwrap())
+            .unzip();
+        for (key, encoded) in keys.iter().zip(encoded.iter()) {
+            assert_eq!(serde_json::to_vec(key).unwrap(), *encoded);
+        }
+
+        // the share party 2 sends to party 1 does not match its proofs
+        let mut tampered_messages = refresh_messages.clone();
+        tampered_messages[1].points_encrypted_vec[0] =
+            tampered_messages[1].points_encrypted_vec[1].clone();
+        assert!(RefreshMessage::collect_reshuffle(
+            &tampered_messages,
+            &mut keys[0],
+            new_dks[0].clone(),
+            &[],
+            &removal,
+        )
+        .is_err());
+        assert_eq!(serde_json::to_vec(&keys[0]).unwrap(), encoded[0]);
+
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect_reshuffle(&refresh_messages, key, new_dk, &[], &removal)
+                .unwrap();
+        }
+        assert!(keys.iter().all(|key| key.t == 1 && key.n == 2));
+        sign_with_quorum(&keys, b"ZenGo").unwrap();
+    }
+
+    #[test]
+    fn test_free_slots() {
+        assert_eq!(free_slots(&[4, 1, 2], 6), vec![3, 5, 6]);
+        assert_eq!(free_slots(&[1, 1, 7], 3), vec![2, 3]);
+        assert_eq!(free_slots(&[], 2), vec![1, 2]);
+        assert!(free_slots(&[1, 2, 3], 3).is_empty());
+    }
+
+    #[test]
+    fn test_reshuffle_rejects_unreachable_quorum() {
+        let over_removal = Reshuffle {
+            add: Vec::new(),
+            remove: vec![2, 3],
+            new_threshold: 1,
+        };
+        assert!(matches!(
+            over_removal.validate(Threshold(1), ShareCount(3)),
+            Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining: 1,
+                threshold: 1
+            })
+        ));
+
+        // removing more parties than the committee has does not underflow
+        assert!(matches!(
+            over_removal.active_after_removal(1),
+            Err(FsDkrError::InvalidReshuffle { .. })
+        ));
+        assert!(matches!(
+            over_removal.new_n(1),
+            Err(FsDkrError::InvalidReshuffle { .. })
+        ));
+        assert_eq!(over_removal.active_after_removal(3).unwrap(), 1);
+
+        // removing one party is fine at the current threshold but not at a raised one
+        let removal = Reshuffle {
+            add: Vec::new(),
+            remove: vec![3],
+            new_threshold: 1,
+        };
+        removal.validate(Threshold(1), ShareCount(3)).unwrap();
+        let raised = Reshuffle {
+            new_threshold: 2,
+            ..removal
+        };
+        assert!(matches!(
+            raised.validate(Threshold(1), ShareCount(3)),
+            Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining: 2,
+                threshold: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_required_online_for_refresh() {
+        let (t, n) = (1, 3);
+        let keys = simulate_keygen(t, n);
+        assert_eq!(required_online_for_refresh(&keys[0]), 2);
+
+        let removal = Reshuffle {
+            add: vec![3],
+            remove: vec![3],
+            new_threshold: 1,
+        };
+        assert!(matches!(
+            required_online_for_reshuffle(&keys[0], &removal, &[1, 2]),
+            Err(FsDkrError::InvalidReshuffle { .. })
+        ));
+
+        let removal = Reshuffle {
+            add: Vec::new(),
+            remove: vec![3],
+            new_threshold: 1,
+        };
+        assert_eq!(
+            required_online_for_reshuffle(&keys[0], &removal, &[1, 2]).unwrap(),
+            2
+        );
+        // the removed party, an index outside of the committee and a repeated index do not count
+        assert!(matches!(
+            required_online_for_reshuffle(&keys[0], &removal, &[1, 1, 3, 4]),
+            Err(FsDkrError::QuorumWouldBeUnreachable {
+                remaining: 1,
+                threshold: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_verify_relay_integrity() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let signing_keys: Vec<Scalar<Secp256k1>> = (0..n).map(|_| Scalar::random()).collect();
+        let registry: HashMap<u16, Point<Secp256k1>> = keys
+            .iter()
+            .zip(signing_keys.iter())
+            .map(|(key, signing_key)| (key.i, Point::generator() * signing_key))
+            .collect();
+
+        let (refresh_messages, new_dks): (
+            Vec<RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>>,
+            Vec<DecryptionKey>,
+        ) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::distribute(key.i, key, n).unwrap())
+            .unzip();
+        let signed: Vec<_> = refresh_messages
+            .iter()
+            .zip(signing_keys.iter())
+            .map(|(refresh_message, signing_key)| refresh_message.sign(signing_key).unwrap())
+            .collect();
+
+        // the relay swaps two encrypted shares of the first message
+        let mut value = serde_json::to_value(&signed[0]).unwrap();
+        let encrypted = value["message"]["points_encrypted_vec"].clone();
+        value["message"]["points_encrypted_vec"][1] = encrypted[2].clone();
+        value["message"]["points_encrypted_vec"][2] = encrypted[1].clone();
+        let mut altered = signed.clone();
+        altered[0] = serde_json::from_value(value).unwrap();
+        assert!(matches!(
+            verify_relay_integrity(&altered, &[], &registry),
+            Err(FsDkrError::MessageSignatureInvalid { party_index: 1 })
+        ));
+
+        // the relay signs a message itself
+        let mut resigned = signed.clone();
+        resigned[1] = refresh_messages[1].sign(&Scalar::random()).unwrap();
+        assert!(matches!(
+            verify_relay_integrity(&resigned, &[], &registry),
+            Err(FsDkrError::MessageSignatureInvalid { party_index: 2 })
+        ));
+
+        let mut duplicated = signed.clone();
+        duplicated.push(signed[0].clone());
+        assert!(matches!(
+            verify_relay_integrity(&duplicated, &[], &registry),
+            Err(FsDkrError::DuplicatePartyIndex { party_index: 1 })
+        ));
+
+        let (relayed, join_messages) = verify_relay_integrity(&signed, &[], &registry).unwrap();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect(&relayed, key, new_dk, &join_messages).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_collect_signed_join_messages() {
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        keys.truncate(2);
+        let old_to_new_map: HashMap<u16, u16> = keys.iter().map(|key| (key.i, key.i)).collect();
+
+        let signing_key = Scalar::<Secp256k1>::random();
+        let registry: HashMap<u16, Point<Secp256k1>> = vec![(3, Point::generator() * &signing_key)]
+            .into_iter()
+            .collect();
+        let (mut join_message, paillier_keys): (
+            JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            _,
+        ) = JoinMessage::distribute().unwrap();
+        join_message.set_party_index(3);
+        let signed_join_messages = vec![join_message.sign(&signing_key).unwrap()];
+
+        let forged = vec![join_message.sign(&Scalar::random()).unwrap()];
+        assert!(matches!(
+            SignedJoinMessage::verify_all(&forged, &registry),
+            Err(FsDkrError::MessageSignatureInvalid { party_index: 3 })
+        ));
+        assert!(matches!(
+            SignedJoinMessage::verify_all(&signed_join_messages, &HashMap::new()),
+            Err(FsDkrError::UnknownSigner { party_index: 3 })
+        ));
+
+        let join_messages = vec![join_message.clone()];
+        let (refresh_messages, new_dks): (Vec<_>, Vec<_>) = keys
+            .iter_mut()
+            .map(|key| RefreshMessage::replace(&join_messages, key, &old_to_new_map, n).unwrap())
+            .unzip();
+        for (key, new_dk) in keys.iter_mut().zip(new_dks) {
+            RefreshMessage::collect_signed(
+                &refresh_messages,
+                key,
+                new_dk,
+                &signed_join_messages,
+                &registry,
+            )
+            .unwrap();
+        }
+        let new_key = join_message
+            .collect_signed(
+                &refresh_messages,
+                paillier_keys,
+                &signed_join_messages,
+                &registry,
+                Threshold(t),
+                ShareCount(n),
+            )
+            .unwrap();
+        assert!(new_key.same_group_as(&keys[0]));
+    }
+
+    #[test]
+    fn test_verify_paillier_key() {
+        let (candidate, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        verify_paillier_key(&candidate.ek, &candidate.dk_correctness_proof).unwrap();
+
+        let (other, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        assert!(matches!(
+            verify_paillier_key(&candidate.ek, &other.dk_correctness_proof),
+            Err(FsDkrError::PaillierVerificationError { party_index: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_join_message_redacted() {
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        join_message.set_party_index(6);
+
+        let summary = join_message.redacted();
+        assert_eq!(summary.party_index, Some(6));
+        assert!(summary.ek_bit_length >= crate::PAILLIER_KEY_SIZE - 1);
+        assert_eq!(summary.dk_correctness_proof_hash.len(), 16);
+        assert_eq!(summary, join_message.clone().redacted());
+
+        let (other, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        assert_ne!(
+            other.redacted().ring_pedersen_proof_hash,
+            summary.ring_pedersen_proof_hash
+        );
+    }
+
+    #[test]
+    fn test_join_message_version() {
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        assert_eq!(join_message.version, JOIN_MESSAGE_VERSION);
+
+        join_message.version = JOIN_MESSAGE_VERSION + 1;
+        let bytes = join_message.to_bytes().unwrap();
+        assert!(matches!(
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::from_bytes(&bytes),
+            Err(FsDkrError::UnsupportedMessageVersion { found, supported })
+                if found == JOIN_MESSAGE_VERSION + 1 && supported == JOIN_MESSAGE_VERSION
+        ));
+    }
+
+    #[test]
+    fn test_join_message_from_bytes() {
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        join_message.set_party_index(6);
+
+        let bytes = join_message.to_bytes().unwrap();
+        let parsed =
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes().unwrap(), bytes);
+
+        // a leading zero in a big integer is not the canonical encoding
+        let n_hex = join_message.ek.n.to_hex();
+        let padded =
+            String::from_utf8(bytes.clone())
+                .unwrap()
+                .replacen(&n_hex, &format!("00{}", n_hex), 1);
+        assert!(matches!(
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::from_bytes(padded.as_bytes()),
+            Err(FsDkrError::NonCanonicalEncoding { .. })
+        ));
+
+        // a negative group element is rejected even when canonically encoded
+        join_message.dlog_statement.g = BigInt::from(0) - &join_message.dlog_statement.g;
+        let negative = join_message.to_bytes().unwrap();
+        assert!(matches!(
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::from_bytes(&negative),
+            Err(FsDkrError::NonCanonicalEncoding { .. })
+        ));
+    }
+
+    #[test]
+    fn test_serialized_size_hint() {
+        let (mut join_message, _keys): (JoinMessage<Secp256k1, Sha256, { crate::M_SECURITY }>, _) =
+            JoinMessage::distribute().unwrap();
+        join_message.set_party_index(4);
+        let join_len = join_message.to_bytes().unwrap().len();
+        let join_hint =
+            JoinMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::serialized_size_hint(
+                crate::PAILLIER_KEY_SIZE,
+            );
+        assert!(join_hint >= join_len);
+        assert!(join_hint < 2 * join_len);
+
+        let (t, n) = (1, 3);
+        let mut keys = simulate_keygen(t, n);
+        let (refresh_message, _new_dk): (
+            RefreshMessage<Secp256k1, Sha256, { crate::M_SECURITY }>,
+            DecryptionKey,
+        ) = RefreshMessage::distribute(keys[0].i, &mut keys[0], n).unwrap();
+        let refresh_len = serde_json::to_vec(&refresh_message).unwrap().len();
+        let refresh_hint =
+            RefreshMessage::<Secp256k1, Sha256, { crate::M_SECURITY }>::serialized_size_hint(
+                crate::PAILLIER_KEY_SIZE,
+                n,
+            );
+        assert!(refresh_hint >= refresh_len);
+        assert!(refresh_hint < 2 * refresh_len);
+    }
+
+    #[test]
+    fn test_threshold_and_share_count() {
+        let t = Threshold::from(2);
+        let n = ShareCount::from(5);
+        assert_eq!(*t, 2);
+        assert_eq!(u16::from(n), 5);
+        assert_eq!(serde_json::to_string(&t).unwrap(), "2");
+        assert_eq!(serde_json::from_str::<ShareCount>("5").unwrap(), n);
+        assert_eq!(
+            estimate_work(t, n, 0),
+            estimate_work(Threshold(2), ShareCount(5), 0)
+        );
+    }
+
+    #[test]
+    fn test_derive_party_indices() {
+        let n = 7;
+        let identities: Vec<Vec<u8>> = (0..n as u8).map(|i| vec![i; 33]).collect();
+        let indices = derive_party_indices(&identities, n).unwrap();
+        let distinct: HashSet<usize> = indices.iter().cloned().collect();
+        assert_eq!(distinct.len(), n);
+        assert!(indices.iter().all(|index| (1..=n).contains(index)));
+
+        // every party derives the same indices whatever the order of the identities
+        let mut reversed = identities.clone();
+        reversed.reverse();
+        let mut reversed_indices = derive_party_indices(&reversed, n).unwrap();
+        reversed_indices.reverse();
+        assert_eq!(reversed_indices, indices);
+
+        // two identities preferring the same index: the smaller one keeps it, the other one
+        // probes to the next index, wrapping from n back to 1
+        let first = vec![0u8; 33];
+        let preferred = derive_party_index(&first, n);
+        let second = (1..=u8::MAX)
+            .map(|i| vec![i; 33])
+            .find(|identity| derive_party_index(identity, n) == preferred)
+            .unwrap();
+        assert_eq!(
+            derive_party_indices(&[second.clone(), first.clone()], n).unwrap(),
+            vec![preferred % n + 1, preferred]
+        );
+
+        assert!(matches!(
+            derive_party_indices(&[first.clone(), first.clone()], n),
+            Err(FsDkrError::InvalidIdentitySet { .. })
+        ));
+        assert!(matches!(
+            derive_party_indices(&[first, second], 1),
+            Err(FsDkrError::InvalidIdentitySet { .. })
+        ));
+    }
+
     #[test]
     fn test_sign_rotate_sign() {
         let mut keys = simulate_keygen(2, 5);
@@ -106,7 +4186,7 @@ mod tests {
             ) -> (Vec<JoinMessage<Secp256k1, Sha256, M>>, Vec<Keys>) {
                 // the new party generates it's join message to start joining the computation
                 (0..number_of_new_parties)
-                    .map(|_| JoinMessage::distribute())
+                    .map(|_| JoinMessage::distribute().unwrap())
                     .unzip()
             }
 
@@ -160,8 +4240,8 @@ mod tests {
                     refresh_messages.as_slice(),
                     dk,
                     join_messages.as_slice(),
-                    t,
-                    n,
+                    Threshold(t),
+                    ShareCount(n),
                 )?;
 
                 new_keys_vec.push((party_index - 1, local_key));
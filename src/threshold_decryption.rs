@@ -0,0 +1,262 @@
+//! Decryption of the ciphertext sum by a quorum of key holders instead of a single party.
+//!
+//! In [RefreshMessage::collect](crate::refresh_message::RefreshMessage::collect) the party
+//! decrypts the homomorphic sum of the ciphertexts it received with its own Paillier decryption
+//! key. A deployment that does not want any single machine to hold a complete decryption key can
+//! split it with [share_decryption_key] into `n` [DecryptionKeyShare]s, any `t + 1` of which
+//! decrypt together: each holder turns the ciphertext sum, see
+//! [RefreshMessage::ciphertext_sum](crate::refresh_message::RefreshMessage::ciphertext_sum), into
+//! a [PartialDecryption], and [combine_partial_decryptions] recovers the plaintext from `t + 1`
+//! of them. Reduced modulo the curve order, the plaintext is the new share collect would have
+//! decrypted, and [RefreshMessage::collect_with_plaintext_share] collects the round with it
+//! instead of decrypting, so that the party never holds the decryption key of its slot.
+//!
+//! The scheme is the threshold Paillier decryption of Shoup and Damgard-Jurik. The decryption
+//! exponent `d`, with `d = 0 mod phi(N)` and `d = 1 mod N`, is Shamir shared over the integers
+//! modulo `N * phi(N)`. A partial decryption is `c^s_i mod N^2`, and the partial decryptions of
+//! a quorum combine with the Lagrange coefficients scaled by `delta = n!`, which are integers,
+//! into `c^(delta * d) = 1 + delta * m * N mod N^2`.
+//!
+//! The security model differs from single key decryption:
+//! * [share_decryption_key] is run by a dealer that holds the complete key once, e.g. the party
+//!   right after distribute, which must erase the key after handing out the shares. Up to `t`
+//!   colluding holders learn nothing about the key, `t + 1` of them can decrypt everything that
+//!   was encrypted to it.
+//! * A partial decryption carries no proof that it was computed with the holder's share. A
+//!   wrong partial decryption, whether faulty or malicious, yields a wrong plaintext without
+//!   identifying its sender. [RefreshMessage::collect_with_plaintext_share] checks the
+//!   resulting share against the public share the quorum committed to and fails the collect
+//!   with [FsDkrError::PublicShareValidationError] if they differ.
+//! * The holders see the ciphertext sum, not the plaintext, unless they are the ones combining.
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::paillier_backend::KzenPaillier;
+use crate::params::{ShareCount, Threshold};
+use crate::refresh_message::{MessageProofs, RefreshMessage, ShareDecryption};
+use crate::strictness::Strictness;
+use curv::arithmetic::{Modulo, One, Samplable, Zero};
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::elliptic::curves::Curve;
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{DecryptionKey, EncryptionKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use zeroize::Zeroize;
+
+/// The share of a Paillier decryption key held by one of `n` holders, see the
+/// [module level](self) documentation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DecryptionKeyShare {
+    /// Index of the holder, in `1..=n`.
+    pub party_index: u16,
+    pub t: u16,
+    pub n: u16,
+    /// `N^2` of the shared key.
+    nn: BigInt,
+    share: BigInt,
+}
+
+/// A holder's contribution to the decryption of a ciphertext, see
+/// [DecryptionKeyShare::partial_decrypt].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartialDecryption {
+    pub party_index: u16,
+    pub value: BigInt,
+}
+
+/// Splits the Paillier decryption key `dk` into `n` shares, any `t + 1` of which decrypt
+/// together, see the [module level](self) documentation. Fails with
+/// [FsDkrError::QuorumWouldBeUnreachable] if `t + 1` exceeds `n` and with
+/// [FsDkrError::InvalidDecryptionKey] if `N` is not invertible modulo `phi(N)`.
+pub fn share_decryption_key(
+    dk: &DecryptionKey,
+    Threshold(t): Threshold,
+    ShareCount(n): ShareCount,
+) -> FsDkrResult<Vec<DecryptionKeyShare>> {
+    crate::validate_committee_size(n as usize)?;
+    if t >= n {
+        return Err(FsDkrError::QuorumWouldBeUnreachable {
+            remaining: n,
+            threshold: t,
+        });
+    }
+
+    let modulus = &dk.p * &dk.q;
+    let nn = &modulus * &modulus;
+    let mut phi = (&dk.p - BigInt::one()) * (&dk.q - BigInt::one());
+    let mut phi_inv = match BigInt::mod_inv(&phi, &modulus) {
+        Some(phi_inv) => phi_inv,
+        None => {
+            phi.zeroize();
+            return Err(FsDkrError::InvalidDecryptionKey);
+        }
+    };
+    let mut exponent_modulus = &modulus * &phi;
+    // d = 0 mod phi(N) and d = 1 mod N
+    let mut coefficients = vec![&phi * &phi_inv];
+    coefficients.extend((0..t).map(|_| BigInt::sample_below(&exponent_modulus)));
+
+    let shares = (1..=n)
+        .map(|party_index| {
+            let x = BigInt::from(party_index as u64);
+            // Horner evaluation of the polynomial at x
+            let share = coefficients.iter().rev().fold(BigInt::zero(), |acc, a| {
+                BigInt::mod_add(
+                    &BigInt::mod_mul(&acc, &x, &exponent_modulus),
+                    a,
+                    &exponent_modulus,
+                )
+            });
+            DecryptionKeyShare {
+                party_index,
+                t,
+                n,
+                nn: nn.clone(),
+                share,
+            }
+        })
+        .collect();
+
+    phi.zeroize();
+    phi_inv.zeroize();
+    exponent_modulus.zeroize();
+    coefficients
+        .iter_mut()
+        .for_each(|coefficient| coefficient.zeroize());
+    Ok(shares)
+}
+
+impl DecryptionKeyShare {
+    /// The holder's partial decryption of `ciphertext`, an encryption under the shared key.
+    pub fn partial_decrypt(&self, ciphertext: &BigInt) -> PartialDecryption {
+        PartialDecryption {
+            party_index: self.party_index,
+            value: BigInt::mod_pow(ciphertext, &self.share, &self.nn),
+        }
+    }
+}
+
+impl Drop for DecryptionKeyShare {
+    fn drop(&mut self) {
+        self.share.zeroize();
+    }
+}
+
+/// Recovers the plaintext of a ciphertext under `ek` from the partial decryptions of `t + 1` of
+/// the `n` holders of its decryption key, see the [module level](self) documentation. Extra
+/// partial decryptions are ignored. Fails with [FsDkrError::PartialDecryptionsBelowThreshold] if
+/// there are not enough of them, with [FsDkrError::PartyIndexOutOfRange] for a holder outside of
+/// `1..=n` and with [FsDkrError::DuplicatePartyIndex] for a holder that contributed twice.
+pub fn combine_partial_decryptions(
+    partials: &[PartialDecryption],
+    ek: &EncryptionKey,
+    Threshold(t): Threshold,
+    ShareCount(n): ShareCount,
+) -> FsDkrResult<BigInt> {
+    crate::validate_committee_size(n as usize)?;
+    if partials.len() <= t as usize {
+        return Err(FsDkrError::PartialDecryptionsBelowThreshold {
+            threshold: t,
+            received: partials.len(),
+        });
+    }
+    let mut seen_indices = HashSet::new();
+    for partial in partials.iter() {
+        if partial.party_index == 0 || partial.party_index > n {
+            return Err(FsDkrError::PartyIndexOutOfRange {
+                index: partial.party_index,
+                n,
+            });
+        }
+        if !seen_indices.insert(partial.party_index) {
+            return Err(FsDkrError::DuplicatePartyIndex {
+                party_index: partial.party_index,
+            });
+        }
+    }
+
+    let quorum = &partials[..t as usize + 1];
+    let delta = (1..=n as u64).fold(BigInt::one(), |acc, i| acc * BigInt::from(i));
+    let mut combined = BigInt::one();
+    for partial in quorum.iter() {
+        let (coefficient, negative) = scaled_lagrange_coefficient(quorum, partial, &delta);
+        let base = if negative {
+            match BigInt::mod_inv(&partial.value, &ek.nn) {
+                Some(inverse) => inverse,
+                None => {
+                    return Err(FsDkrError::DecryptionVerificationFailed {
+                        party_index: partial.party_index,
+                    })
+                }
+            }
+        } else {
+            partial.value.clone()
+        };
+        combined = BigInt::mod_mul(
+            &combined,
+            &BigInt::mod_pow(&base, &coefficient, &ek.nn),
+            &ek.nn,
+        );
+    }
+
+    // combined = 1 + delta * m * N mod N^2
+    let delta_inv = BigInt::mod_inv(&delta, &ek.n).ok_or(FsDkrError::InvalidDecryptionKey)?;
+    let delta_m = (combined - BigInt::one()) / &ek.n;
+    Ok(BigInt::mod_mul(&delta_m, &delta_inv, &ek.n))
+}
+
+impl<E: Curve, H: Digest + Clone, const M: usize> RefreshMessage<E, H, M> {
+    /// Same as [RefreshMessage::collect] with the ciphertext sum decrypted outside of the
+    /// collect, see the [module level](self) documentation. `plaintext` is the result of
+    /// [combine_partial_decryptions] for the
+    /// [ciphertext_sum](RefreshMessage::ciphertext_sum) of the round, the `paillier_dk` of
+    /// `local_key` is not used. The new share is checked against the points the quorum committed
+    /// to for the slot, which fails with [FsDkrError::PublicShareValidationError] if a partial
+    /// decryption was wrong.
+    pub fn collect_with_plaintext_share(
+        refresh_messages: &[Self],
+        local_key: &mut LocalKey<E>,
+        new_dk: DecryptionKey,
+        join_messages: &[JoinMessage<E, H, M>],
+        plaintext: &BigInt,
+    ) -> FsDkrResult<()> {
+        RefreshMessage::collect_returning_sum::<KzenPaillier>(
+            refresh_messages,
+            local_key,
+            new_dk,
+            join_messages,
+            Strictness::default(),
+            ShareDecryption::Plaintext(plaintext),
+            MessageProofs::All,
+            &mut |_| {},
+        )
+        .map(|_| ())
+    }
+}
+
+// the absolute value and the sign of delta times the lagrange coefficient of `partial` for the
+// evaluation at 0 of the polynomial through the holders of `quorum`, an integer
+fn scaled_lagrange_coefficient(
+    quorum: &[PartialDecryption],
+    partial: &PartialDecryption,
+    delta: &BigInt,
+) -> (BigInt, bool) {
+    let i = partial.party_index as i64;
+    let (num, den, negative) = quorum
+        .iter()
+        .map(|other| other.party_index as i64)
+        .filter(|j| *j != i)
+        .fold(
+            (delta.clone(), BigInt::one(), false),
+            |(num, den, negative), j| {
+                (
+                    num * BigInt::from(j as u64),
+                    den * BigInt::from((j - i).unsigned_abs()),
+                    negative ^ (j < i),
+                )
+            },
+        );
+    (num / den, negative)
+}
@@ -9,6 +9,7 @@
 
 use std::marker::PhantomData;
 
+use crate::entropy::Entropy;
 use crate::error::{FsDkrError, FsDkrResult};
 use curv::arithmetic::traits::*;
 use curv::cryptographic_primitives::hashing::{Digest, DigestExt};
@@ -51,15 +52,25 @@ pub struct PDLwSlackProof<E: Curve, H: Digest + Clone> {
 
 impl<E: Curve, H: Digest + Clone> PDLwSlackProof<E, H> {
     pub fn prove(witness: &PDLwSlackWitness<E>, statement: &PDLwSlackStatement<E>) -> Self {
+        Self::prove_with(witness, statement, &mut Entropy::Os)
+            .expect("the operating system generator does not fail")
+    }
+
+    /// Same as [PDLwSlackProof::prove], drawing the nonces from `entropy`.
+    pub(crate) fn prove_with(
+        witness: &PDLwSlackWitness<E>,
+        statement: &PDLwSlackStatement<E>,
+        entropy: &mut Entropy,
+    ) -> FsDkrResult<Self> {
         let q3 = Scalar::<E>::group_order().pow(3);
         let q_N_tilde = Scalar::<E>::group_order() * &statement.N_tilde;
         let q3_N_tilde = &q3 * &statement.N_tilde;
 
-        let alpha = BigInt::sample_below(&q3);
+        let alpha = entropy.sample_below(&q3)?;
         let one = BigInt::one();
-        let beta = BigInt::sample_range(&one, &(&statement.ek.n - &one));
-        let rho = BigInt::sample_below(&q_N_tilde);
-        let gamma = BigInt::sample_below(&q3_N_tilde);
+        let beta = entropy.sample_range(&one, &(&statement.ek.n - &one))?;
+        let rho = entropy.sample_below(&q_N_tilde)?;
+        let gamma = entropy.sample_below(&q3_N_tilde)?;
 
         let z = commitment_unknown_order(
             &statement.h1,
@@ -98,7 +109,7 @@ impl<E: Curve, H: Digest + Clone> PDLwSlackProof<E, H> {
         let s2 = commitment_unknown_order(&witness.r, &beta, &statement.ek.n, &e, &BigInt::one());
         let s3 = &e * rho + gamma;
 
-        PDLwSlackProof {
+        Ok(PDLwSlackProof {
             z,
             u1,
             u2,
@@ -107,7 +118,7 @@ impl<E: Curve, H: Digest + Clone> PDLwSlackProof<E, H> {
             s2,
             s3,
             _phantom: PhantomData,
-        }
+        })
     }
 
     pub fn verify(&self, statement: &PDLwSlackStatement<E>) -> FsDkrResult<()> {